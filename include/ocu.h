@@ -0,0 +1,42 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/* C interface of opencode-usage-companion, built with the `ffi` feature */
+
+#ifndef OCU_H
+#define OCU_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Fetch every configured provider; returns the `ocu -f json` document or NULL */
+char *ocu_fetch_json(uint32_t timeout_secs);
+
+/* Fetch every configured provider; returns the uncolored `--summary` line or NULL */
+char *ocu_fetch_summary(uint32_t timeout_secs);
+
+/* Free a string returned by ocu_fetch_json or ocu_fetch_summary */
+void ocu_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OCU_H */
@@ -15,13 +15,16 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::error::{QuotaError, Result};
 
 /// OpenCode Auth structure for ~/.local/share/opencode/auth.json
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct OpenCodeAuth {
     #[serde(rename = "google")]
     pub google: Option<OAuthToken>,
@@ -46,6 +49,27 @@ pub struct OAuthToken {
     pub account_id: Option<String>,
 }
 
+impl OAuthToken {
+    /// When the access token expires
+    /// `expires` is a Unix timestamp in milliseconds; zero or missing means no known expiry
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires
+            .filter(|&expires| expires > 0)
+            .and_then(DateTime::from_timestamp_millis)
+    }
+
+    /// Whether the access token has expired (or expires within the next minute)
+    pub fn is_expired(&self) -> bool {
+        self.expires_at()
+            .is_some_and(|expires| expires <= Utc::now() + chrono::Duration::minutes(1))
+    }
+
+    /// Whether a refresh token is stored alongside the access token
+    pub fn can_refresh(&self) -> bool {
+        self.refresh.as_deref().is_some_and(|r| !r.is_empty())
+    }
+}
+
 /// Antigravity Accounts structure for antigravity-accounts.json
 /// On Windows: %APPDATA%/opencode/antigravity-accounts.json
 /// On macOS/Linux: ~/.config/opencode/antigravity-accounts.json
@@ -59,6 +83,18 @@ pub struct AntigravityAccounts {
     pub active_index_by_family: Option<std::collections::HashMap<String, usize>>,
 }
 
+impl AntigravityAccounts {
+    /// A file with one account, active for every model family
+    pub fn single(account: AntigravityAccount) -> Self {
+        Self {
+            version: 1,
+            accounts: vec![account],
+            active_index: 0,
+            active_index_by_family: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AntigravityAccount {
     pub email: String,
@@ -88,6 +124,120 @@ pub struct GeminiTokenResponse {
     pub token_type: Option<String>,
 }
 
+/// Credential state of one provider, for `ocu auth status`
+#[derive(Debug, Clone)]
+pub struct AuthStatus {
+    pub provider: &'static str,
+    pub configured: bool,
+    /// When the stored access token expires, if known
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether an expired token can be renewed without logging in again
+    pub can_refresh: bool,
+    /// Number of Antigravity accounts (Gemini only)
+    pub accounts: Option<usize>,
+}
+
+impl AuthStatus {
+    /// Expired and not renewable: the provider fails until the user logs in again
+    pub fn needs_login(&self) -> bool {
+        !self.configured || (self.expires_at.is_some_and(|t| t <= Utc::now()) && !self.can_refresh)
+    }
+}
+
+/// Where providers get their credentials
+/// `AuthManager` reads OpenCode's files; `EnvAuth` and `MemoryAuth` let library users supply
+/// tokens programmatically, and `KeyringAuth` (`keyring` feature) reads them from the OS keyring
+pub trait AuthSource: Send + Sync {
+    /// OpenCode-style tokens for Claude, Codex and Copilot, or None when there are none
+    fn opencode_auth(&self) -> Result<Option<OpenCodeAuth>>;
+
+    /// Antigravity accounts for Gemini, or None when there are none
+    fn antigravity_accounts(&self) -> Result<Option<AntigravityAccounts>>;
+
+    /// Keep a refreshed token under its OpenCode key ("anthropic", "openai", ...)
+    /// Sources that can't store tokens may ignore it; the provider then refreshes again next time
+    fn save_token(&self, key: &str, token: &OAuthToken) -> Result<()>;
+
+    /// Check if a specific provider is configured
+    /// Each auth method is checked independently so that if one fails, we still check the others
+    fn is_provider_configured(&self, provider: &str) -> Result<bool> {
+        // Each provider only looks at its own auth file. A file that exists but doesn't parse
+        // counts as configured, so fetching reports the file and offset instead of the
+        // provider silently disappearing
+        match provider {
+            "gemini" => {
+                // Only check for antigravity-accounts.json - Google OAuth alone is not sufficient
+                // since the Gemini provider only supports Antigravity accounts
+                match self.antigravity_accounts() {
+                    Ok(accounts) => Ok(accounts.is_some()),
+                    Err(QuotaError::AuthFileInvalid { .. }) => Ok(true),
+                    Err(_) => Ok(false),
+                }
+            }
+            "claude" | "codex" | "copilot" => match self.opencode_auth() {
+                Ok(Some(auth)) => Ok(match provider {
+                    "claude" => auth.anthropic.is_some(),
+                    "codex" => auth.openai.is_some(),
+                    _ => auth.github_copilot.is_some(),
+                }),
+                Err(QuotaError::AuthFileInvalid { .. }) => Ok(true),
+                _ => Ok(false),
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// Credential state of every provider, read from the auth files only (no network I/O)
+    fn auth_status(&self) -> Vec<AuthStatus> {
+        let opencode_auth = self.opencode_auth().ok().flatten();
+        let antigravity_accounts = self.antigravity_accounts().ok().flatten();
+
+        let token_status = |provider: &'static str, token: Option<&OAuthToken>| AuthStatus {
+            provider,
+            configured: token.is_some(),
+            expires_at: token.and_then(|t| t.expires_at()),
+            can_refresh: token.is_some_and(|t| t.can_refresh()),
+            accounts: None,
+        };
+
+        let auth = opencode_auth.as_ref();
+        vec![
+            AuthStatus {
+                provider: "gemini",
+                configured: antigravity_accounts.is_some(),
+                expires_at: None,
+                // Antigravity accounts only store refresh tokens
+                can_refresh: true,
+                accounts: antigravity_accounts.as_ref().map(|a| a.accounts.len()),
+            },
+            token_status("codex", auth.and_then(|a| a.openai.as_ref())),
+            token_status("copilot", auth.and_then(|a| a.github_copilot.as_ref())),
+            token_status("claude", auth.and_then(|a| a.anthropic.as_ref())),
+        ]
+    }
+
+    /// Get list of configured providers
+    fn get_configured_providers(&self) -> Result<Vec<String>> {
+        let mut providers = Vec::new();
+
+        if self.is_provider_configured("gemini")? {
+            providers.push("gemini".to_string());
+        }
+        if self.is_provider_configured("claude")? {
+            providers.push("claude".to_string());
+        }
+        if self.is_provider_configured("codex")? {
+            providers.push("codex".to_string());
+        }
+        if self.is_provider_configured("copilot")? {
+            providers.push("copilot".to_string());
+        }
+
+        Ok(providers)
+    }
+}
+
+/// Credentials read from OpenCode's auth files
 pub struct AuthManager;
 
 impl AuthManager {
@@ -96,47 +246,80 @@ impl AuthManager {
     }
 
     /// Get path to OpenCode auth file
+    /// The first existing candidate wins; without one, the XDG location OpenCode uses by default
     fn get_opencode_auth_path() -> Result<PathBuf> {
-        let home = dirs::home_dir().ok_or_else(|| {
-            QuotaError::AuthFileNotFound("Could not find home directory".to_string())
-        })?;
-        Ok(home
-            .join(".local")
-            .join("share")
-            .join("opencode")
-            .join("auth.json"))
+        let paths = Self::get_opencode_auth_paths();
+        paths
+            .iter()
+            .find(|path| path.exists())
+            .or_else(|| paths.first())
+            .cloned()
+            .ok_or_else(|| QuotaError::AuthFileNotFound("Could not find home directory".to_string()))
     }
 
-    /// Get possible paths to Antigravity accounts file
+    /// Get possible paths to the OpenCode auth file
     /// Tries multiple locations for cross-platform support
-    fn get_antigravity_accounts_paths() -> Vec<PathBuf> {
+    fn get_opencode_auth_paths() -> Vec<PathBuf> {
+        // An explicit OPENCODE_DATA_DIR is the only place OpenCode itself looks
+        if let Some(dir) = crate::paths::opencode_data_dir_override() {
+            return vec![dir.join("auth.json")];
+        }
+
         let mut paths = Vec::new();
 
-        if let Some(home) = dirs::home_dir() {
-            // Windows: %APPDATA%/opencode/antigravity-accounts.json
-            if let Some(app_data) = dirs::data_dir() {
-                paths.push(app_data.join("opencode").join("antigravity-accounts.json"));
+        // All platforms: $XDG_DATA_HOME/opencode/auth.json (~/.local/share/opencode/auth.json)
+        if let Some(dir) = crate::paths::opencode_data_dir() {
+            paths.push(dir.join("auth.json"));
+        }
+
+        // Windows: %APPDATA%/opencode/auth.json, then %LOCALAPPDATA%/opencode/auth.json
+        for dir in [dirs::data_dir(), dirs::data_local_dir()].into_iter().flatten() {
+            let path = dir.join("opencode").join("auth.json");
+            if !paths.contains(&path) {
+                paths.push(path);
             }
+        }
+
+        paths
+    }
+
+    /// Get possible paths to Antigravity accounts file
+    /// Tries multiple locations for cross-platform support
+    fn get_antigravity_accounts_paths() -> Vec<PathBuf> {
+        let mut candidates = Vec::new();
 
-            // Windows/Linux: ~/.config/opencode/antigravity-accounts.json
-            paths.push(
-                home.join(".config")
-                    .join("opencode")
-                    .join("antigravity-accounts.json"),
-            );
+        // $OPENCODE_DATA_DIR/antigravity-accounts.json, when set
+        candidates.extend(crate::paths::opencode_data_dir_override());
 
-            // Linux: ~/.local/share/opencode/antigravity-accounts.json
-            paths.push(
-                home.join(".local")
-                    .join("share")
-                    .join("opencode")
-                    .join("antigravity-accounts.json"),
-            );
-        }
+        // Windows: %APPDATA%/opencode/antigravity-accounts.json
+        candidates.extend(dirs::data_dir().map(|dir| dir.join("opencode")));
 
+        // All platforms: $XDG_CONFIG_HOME/opencode (~/.config/opencode)
+        candidates.extend(crate::paths::config_home().map(|dir| dir.join("opencode")));
+
+        // Linux: $XDG_DATA_HOME/opencode (~/.local/share/opencode)
+        candidates.extend(crate::paths::data_home().map(|dir| dir.join("opencode")));
+
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for dir in candidates {
+            let path = dir.join("antigravity-accounts.json");
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
         paths
     }
 
+    /// OpenCode auth file in use, if one exists
+    pub fn opencode_auth_file(&self) -> Option<PathBuf> {
+        Self::get_opencode_auth_path().ok().filter(|path| path.exists())
+    }
+
+    /// Antigravity accounts file in use, if one exists
+    pub fn antigravity_accounts_file(&self) -> Option<PathBuf> {
+        Self::get_antigravity_accounts_paths().into_iter().find(|path| path.exists())
+    }
+
     /// Read OpenCode auth file
     pub fn read_opencode_auth(&self) -> Result<Option<OpenCodeAuth>> {
         let path = Self::get_opencode_auth_path()?;
@@ -145,11 +328,28 @@ impl AuthManager {
             return Ok(None);
         }
 
-        let content = std::fs::read_to_string(&path)?;
-        let auth: OpenCodeAuth = serde_json::from_str(&content)?;
+        let auth: OpenCodeAuth = read_json_file(&path)?;
         Ok(Some(auth))
     }
 
+    /// Store a refreshed token for one provider in the OpenCode auth file
+    /// Only the token fields of that entry are replaced, so keys ocu doesn't know about survive;
+    /// the file is written to a temporary sibling and renamed so OpenCode never sees a partial file
+    pub fn save_opencode_token(&self, key: &str, token: &OAuthToken) -> Result<()> {
+        let path = Self::get_opencode_auth_path()?;
+        let mut auth: serde_json::Value = read_json_file(&path)?;
+        replace_token(&mut auth, key, token)?;
+
+        // Keep the original permissions (auth.json is usually private to the user)
+        let permissions = std::fs::metadata(&path)?.permissions();
+        let tmp = path.with_extension("json.ocu-tmp");
+        std::fs::write(&tmp, "")?;
+        std::fs::set_permissions(&tmp, permissions)?;
+        std::fs::write(&tmp, serde_json::to_string_pretty(&auth)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
     /// Read Antigravity accounts file
     /// Tries multiple locations and returns the first one found
     pub fn read_antigravity_accounts(&self) -> Result<Option<AntigravityAccounts>> {
@@ -157,67 +357,354 @@ impl AuthManager {
 
         for path in &paths {
             if path.exists() {
-                let content = std::fs::read_to_string(path)?;
-                let accounts: AntigravityAccounts = serde_json::from_str(&content)?;
+                let accounts: AntigravityAccounts = read_json_file(path)?;
                 return Ok(Some(accounts));
             }
         }
 
         Ok(None)
     }
+}
 
-    /// Check if a specific provider is configured
-    /// Each auth method is checked independently so that if one fails, we still check the others
-    pub fn is_provider_configured(&self, provider: &str) -> Result<bool> {
-        // Check each auth source independently - don't fail early if one errors
-        let opencode_auth = self.read_opencode_auth().ok().flatten();
-        let antigravity_accounts = self.read_antigravity_accounts().ok().flatten();
+impl Default for AuthManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        match provider {
-            "gemini" => {
-                // Only check for antigravity-accounts.json - Google OAuth alone is not sufficient
-                // since the Gemini provider only supports Antigravity accounts
-                Ok(antigravity_accounts.is_some())
-            }
-            "claude" => Ok(opencode_auth
-                .as_ref()
-                .map(|a| a.anthropic.is_some())
-                .unwrap_or(false)),
-            "codex" => Ok(opencode_auth
-                .as_ref()
-                .map(|a| a.openai.is_some())
-                .unwrap_or(false)),
-            "copilot" => Ok(opencode_auth
-                .as_ref()
-                .map(|a| a.github_copilot.is_some())
-                .unwrap_or(false)),
-            _ => Ok(false),
-        }
+impl AuthSource for AuthManager {
+    fn opencode_auth(&self) -> Result<Option<OpenCodeAuth>> {
+        self.read_opencode_auth()
     }
 
-    /// Get list of configured providers
-    pub fn get_configured_providers(&self) -> Result<Vec<String>> {
-        let mut providers = Vec::new();
+    fn antigravity_accounts(&self) -> Result<Option<AntigravityAccounts>> {
+        self.read_antigravity_accounts()
+    }
 
-        if self.is_provider_configured("gemini")? {
-            providers.push("gemini".to_string());
-        }
-        if self.is_provider_configured("claude")? {
-            providers.push("claude".to_string());
+    fn save_token(&self, key: &str, token: &OAuthToken) -> Result<()> {
+        self.save_opencode_token(key, token)
+    }
+}
+
+/// Credentials from environment variables, for CI and containers without OpenCode's files
+/// `OCU_CLAUDE_TOKEN`, `OCU_CODEX_TOKEN` (with `OCU_CODEX_ACCOUNT_ID`) and `OCU_COPILOT_TOKEN`
+/// hold access tokens; `OCU_GEMINI_REFRESH_TOKEN` and `OCU_GEMINI_EMAIL` describe one Antigravity account
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvAuth;
+
+impl EnvAuth {
+    fn token(name: &str, account_id: Option<&str>) -> Option<OAuthToken> {
+        let access = std::env::var(name).ok().filter(|v| !v.is_empty())?;
+        Some(OAuthToken {
+            token_type: "oauth".to_string(),
+            access,
+            refresh: None,
+            expires: None,
+            account_id: account_id.and_then(|name| std::env::var(name).ok()),
+        })
+    }
+}
+
+impl AuthSource for EnvAuth {
+    fn opencode_auth(&self) -> Result<Option<OpenCodeAuth>> {
+        let auth = OpenCodeAuth {
+            google: None,
+            anthropic: Self::token("OCU_CLAUDE_TOKEN", None),
+            openai: Self::token("OCU_CODEX_TOKEN", Some("OCU_CODEX_ACCOUNT_ID")),
+            github_copilot: Self::token("OCU_COPILOT_TOKEN", None),
+        };
+        let any = auth.anthropic.is_some() || auth.openai.is_some() || auth.github_copilot.is_some();
+        Ok(any.then_some(auth))
+    }
+
+    fn antigravity_accounts(&self) -> Result<Option<AntigravityAccounts>> {
+        let Some(refresh_token) = std::env::var("OCU_GEMINI_REFRESH_TOKEN").ok().filter(|v| !v.is_empty()) else {
+            return Ok(None);
+        };
+        Ok(Some(AntigravityAccounts::single(AntigravityAccount {
+            email: std::env::var("OCU_GEMINI_EMAIL").unwrap_or_else(|_| "gemini".to_string()),
+            refresh_token,
+            project_id: std::env::var("OCU_GEMINI_PROJECT_ID").ok(),
+            managed_project_id: None,
+            rate_limit_reset_times: None,
+            added_at: None,
+            last_used: None,
+            fingerprint: None,
+        })))
+    }
+
+    /// Environment variables can't be updated for later runs, so refreshed tokens are dropped
+    fn save_token(&self, _key: &str, _token: &OAuthToken) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Credentials held in memory, e.g. fetched by the embedding application from its own store
+/// Refreshed tokens replace the stored ones for the lifetime of the value
+#[derive(Debug, Default)]
+pub struct MemoryAuth {
+    opencode: Mutex<Option<OpenCodeAuth>>,
+    antigravity: Option<AntigravityAccounts>,
+}
+
+impl MemoryAuth {
+    pub fn new(opencode: Option<OpenCodeAuth>, antigravity: Option<AntigravityAccounts>) -> Self {
+        Self {
+            opencode: Mutex::new(opencode),
+            antigravity,
         }
-        if self.is_provider_configured("codex")? {
-            providers.push("codex".to_string());
+    }
+}
+
+impl AuthSource for MemoryAuth {
+    fn opencode_auth(&self) -> Result<Option<OpenCodeAuth>> {
+        Ok(self.opencode.lock().map(|auth| auth.clone()).unwrap_or_default())
+    }
+
+    fn antigravity_accounts(&self) -> Result<Option<AntigravityAccounts>> {
+        Ok(self.antigravity.clone())
+    }
+
+    fn save_token(&self, key: &str, token: &OAuthToken) -> Result<()> {
+        if let Ok(mut auth) = self.opencode.lock() {
+            let slot = auth.as_mut().and_then(|auth| match key {
+                "google" => Some(&mut auth.google),
+                "anthropic" => Some(&mut auth.anthropic),
+                "openai" => Some(&mut auth.openai),
+                "github-copilot" => Some(&mut auth.github_copilot),
+                _ => None,
+            });
+            if let Some(slot) = slot {
+                *slot = Some(token.clone());
+            }
         }
-        if self.is_provider_configured("copilot")? {
-            providers.push("copilot".to_string());
+        Ok(())
+    }
+}
+
+/// Keyring service `KeyringAuth::new` callers would normally pass
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+pub const KEYRING_SERVICE: &str = "ocu";
+
+/// Credentials kept in the OS keyring: the macOS Keychain, the Windows Credential Manager or the Secret Service
+/// The "opencode-auth" entry holds a document shaped like OpenCode's `auth.json` and "antigravity-accounts"
+/// one like `antigravity-accounts.json`; refreshed tokens are written back to the first
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+#[derive(Debug)]
+pub struct KeyringAuth {
+    service: String,
+    opencode: keyring::Entry,
+    antigravity: keyring::Entry,
+}
+
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+impl KeyringAuth {
+    const OPENCODE: &'static str = "opencode-auth";
+    const ANTIGRAVITY: &'static str = "antigravity-accounts";
+
+    /// The entries under `service`, e.g. `KEYRING_SERVICE`
+    pub fn new(service: &str) -> Result<Self> {
+        let entry = |name| keyring::Entry::new(service, name).map_err(|e| keyring_error(service, name, e));
+        Ok(Self {
+            service: service.to_string(),
+            opencode: entry(Self::OPENCODE)?,
+            antigravity: entry(Self::ANTIGRAVITY)?,
+        })
+    }
+
+    /// Replace the stored OpenCode tokens, e.g. with the contents of `auth.json`
+    pub fn store_opencode_auth(&self, auth: &OpenCodeAuth) -> Result<()> {
+        self.store(&self.opencode, Self::OPENCODE, auth)
+    }
+
+    /// Replace the stored Antigravity accounts
+    pub fn store_antigravity_accounts(&self, accounts: &AntigravityAccounts) -> Result<()> {
+        self.store(&self.antigravity, Self::ANTIGRAVITY, accounts)
+    }
+
+    /// The JSON document in an entry, or None when the entry doesn't exist
+    fn read<T: serde::de::DeserializeOwned>(&self, entry: &keyring::Entry, name: &str) -> Result<Option<T>> {
+        let content = match entry.get_password() {
+            Ok(content) => content,
+            Err(keyring::Error::NoEntry) => return Ok(None),
+            Err(e) => return Err(keyring_error(&self.service, name, e)),
+        };
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| invalid_json(format!("keyring entry {}/{}", self.service, name), &content, &e))
+    }
+
+    fn store<T: Serialize>(&self, entry: &keyring::Entry, name: &str, value: &T) -> Result<()> {
+        entry
+            .set_password(&serde_json::to_string(value)?)
+            .map_err(|e| keyring_error(&self.service, name, e))
+    }
+}
+
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+impl AuthSource for KeyringAuth {
+    fn opencode_auth(&self) -> Result<Option<OpenCodeAuth>> {
+        self.read(&self.opencode, Self::OPENCODE)
+    }
+
+    fn antigravity_accounts(&self) -> Result<Option<AntigravityAccounts>> {
+        self.read(&self.antigravity, Self::ANTIGRAVITY)
+    }
+
+    /// Only the token fields of that entry are replaced, as in `AuthManager::save_opencode_token`
+    fn save_token(&self, key: &str, token: &OAuthToken) -> Result<()> {
+        let mut auth: serde_json::Value = self
+            .read(&self.opencode, Self::OPENCODE)?
+            .ok_or_else(|| QuotaError::ProviderNotConfigured(key.to_string()))?;
+        replace_token(&mut auth, key, token)?;
+        self.store(&self.opencode, Self::OPENCODE, &auth)
+    }
+}
+
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+fn keyring_error(service: &str, name: &str, e: keyring::Error) -> QuotaError {
+    std::io::Error::new(std::io::ErrorKind::Other, format!("keyring entry {}/{}: {}", service, name, e)).into()
+}
+
+/// Update the token fields of `key` in an OpenCode auth document, keeping every other field
+fn replace_token(auth: &mut serde_json::Value, key: &str, token: &OAuthToken) -> Result<()> {
+    let entry = auth
+        .get_mut(key)
+        .and_then(|e| e.as_object_mut())
+        .ok_or_else(|| QuotaError::ProviderNotConfigured(key.to_string()))?;
+    entry.insert("access".to_string(), token.access.clone().into());
+    if let Some(refresh) = &token.refresh {
+        entry.insert("refresh".to_string(), refresh.clone().into());
+    }
+    if let Some(expires) = token.expires {
+        entry.insert("expires".to_string(), expires.into());
+    }
+    Ok(())
+}
+
+/// Backoff between attempts to read an auth file that doesn't parse
+const READ_RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(25),
+    Duration::from_millis(75),
+    Duration::from_millis(200),
+];
+
+/// Read and parse a JSON file that OpenCode may be rewriting at the same moment
+/// A file that ends early (a write in progress) is read again after a short backoff; other
+/// errors, and one that persists, name the file and the byte offset where parsing stopped.
+/// The backoff blocks the thread, so async code reaches it through `run_blocking`
+fn read_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let mut delays = READ_RETRY_DELAYS.iter();
+    loop {
+        let content = std::fs::read_to_string(path)?;
+        match serde_json::from_str(&content) {
+            Ok(value) => return Ok(value),
+            Err(e) => match delays.next() {
+                Some(delay) if e.classify() == serde_json::error::Category::Eof => std::thread::sleep(*delay),
+                _ => return Err(invalid_json(path.display().to_string(), &content, &e)),
+            },
         }
+    }
+}
 
-        Ok(providers)
+/// Run an `AuthSource` call from async code without holding up the runtime's workers
+/// `AuthManager` may wait out a concurrent write of OpenCode's files; the wasm32 build
+/// has no files to wait for, so it calls `f` in place
+pub(crate) async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    return tokio::task::spawn_blocking(f).await.map_err(std::io::Error::from)?;
+    #[cfg(target_arch = "wasm32")]
+    return f();
+}
+
+/// The error for `content`, read from `path`, that failed to parse with `e`
+fn invalid_json(path: String, content: &str, e: &serde_json::Error) -> QuotaError {
+    QuotaError::AuthFileInvalid {
+        path,
+        offset: byte_offset(content, e.line(), e.column()),
+        message: e.to_string().split(" at line ").next().unwrap_or_default().to_string(),
     }
 }
 
-impl Default for AuthManager {
-    fn default() -> Self {
-        Self::new()
+/// Byte offset of a 1-based line/column position reported by serde_json
+fn byte_offset(content: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = content
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    (line_start + column.saturating_sub(1)).min(content.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn rereads_a_file_that_ends_early() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("auth.json");
+        std::fs::write(&path, r#"{"anthropic": {"type": "oauth", "ac"#).unwrap();
+
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(10));
+                std::fs::write(path, r#"{"anthropic": {"type": "oauth", "access": "token"}}"#).unwrap();
+            })
+        };
+        let auth: OpenCodeAuth = read_json_file(&path).unwrap();
+        writer.join().unwrap();
+        assert_eq!(auth.anthropic.map(|t| t.access), Some("token".to_string()));
+    }
+
+    #[test]
+    fn fails_at_once_on_a_malformed_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("auth.json");
+        std::fs::write(&path, "{\"anthropic\": nope}").unwrap();
+
+        let started = Instant::now();
+        let err = read_json_file::<OpenCodeAuth>(&path).unwrap_err();
+        assert!(started.elapsed() < READ_RETRY_DELAYS[0]);
+        match err {
+            QuotaError::AuthFileInvalid { path: named, offset, .. } => {
+                assert_eq!(named, path.display().to_string());
+                assert_eq!(offset, 15);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[cfg(feature = "keyring")]
+    #[test]
+    fn keyring_auth_keeps_refreshed_tokens() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let token = |access: &str| OAuthToken {
+            token_type: "oauth".to_string(),
+            access: access.to_string(),
+            refresh: Some("refresh".to_string()),
+            expires: None,
+            account_id: None,
+        };
+        let auth = KeyringAuth::new(KEYRING_SERVICE).unwrap();
+        assert!(auth.opencode_auth().unwrap().is_none());
+        assert!(!auth.is_provider_configured("claude").unwrap());
+
+        let stored = OpenCodeAuth {
+            anthropic: Some(token("old")),
+            ..OpenCodeAuth::default()
+        };
+        auth.store_opencode_auth(&stored).unwrap();
+        auth.save_token("anthropic", &token("new")).unwrap();
+        let read = auth.opencode_auth().unwrap().unwrap();
+        assert_eq!(read.anthropic.map(|t| t.access), Some("new".to_string()));
+        assert!(auth.is_provider_configured("claude").unwrap());
+        assert!(!auth.is_provider_configured("codex").unwrap());
     }
 }
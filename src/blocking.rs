@@ -0,0 +1,48 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use futures::stream::StreamExt;
+
+use crate::error::Result;
+use crate::providers::claude::ClaudeProvider;
+use crate::providers::codex::CodexProvider;
+use crate::providers::copilot::CopilotProvider;
+use crate::providers::gemini::GeminiProvider;
+use crate::providers::{fetch_all_stream, FetchOptions, Provider, ProviderResult};
+
+/// Fetch every configured provider with default settings, in the order `ocu` lists them
+/// For synchronous callers: runs its own runtime, so it must not be called from async code
+pub fn fetch_all(options: FetchOptions) -> Result<Vec<ProviderResult>> {
+    let gemini = GeminiProvider::new();
+    let codex = CodexProvider::new();
+    let copilot = CopilotProvider::new();
+    let claude = ClaudeProvider::new();
+    let configured: Vec<&dyn Provider> = [&gemini as &dyn Provider, &codex, &copilot, &claude]
+        .into_iter()
+        .filter(|p| p.is_configured())
+        .collect();
+    fetch(&configured, options)
+}
+
+/// Fetch these providers, returning results in the order given
+/// For synchronous callers: runs its own runtime, so it must not be called from async code
+pub fn fetch(providers: &[&dyn Provider], options: FetchOptions) -> Result<Vec<ProviderResult>> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let mut results: Vec<ProviderResult> = runtime.block_on(fetch_all_stream(providers, options).collect());
+    results.sort_by_key(|r| r.index);
+    Ok(results)
+}
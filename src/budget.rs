@@ -0,0 +1,195 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::cache::cache_dir;
+use crate::error::{QuotaError, Result};
+use crate::providers::{CostModel, ProviderData};
+
+/// Months of spend kept in the history
+const MONTHS_KEPT: usize = 24;
+
+/// `[budget]` section: monthly spending limits on the estimated cost
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetConfig {
+    /// Limit on the month's estimated spend across every provider (USD)
+    pub monthly: Option<f64>,
+    /// Limits per provider (USD), e.g. `copilot = 10`
+    pub providers: BTreeMap<String, f64>,
+}
+
+impl BudgetConfig {
+    pub fn is_empty(&self) -> bool {
+        self.monthly.is_none() && self.providers.is_empty()
+    }
+
+    /// Every budget must be a positive amount; the error names the offending key
+    pub fn validate(&self) -> Result<()> {
+        let budgets = self.monthly.map(|b| ("monthly".to_string(), b)).into_iter().chain(
+            self.providers
+                .iter()
+                .map(|(provider, &b)| (format!("providers.{}", provider), b)),
+        );
+        for (key, budget) in budgets {
+            if !is_valid_budget(budget) {
+                return Err(QuotaError::ConfigError(format!(
+                    "budget.{} must be an amount above zero, got {}",
+                    key, budget
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// How much of each budget the month's spend has used, providers first and the overall budget last
+    /// Budgets that `validate` would reject are left out, so percentages are always finite
+    pub fn usage(&self, spent: &BTreeMap<String, f64>) -> Vec<BudgetUsage> {
+        let total = spent.values().sum();
+        self.providers
+            .iter()
+            .map(|(provider, &budget)| (provider.as_str(), budget, spent.get(provider).copied().unwrap_or(0.0)))
+            .chain(self.monthly.map(|budget| (TOTAL, budget, total)))
+            .filter(|&(_, budget, _)| is_valid_budget(budget))
+            .map(|(scope, budget, spent)| BudgetUsage::new(scope, budget, spent))
+            .collect()
+    }
+}
+
+fn is_valid_budget(budget: f64) -> bool {
+    budget.is_finite() && budget > 0.0
+}
+
+/// Scope of the overall budget
+pub const TOTAL: &str = "total";
+
+/// Month-to-date spend against one budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BudgetUsage {
+    /// Provider name, or "total" for the overall budget
+    pub scope: String,
+    pub budget_usd: f64,
+    pub spent_usd: f64,
+    /// Percentage of the budget spent; above 100 once it is exceeded
+    pub used_percent: f64,
+}
+
+impl BudgetUsage {
+    fn new(scope: &str, budget_usd: f64, spent_usd: f64) -> Self {
+        Self {
+            scope: scope.to_string(),
+            budget_usd,
+            spent_usd,
+            used_percent: spent_usd / budget_usd * 100.0,
+        }
+    }
+
+    /// Short description, e.g. "copilot $0.80 of $10.00 (8%)"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ${:.2} of ${:.2} ({:.0}%)",
+            self.scope, self.spent_usd, self.budget_usd, self.used_percent
+        )
+    }
+}
+
+/// Estimated spend per provider and month, kept between runs (~/.cache/ocu/spend.json)
+/// Estimates are cumulative over a billing period, so each month keeps the highest one seen
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SpendHistory {
+    /// Keyed by month ("2026-10"), then provider
+    months: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl SpendHistory {
+    fn default_path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("spend.json"))
+    }
+
+    /// The stored history, or an empty one when missing or unreadable
+    pub fn load() -> Self {
+        Self::default_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn store(&self) -> Result<()> {
+        let path = Self::default_path()
+            .ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Record the current estimates under `month`, dropping the oldest months beyond the ones kept
+    pub fn record(&mut self, current: &[ProviderData], cost: &CostModel, month: &str) {
+        let spent = self.months.entry(month.to_string()).or_default();
+        for data in current {
+            if let Some(usd) = cost.estimate(data) {
+                let entry = spent.entry(data.provider_name().to_string()).or_insert(0.0);
+                *entry = entry.max(usd);
+            }
+        }
+        while self.months.len() > MONTHS_KEPT {
+            self.months.pop_first();
+        }
+    }
+
+    /// Spend per provider recorded for `month`
+    pub fn month(&self, month: &str) -> BTreeMap<String, f64> {
+        self.months.get(month).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budgets(monthly: Option<f64>, providers: &[(&str, f64)]) -> BudgetConfig {
+        BudgetConfig {
+            monthly,
+            providers: providers.iter().map(|&(p, b)| (p.to_string(), b)).collect(),
+        }
+    }
+
+    #[test]
+    fn validate_rejects_budgets_not_above_zero_by_key() {
+        let err = budgets(Some(0.0), &[]).validate().unwrap_err().to_string();
+        assert!(err.contains("budget.monthly"), "{}", err);
+        let err = budgets(None, &[("copilot", -1.0)]).validate().unwrap_err().to_string();
+        assert!(err.contains("budget.providers.copilot"), "{}", err);
+        assert!(budgets(None, &[("copilot", f64::NAN)]).validate().is_err());
+        assert!(budgets(Some(50.0), &[("copilot", 10.0)]).validate().is_ok());
+    }
+
+    #[test]
+    fn usage_skips_invalid_budgets_and_round_trips() {
+        let spent = BTreeMap::from([("copilot".to_string(), 2.0)]);
+        let usage = budgets(Some(0.0), &[("copilot", 10.0)]).usage(&spent);
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].used_percent, 20.0);
+        let json = serde_json::to_string(&usage).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<BudgetUsage>>(&json).unwrap(), usage);
+    }
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::error::{QuotaError, Result};
+use crate::providers::ProviderData;
+
+/// Directory for ocu's cached data (`$XDG_CACHE_HOME/ocu`, by default ~/.cache/ocu)
+pub fn cache_dir() -> Option<PathBuf> {
+    crate::paths::cache_home().map(|dir| dir.join("ocu"))
+}
+
+/// Results of the most recent fetch, stored so `ocu prompt` can render without network I/O
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResults {
+    pub fetched_at: DateTime<Utc>,
+    pub providers: Vec<ProviderData>,
+}
+
+impl CachedResults {
+    pub fn new(providers: Vec<ProviderData>) -> Self {
+        Self {
+            fetched_at: Utc::now(),
+            providers,
+        }
+    }
+
+    /// Cache file location (~/.cache/ocu/last.json)
+    pub fn default_path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("last.json"))
+    }
+
+    /// Read the cached results, or None when nothing has been cached yet
+    pub fn load() -> Result<Option<Self>> {
+        let path = Self::default_path()
+            .ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Replace the cached results
+    /// Written to a temporary file first so a concurrent `ocu prompt` never reads a partial file
+    pub fn store(&self) -> Result<()> {
+        let path = Self::default_path()
+            .ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// Non-reversible identifier of a token, for keying cached credentials without storing the token
+pub(crate) fn fingerprint(token: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    token.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Replace a cache file holding credentials, readable only by the user
+/// Written to a temporary file first so concurrent runs never read a partial file
+pub(crate) fn write_private(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, "")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
@@ -15,23 +15,52 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use clap::{Parser, ValueEnum};
+#[cfg(feature = "cli")]
+use clap::error::ErrorKind;
+#[cfg(feature = "cli")]
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+#[cfg(feature = "cli")]
+use std::io::IsTerminal;
+#[cfg(feature = "cli")]
+use std::path::PathBuf;
 
+/// Exit code reference appended to `--help`
+#[cfg(feature = "cli")]
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0    All providers answered and no quota is past its warning level
+  1    Some providers failed (subcommands: a check failed)
+  2    No providers configured, or invalid arguments or configuration
+  3    Every provider query failed
+  4    A quota is past its warning level
+  5    A provider's login expired; run `opencode auth login <provider>`
+  130  Interrupted with Ctrl-C";
+
+#[cfg(feature = "cli")]
 #[derive(Parser)]
 #[command(name = "ocu")]
 #[command(about = "OpenCode Usage Companion - Check AI provider quotas")]
 #[command(version)]
+#[command(after_help = EXIT_CODES_HELP)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Provider(s) to check
-    #[arg(short, long, value_enum)]
+    #[arg(short, long, value_enum, global = true)]
     pub provider: Vec<ProviderArg>,
 
     /// Output format
     #[arg(short, long, value_enum, default_value = "table")]
     pub format: OutputFormat,
 
-    /// Timeout per provider in seconds
-    #[arg(short, long, default_value = "10")]
+    /// Print a single summary line (same as --format summary)
+    #[arg(long, conflicts_with = "format")]
+    pub summary: bool,
+
+    /// Deadline per provider in seconds, covering every request it makes
+    #[arg(short, long, default_value = "10", global = true, value_parser = clap::value_parser!(u64).range(1..))]
     pub timeout: u64,
 
     /// Query providers concurrently
@@ -39,15 +68,392 @@ pub struct Args {
     pub concurrent: bool,
 
     /// Disable colored output
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub no_color: bool,
 
+    /// Only show these Gemini accounts (repeatable, supports `*` and `?` globs)
+    #[arg(long, value_name = "EMAIL")]
+    pub account: Vec<String>,
+
+    /// Only show models/windows matching this pattern (repeatable, substring or `*`/`?` glob)
+    #[arg(long, value_name = "PATTERN", global = true)]
+    pub model: Vec<String>,
+
+    /// Only fetch the active Gemini account (default for --format simple)
+    #[arg(long, conflicts_with = "all_accounts")]
+    pub active_only: bool,
+
+    /// Fetch every Gemini account, including for --format simple
+    #[arg(long)]
+    pub all_accounts: bool,
+
+    /// Don't link provider names to their usage dashboards
+    #[arg(long)]
+    pub no_hyperlinks: bool,
+
+    /// Disable inline usage bars in the table
+    #[arg(long)]
+    pub no_bars: bool,
+
+    /// Omit the summary headline above the table
+    #[arg(long)]
+    pub no_summary: bool,
+
+    /// Mask account emails and plan identifiers, for sharing screenshots
+    #[arg(long)]
+    pub redact: bool,
+
+    /// Show how far through each window we are, flagging usage that runs ahead of time
+    #[arg(long)]
+    pub pace: bool,
+
+    /// Add a column showing the warning/critical usage limits applied to each row
+    #[arg(long)]
+    pub show_thresholds: bool,
+
+    /// Used percentage from which a window is a warning (default: 80, or `[thresholds]` in the config)
+    #[arg(long, value_name = "PCT")]
+    pub warn_at: Option<f64>,
+
+    /// Used percentage from which a window is exhausted (default: 100, or `[thresholds]` in the config)
+    #[arg(long, value_name = "PCT")]
+    pub critical_at: Option<f64>,
+
+    /// Show plain status words instead of ✓/⚠️/✗ glyphs
+    #[arg(long)]
+    pub no_emoji: bool,
+
+    /// Show usage as percent used or percent remaining (default: used)
+    #[arg(long, value_enum, global = true)]
+    pub show: Option<UsageView>,
+
+    /// Show remaining request counts instead of percentages where available (Copilot)
+    #[arg(long)]
+    pub counts: bool,
+
+    /// Hide Claude's per-model 7-day windows (Sonnet/Opus)
+    #[arg(long)]
+    pub hide_model_windows: bool,
+
+    /// List every Gemini model with its own quota instead of quota buckets
+    #[arg(long)]
+    pub detailed: bool,
+
+    /// Show reset times as absolute timestamps instead of relative durations
+    #[arg(long)]
+    pub absolute_times: bool,
+
+    /// Show absolute timestamps in UTC instead of the local timezone
+    #[arg(long)]
+    pub utc: bool,
+
+    /// Table border style (default: rounded)
+    #[arg(long, value_enum)]
+    pub style: Option<TableStyle>,
+
+    /// Only show these table columns, e.g. `provider,model,usage`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub columns: Vec<TableColumn>,
+
+    /// Truncate the table to this many characters wide
+    #[arg(long, value_name = "CHARS", value_parser = clap::value_parser!(u16).range(20..))]
+    pub width: Option<u16>,
+
+    /// Group table rows by provider or by model family
+    #[arg(long, value_enum, default_value_t = GroupBy::Provider)]
+    pub group_by: GroupBy,
+
+    /// Sort providers and rows
+    #[arg(long, value_enum)]
+    pub sort: Option<SortKey>,
+
+    /// Sort direction (default: desc for usage, asc for reset and name)
+    #[arg(long, value_enum, requires = "sort")]
+    pub sort_order: Option<SortOrder>,
+
+    /// How provider errors are reported on stderr
+    #[arg(long, value_enum, default_value_t = ErrorFormat::Text)]
+    pub errors: ErrorFormat,
+
     /// Show verbose output (API requests and responses)
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// Print the raw response body when a provider response fails to decode
+    #[arg(long)]
+    pub dump_body_on_error: bool,
+
+    /// Also write the used percentage of every window as OpenMetrics text to this file
+    /// (e.g. `metrics.txt` for GitLab's metrics report)
+    #[arg(long, value_name = "PATH")]
+    pub metrics_file: Option<PathBuf>,
+
+    /// Push usage gauges and request latencies to this OTLP/HTTP collector, e.g. `http://localhost:4318`
+    /// (default: `[otlp] endpoint` in the config)
+    #[arg(long, value_name = "URL")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Submit usage gauges and service checks to Datadog (API key from DD_API_KEY or `[datadog]`)
+    #[arg(long)]
+    pub datadog: bool,
+
+    /// Publish usage to CloudWatch under this namespace with the default AWS credentials
+    /// (default: `[cloudwatch] namespace` in the config)
+    #[arg(long, value_name = "NAMESPACE")]
+    pub cloudwatch_namespace: Option<String>,
+
+    /// Push the report to a team aggregation server (`ocu serve --aggregate`) at this URL
+    /// (default: `[team] url` in the config)
+    #[arg(long, value_name = "URL")]
+    pub push: Option<String>,
+
+    /// Keep running and print the `--format json` report as one line after every fetch,
+    /// every SECONDS (default 300, at least 30), for eww's `deflisten` and other widgets reading a stream
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "300", value_parser = clap::value_parser!(u64).range(30..))]
+    pub listen_json: Option<u64>,
+
+    /// Print the JSON Schema of the `--format json` output and exit
+    #[arg(long)]
+    pub print_schema: bool,
+
+    /// Path to the config file (default: ~/.config/ocu/config.toml)
+    #[arg(long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+#[cfg(feature = "cli")]
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Print a one-line summary of the last fetch for shell prompts, from a local `ocu serve` or the cache
+    Prompt,
+    /// Print the last fetch as a Lua table for Neovim statusline components (no network I/O)
+    NvimStatus,
+    /// Check auth files, configuration and the local clock against provider clocks
+    Doctor,
+    /// Summarize recorded runs (peaks, overages, failures) into a Markdown or HTML report
+    Report {
+        /// Span of the report, ending now
+        #[arg(long, value_enum, default_value_t = ReportPeriod::Week)]
+        period: ReportPeriod,
+        /// Document format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+        format: ReportFormat,
+        /// Also POST the report to this URL
+        #[arg(long, value_name = "URL")]
+        webhook: Option<String>,
+    },
+    /// List what each detected plan includes (premium requests, messages per window) next to current usage
+    Limits,
+    /// Attribute token usage from OpenCode's local session data to projects, sessions or models
+    Sessions {
+        /// Start of the range: a duration back from now (7d, 12h, 30m, 2w) or a date (2026-10-01)
+        #[arg(long, default_value = "7d", value_parser = crate::sessions::parse_since)]
+        since: chrono::DateTime<chrono::Utc>,
+        /// End of the range, in the same forms as --since (default: now)
+        #[arg(long, value_parser = crate::sessions::parse_since)]
+        until: Option<chrono::DateTime<chrono::Utc>>,
+        /// Group usage by project, session or model
+        #[arg(long, value_enum, default_value_t = SessionGroup::Project)]
+        by: SessionGroup,
+    },
+    /// Refresh on an interval and send the status line to Zellij plugins with `zellij pipe`
+    Zellij {
+        /// Seconds between refreshes
+        #[arg(long, default_value = "300", value_parser = clap::value_parser!(u64).range(30..))]
+        interval: u64,
+        /// Pipe name the plugin listens on
+        #[arg(long, default_value = "ocu")]
+        name: String,
+    },
+    /// Serve usage to editor extensions as JSON-RPC over stdin/stdout (`usage/get`, `usage/changed`)
+    Rpc {
+        /// Seconds between refreshes
+        #[arg(long, default_value = "300", value_parser = clap::value_parser!(u64).range(30..))]
+        interval: u64,
+    },
+    /// Serve the latest report over HTTP (`GET /v1/usage`), refresh on demand (`POST /v1/refresh`), and answer Grafana, Slack and Stream Deck
+    Serve {
+        /// Seconds between refreshes
+        #[arg(long, default_value = "300", value_parser = clap::value_parser!(u64).range(30..))]
+        interval: u64,
+        /// Address to listen on (default: `[serve] listen`, or 127.0.0.1:47383)
+        #[arg(long)]
+        listen: Option<std::net::SocketAddr>,
+        /// Collect reports pushed by team members (`--push`) instead of fetching, and serve the team view
+        #[arg(long)]
+        aggregate: bool,
+    },
+    /// Serve usage on the session D-Bus as net.smux.Ocu, for desktop shell widgets
+    #[cfg(feature = "dbus")]
+    Dbus {
+        /// Seconds between refreshes
+        #[arg(long, default_value = "300", value_parser = clap::value_parser!(u64).range(30..))]
+        interval: u64,
+    },
+    /// Inspect the OpenCode credentials ocu uses
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Run a chat bot answering with the current quotas
+    Bot {
+        #[command(subcommand)]
+        platform: BotPlatform,
+    },
+}
+
+#[cfg(feature = "cli")]
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq)]
+pub enum AuthAction {
+    /// Show which providers are authenticated and when their tokens expire
+    Status,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq)]
+pub enum BotPlatform {
+    /// Answer `/usage` with an embed and post a daily summary (`[discord]` in the config)
+    Discord {
+        /// Address the interactions endpoint listens on
+        #[arg(long, default_value = "127.0.0.1:47382")]
+        listen: std::net::SocketAddr,
+    },
+}
+
+#[cfg(feature = "cli")]
+impl Args {
+    /// Reject combinations clap can't express declaratively, with clap-style errors
+    pub fn validate(&self) -> Result<(), clap::Error> {
+        let conflict = |message: String| Args::command().error(ErrorKind::ArgumentConflict, message);
+
+        for (i, provider) in self.provider.iter().enumerate() {
+            if self.provider[..i].contains(provider) {
+                return Err(conflict(format!(
+                    "the argument '--provider {}' was provided more than once",
+                    provider.name()
+                )));
+            }
+        }
+        if self.provider.contains(&ProviderArg::All) && self.provider.len() > 1 {
+            return Err(conflict(
+                "'--provider all' already includes every provider and can't be combined with others".to_string(),
+            ));
+        }
+        if self.listen_json.is_some() && self.command.is_some() {
+            return Err(conflict("'--listen-json' can't be used with a subcommand".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Whether only the active Gemini account should be fetched
+    /// Status-bar style output (simple, summary, statusline, raycast, plasma) shows just the active account unless --all-accounts is given
+    pub fn gemini_active_only(&self) -> bool {
+        let compact = matches!(
+            self.output_format(),
+            OutputFormat::Simple
+                | OutputFormat::Summary
+                | OutputFormat::Statusline
+                | OutputFormat::Raycast
+                | OutputFormat::Plasma
+        );
+        self.active_only || (compact && !self.all_accounts)
+    }
+
+    /// Output format, with `--summary` as a shorthand for `--format summary`
+    pub fn output_format(&self) -> OutputFormat {
+        if self.summary {
+            OutputFormat::Summary
+        } else {
+            self.format
+        }
+    }
+
+    /// Whether output should be colored
+    /// `--no-color` and `NO_COLOR` always win, `CLICOLOR_FORCE` forces colors,
+    /// `CLICOLOR=0` disables them, otherwise colors are used only when stdout is a terminal
+    /// (or for `ocu prompt`, whose output is always captured by the shell)
+    pub fn use_color(&self) -> bool {
+        if self.no_color || env_is_set("NO_COLOR") {
+            return false;
+        }
+        if env_is_set("CLICOLOR_FORCE") && std::env::var("CLICOLOR_FORCE").as_deref() != Ok("0") {
+            return true;
+        }
+        if std::env::var("CLICOLOR").as_deref() == Ok("0") {
+            return false;
+        }
+        // Prompts and Raycast render ANSI colors without being terminals
+        self.command == Some(Command::Prompt)
+            || self.output_format() == OutputFormat::Raycast
+            || std::io::stdout().is_terminal()
+    }
+
+    /// Whether provider names should be rendered as OSC 8 hyperlinks
+    /// `FORCE_HYPERLINK` overrides detection; otherwise only terminals known to support them get links
+    pub fn use_hyperlinks(&self) -> bool {
+        if self.no_hyperlinks || !std::io::stdout().is_terminal() {
+            return false;
+        }
+        if let Ok(force) = std::env::var("FORCE_HYPERLINK") {
+            return !force.is_empty() && force != "0";
+        }
+        terminal_supports_hyperlinks()
+    }
+}
+
+/// Best-effort detection of terminals that render OSC 8 hyperlinks
+#[cfg(feature = "cli")]
+fn terminal_supports_hyperlinks() -> bool {
+    if std::env::var("TERM").as_deref() == Ok("dumb") {
+        return false;
+    }
+    if env_is_set("WT_SESSION") || env_is_set("KITTY_WINDOW_ID") || env_is_set("WEZTERM_EXECUTABLE") {
+        return true;
+    }
+    if let Ok(program) = std::env::var("TERM_PROGRAM") {
+        if matches!(program.as_str(), "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper") {
+            return true;
+        }
+    }
+    // VTE-based terminals (GNOME Terminal, Tilix, ...) support links since 0.50
+    std::env::var("VTE_VERSION")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .is_some_and(|v| v >= 5000)
+}
+
+/// True when an environment variable is present and non-empty
+#[cfg(feature = "cli")]
+fn env_is_set(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|v| !v.is_empty())
+}
+
+/// Process exit codes, stable so wrappers can branch on the cause without parsing stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Success = 0,
+    /// Some providers failed; also a failed check for subcommands
+    Failure = 1,
+    /// No providers configured, or invalid arguments or configuration (clap also uses 2)
+    NoProviders = 2,
+    /// Every provider query failed
+    AllFailed = 3,
+    /// All providers answered but a quota is past its warning level
+    ThresholdExceeded = 4,
+    /// A provider's token expired or was rejected and needs a new login
+    AuthExpired = 5,
+    /// Interrupted with Ctrl-C (128 + SIGINT)
+    Interrupted = 130,
+}
+
+impl From<Exit> for std::process::ExitCode {
+    fn from(exit: Exit) -> Self {
+        std::process::ExitCode::from(exit as u8)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 pub enum ProviderArg {
     /// Google Gemini / Antigravity
     Gemini,
@@ -61,7 +467,22 @@ pub enum ProviderArg {
     All,
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+impl ProviderArg {
+    /// Name as written on the command line
+    pub fn name(self) -> String {
+        match self {
+            ProviderArg::Gemini => "gemini",
+            ProviderArg::Codex => "codex",
+            ProviderArg::Copilot => "copilot",
+            ProviderArg::Claude => "claude",
+            ProviderArg::All => "all",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 pub enum OutputFormat {
     /// Pretty table format with colors
     Table,
@@ -69,4 +490,207 @@ pub enum OutputFormat {
     Json,
     /// Simple text format
     Simple,
+    /// Single-line summary for prompts and scripts
+    Summary,
+    /// One JSON line (text, severity, details) for OpenCode plugins and status bars
+    Statusline,
+    /// Colored summary line for Raycast script commands in inline mode
+    Raycast,
+    /// Alfred script filter JSON, one item per window
+    Alfred,
+    /// JSON with color and icon hints for KDE Plasma widgets
+    Plasma,
+    /// GitHub Actions workflow commands, plus a table appended to the job summary
+    Gha,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    /// Rounded UTF-8 borders with section separators
+    #[default]
+    Rounded,
+    /// Header underline and column separators only, for small terminals
+    Compact,
+    /// Plain ASCII borders
+    Ascii,
+    /// No borders at all
+    Borderless,
+}
+
+/// A column of the table output
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum TableColumn {
+    /// Provider (or model family with --group-by model)
+    Provider,
+    /// Model or window (or source with --group-by model)
+    Model,
+    Usage,
+    Resets,
+    Status,
+    /// Billing beyond the plan
+    Overage,
+    /// Progress through the window, as with --pace
+    Pace,
+    /// Warning/critical limits, as with --show-thresholds
+    Limits,
+    /// Estimated spend beyond the plan, at the `[cost]` prices
+    Cost,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum ErrorFormat {
+    /// Human-readable warnings
+    #[default]
+    Text,
+    /// A single JSON document with error kind, provider, HTTP status and retryability
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum UsageView {
+    /// Percentage of the quota consumed
+    #[default]
+    Used,
+    /// Percentage of the quota left
+    Remaining,
+}
+
+/// Span of `ocu report`, ending now
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    /// The last 7 days
+    #[default]
+    Week,
+    /// The last 30 days
+    Month,
+}
+
+impl ReportPeriod {
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            ReportPeriod::Week => chrono::Duration::days(7),
+            ReportPeriod::Month => chrono::Duration::days(30),
+        }
+    }
+}
+
+/// Document format of `ocu report`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    /// A self-contained page
+    Html,
+}
+
+/// What `ocu sessions` attributes token usage to
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum SessionGroup {
+    /// The directory each session ran in
+    #[default]
+    Project,
+    /// Each session, by title
+    Session,
+    /// Each provider and model
+    Model,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum GroupBy {
+    /// One section per provider (and Gemini account)
+    #[default]
+    Provider,
+    /// One section per model family, listing every quota that can serve it
+    Model,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum SortKey {
+    /// Usage percentage (most used first)
+    Usage,
+    /// Reset time (soonest first)
+    Reset,
+    /// Provider name
+    Name,
+}
+
+impl SortKey {
+    /// Natural direction for the key, so the most at-risk entries come first
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortKey::Usage => SortOrder::Desc,
+            SortKey::Reset | SortKey::Name => SortOrder::Asc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum SortOrder {
+    /// Ascending
+    #[default]
+    Asc,
+    /// Descending
+    Desc,
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    fn validated(argv: &[&str]) -> Result<Args, clap::Error> {
+        let args = Args::try_parse_from(std::iter::once("ocu").chain(argv.iter().copied()))?;
+        args.validate()?;
+        Ok(args)
+    }
+
+    fn rejection(argv: &[&str]) -> clap::Error {
+        match validated(argv) {
+            Ok(_) => panic!("{:?} should be rejected", argv),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_provider() {
+        let err = rejection(&["-p", "claude", "-p", "claude"]);
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        assert!(err.to_string().contains("'--provider claude' was provided more than once"));
+    }
+
+    #[test]
+    fn rejects_all_combined_with_a_provider() {
+        let err = rejection(&["-p", "all", "-p", "codex"]);
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        assert!(err.to_string().contains("'--provider all'"));
+    }
+
+    #[test]
+    fn rejects_listen_json_with_a_subcommand() {
+        let err = rejection(&["--listen-json", "60", "prompt"]);
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        assert!(err.to_string().contains("'--listen-json'"));
+    }
+
+    #[test]
+    fn accepts_a_valid_combination() {
+        let args = validated(&["-p", "claude", "-p", "codex", "--format", "json", "--listen-json", "60"]).unwrap();
+        assert_eq!(args.provider, vec![ProviderArg::Claude, ProviderArg::Codex]);
+        assert_eq!(args.output_format(), OutputFormat::Json);
+        assert_eq!(args.listen_json, Some(60));
+    }
 }
@@ -0,0 +1,276 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::budget::BudgetConfig;
+use crate::cli::{TableColumn, TableStyle, UsageView};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::daemon::ServeConfig;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::discord::DiscordConfig;
+use crate::error::{QuotaError, Result};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export::cloudwatch::CloudWatchConfig;
+use crate::export::datadog::DatadogConfig;
+use crate::export::otlp::OtlpConfig;
+use crate::export::team::TeamConfig;
+use crate::glob::matches_filter;
+use crate::notify::gotify::GotifyConfig;
+use crate::notify::matrix::MatrixConfig;
+use crate::notify::NotifyConfig;
+use crate::output::colors::ColorScheme;
+use crate::output::time::{is_valid_time_format, DurationStyle, Locale};
+use crate::providers::{CostModel, Thresholds};
+use crate::report::ReportConfig;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::slack::SlackConfig;
+
+/// User configuration read from ~/.config/ocu/config.toml
+/// Every setting is optional; command-line flags take precedence
+/// The wasm32 build has no servers or CloudWatch, so it rejects their sections
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub display: DisplayConfig,
+    pub colors: ColorScheme,
+    pub thresholds: Thresholds,
+    pub cost: CostModel,
+    pub budget: BudgetConfig,
+    pub gemini: GeminiConfig,
+    pub client: ClientIdentity,
+    pub otlp: OtlpConfig,
+    pub datadog: DatadogConfig,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub cloudwatch: CloudWatchConfig,
+    pub team: TeamConfig,
+    pub notify: NotifyConfig,
+    pub report: ReportConfig,
+    pub gotify: Option<GotifyConfig>,
+    pub matrix: Option<MatrixConfig>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub serve: ServeConfig,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub slack: SlackConfig,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub discord: DiscordConfig,
+}
+
+/// `[display]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// strftime pattern for absolute timestamps (e.g. "%a %H:%M")
+    pub time_format: Option<String>,
+    /// Relative duration style: "compact", "long" or "clock"
+    pub duration_style: Option<DurationStyle>,
+    /// Language for relative durations: "en", "fr", "es" or "pt"
+    pub locale: Option<Locale>,
+    /// Table border style: "rounded", "compact", "ascii" or "borderless"
+    pub style: Option<TableStyle>,
+    /// Table columns to show, e.g. ["provider", "model", "usage", "resets"]
+    pub columns: Option<Vec<TableColumn>>,
+    /// Set to false to show plain status words instead of ✓/⚠️/✗ glyphs
+    pub emoji: Option<bool>,
+    /// Show usage as "used" or "remaining" percentages
+    pub show: Option<UsageView>,
+}
+
+/// `[client]` section: how ocu identifies itself to provider APIs
+/// Packagers and forks can set accurate values here instead of patching each provider
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientIdentity {
+    /// User-Agent sent to the Copilot API (defaults to `ocu/<crate version>`)
+    pub user_agent: String,
+    /// Editor version the Copilot API expects, e.g. "vscode/1.96.2"
+    pub editor_version: String,
+    /// Antigravity release reported in Gemini's User-Agent, e.g. "1.15.8"
+    pub antigravity_version: String,
+}
+
+impl Default for ClientIdentity {
+    fn default() -> Self {
+        Self {
+            user_agent: concat!("ocu/", env!("CARGO_PKG_VERSION")).to_string(),
+            editor_version: "vscode/1.96.2".to_string(),
+            antigravity_version: "1.15.8".to_string(),
+        }
+    }
+}
+
+/// `[gemini]` section: how Antigravity models are grouped into shared quota buckets
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeminiConfig {
+    /// Models left out entirely, even with --detailed (substring or glob, case-insensitive)
+    pub hide: Vec<String>,
+    /// Quota buckets in display order; a model joins the first bucket it matches,
+    /// and models matching none are only listed with --detailed
+    pub buckets: Vec<BucketRule>,
+}
+
+/// One `[[gemini.buckets]]` entry
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BucketRule {
+    /// Name shown for the bucket, e.g. "Claude Models"
+    pub name: String,
+    /// Model display names that share the bucket's quota (substring or glob, case-insensitive)
+    pub models: Vec<String>,
+    /// Models kept out of the bucket even when they match `models`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl BucketRule {
+    fn new(name: &str, models: &[&str], exclude: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            models: models.iter().map(|m| m.to_string()).collect(),
+            exclude: exclude.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    /// Whether a model (by display name) belongs to this bucket
+    pub fn matches(&self, model: &str) -> bool {
+        self.models.iter().any(|p| matches_filter(p, model))
+            && !self.exclude.iter().any(|p| matches_filter(p, model))
+    }
+}
+
+impl GeminiConfig {
+    /// Whether a model is internal or a test model that should not be shown
+    pub fn is_hidden(&self, model: &str) -> bool {
+        self.hide.iter().any(|p| matches_filter(p, model))
+    }
+
+    /// Index of the bucket a model's quota counts against
+    pub fn bucket_for(&self, model: &str) -> Option<usize> {
+        self.buckets.iter().position(|b| b.matches(model))
+    }
+}
+
+/// The groupings Antigravity used when this release was made
+impl Default for GeminiConfig {
+    fn default() -> Self {
+        Self {
+            hide: vec!["chat_*".to_string(), "rev19*".to_string()],
+            buckets: vec![
+                BucketRule::new("Claude Models", &["claude", "gpt-oss"], &[]),
+                // Gemini 2.5 variants have their own, unlisted quota
+                BucketRule::new("Gemini Flash", &["*gemini*flash*"], &["2.5"]),
+                BucketRule::new("Gemini 3 Pro", &["gemini 3 pro"], &["gemini 3 pro image"]),
+                BucketRule::new("Gemini 3 Pro Image", &["gemini 3 pro image"], &[]),
+            ],
+        }
+    }
+}
+
+impl Config {
+    /// Default config file location (`$XDG_CONFIG_HOME/ocu/config.toml`, by default under ~/.config)
+    pub fn default_path() -> Option<PathBuf> {
+        crate::paths::config_home().map(|dir| dir.join("ocu").join("config.toml"))
+    }
+
+    /// Load the config from the given path, or the default location
+    /// A missing file at the default location yields the default config
+    pub fn load(path: Option<&Path>) -> Result<Config> {
+        let (path, required) = match path {
+            Some(p) => (p.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(p) => (p, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        if !path.exists() {
+            if required {
+                return Err(QuotaError::ConfigError(format!(
+                    "{} does not exist",
+                    path.display()
+                )));
+            }
+            return Ok(Config::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let config: Config = toml::from_str(&content)
+            .map_err(|e| QuotaError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.notify.summary_time()?;
+        if let Some(pattern) = &self.display.time_format {
+            if !is_valid_time_format(pattern) {
+                return Err(QuotaError::ConfigError(format!(
+                    "invalid display.time_format: {:?}",
+                    pattern
+                )));
+            }
+        }
+        if self.colors.usage_warning > self.colors.usage_critical {
+            return Err(QuotaError::ConfigError(format!(
+                "colors.usage_warning ({}) must not exceed colors.usage_critical ({})",
+                self.colors.usage_warning, self.colors.usage_critical
+            )));
+        }
+        self.thresholds.validate()?;
+        let prices = std::iter::once(("copilot_premium_request", self.cost.copilot_premium_request)).chain(
+            self.cost
+                .models
+                .iter()
+                .flat_map(|p| [("models input", p.input), ("models output", p.output)]),
+        );
+        self.budget.validate()?;
+        self.report.validate()?;
+        #[cfg(not(target_arch = "wasm32"))]
+        self.serve.validate()?;
+        for (key, price) in prices {
+            if !price.is_finite() || price < 0.0 {
+                return Err(QuotaError::ConfigError(format!(
+                    "cost.{} must be a price of zero or more, got {}",
+                    key, price
+                )));
+            }
+        }
+        for (key, value) in [
+            ("user_agent", &self.client.user_agent),
+            ("editor_version", &self.client.editor_version),
+            ("antigravity_version", &self.client.antigravity_version),
+        ] {
+            if value.trim().is_empty() || reqwest::header::HeaderValue::from_str(value).is_err() {
+                return Err(QuotaError::ConfigError(format!(
+                    "client.{} must be a non-empty header value, got {:?}",
+                    key, value
+                )));
+            }
+        }
+        for bucket in &self.gemini.buckets {
+            if bucket.name.trim().is_empty() || bucket.models.is_empty() {
+                return Err(QuotaError::ConfigError(format!(
+                    "gemini.buckets entry {:?} needs a name and at least one model pattern",
+                    bucket.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,500 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot, watch};
+
+use crate::cache::CachedResults;
+use crate::cli::ProviderArg;
+use crate::error::{QuotaError, Result};
+use crate::history::{SampleWindow, UsageHistory, UsageSample};
+use crate::output::json::{format_json_line, parse_json};
+use crate::output::redact::mask_email;
+use crate::output::FormatOptions;
+use crate::providers::ProviderData;
+use crate::run::{after_fetch, fetch_all, provider_names, RunOptions};
+use crate::server::{read_request, respond};
+use crate::{slack, streamdeck};
+
+/// Where `ocu serve` listens unless told otherwise
+pub const DEFAULT_LISTEN: SocketAddr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 47383));
+
+/// `[serve]` section: the `ocu serve` HTTP API
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServeConfig {
+    /// Address `ocu serve` listens on without `--listen`, and where `ocu prompt` looks for it
+    pub listen: Option<SocketAddr>,
+    /// Shared secret required by `POST /v1/refresh`; `OCU_SERVE_TOKEN` is used when unset,
+    /// and without either anyone reaching the server can trigger a refresh.
+    /// With `--aggregate`, it lets viewers read the team view without a member token
+    pub token: Option<String>,
+    /// Team members allowed to push to `ocu serve --aggregate`, by name, with their tokens
+    pub members: BTreeMap<String, String>,
+}
+
+impl ServeConfig {
+    /// The configured address, or `DEFAULT_LISTEN`
+    pub fn listen(&self) -> SocketAddr {
+        self.listen.unwrap_or(DEFAULT_LISTEN)
+    }
+
+    /// Reject empty tokens: a request without credentials would match them
+    pub fn validate(&self) -> Result<()> {
+        if self.token.as_deref() == Some("") {
+            return Err(QuotaError::ConfigError("serve.token must not be empty".to_string()));
+        }
+        if let Some((name, _)) = self.members.iter().find(|(_, token)| token.is_empty()) {
+            return Err(QuotaError::ConfigError(format!(
+                "serve.members.{} needs a token; an empty one lets anyone push as {}",
+                name, name
+            )));
+        }
+        Ok(())
+    }
+
+    /// The configured token, falling back to `OCU_SERVE_TOKEN`
+    pub fn token(&self) -> Option<String> {
+        self.token
+            .clone()
+            .or_else(|| std::env::var("OCU_SERVE_TOKEN").ok().filter(|v| !v.is_empty()))
+    }
+}
+
+/// The latest report of the `ocu serve` listening on `listen`, as `GET /v1/usage` answers it
+/// Goes straight to the address, bypassing any proxy, and gives up after `timeout`
+pub async fn latest_report(listen: SocketAddr, timeout: Duration) -> Result<CachedResults> {
+    let client = reqwest::Client::builder().no_proxy().timeout(timeout).build()?;
+    let response = client.get(format!("http://{}/v1/usage", listen)).send().await?;
+    if !response.status().is_success() {
+        return Err(QuotaError::HttpError {
+            status: response.status().as_u16(),
+            message: format!("ocu serve at {} answered {}", listen, response.status()),
+        });
+    }
+    let report = parse_json(&response.text().await?)?;
+    let fetched_at = chrono::DateTime::parse_from_rfc3339(&report.fetched_at)
+        .map_err(|e| QuotaError::ApiError(format!("ocu serve at {} sent a bad fetched_at: {}", listen, e)))?;
+    Ok(CachedResults {
+        fetched_at: fetched_at.with_timezone(&chrono::Utc),
+        providers: report.providers,
+    })
+}
+
+/// What the server answers with, rebuilt after every fetch
+#[derive(Clone)]
+struct Latest {
+    /// `GET /v1/usage`: the `--format json` report
+    report: String,
+    /// `POST /slack`: the quota table Slack posts to the channel
+    table: String,
+}
+
+impl Latest {
+    fn new(data: &[ProviderData], fetched_at: DateTime<Utc>, format: &FormatOptions) -> Self {
+        Latest {
+            report: report(data, fetched_at, format),
+            table: slack::message(data, format),
+        }
+    }
+}
+
+/// A `POST /v1/refresh`, answered by the fetch loop once the providers are fetched
+struct Refresh {
+    providers: Vec<ProviderArg>,
+    reply: oneshot::Sender<Result<String>>,
+}
+
+/// Serve the HTTP API on `listener`:
+/// - `GET /v1/usage`: the latest `--format json` report
+/// - `POST /v1/refresh`: fetch now and answer with the fresh report; `?provider=claude` (repeatable)
+///   refreshes only those providers
+/// - `GET /`, `POST /search` and `POST /query`: the usage history as a Grafana JSON datasource,
+///   one series per window, named "provider/label" or "provider/account/label"
+/// - `POST /slack`: Slack `/ocu` slash commands, answered with the quota table; only with a signing secret
+///   in `[slack]` or `SLACK_SIGNING_SECRET`
+/// - `GET /buttons`: per-provider key states for a Stream Deck plugin (`text`, `severity`, `buttons` with
+///   `title`, `percent`, `status`, `color`)
+/// - `GET /events`: the same document as Server-Sent Events, pushed on connect and whenever it changes
+///
+/// With a `token`, refreshes must send it as `Authorization: Bearer <token>`. Providers are fetched on
+/// start and every `interval`, one fetch at a time, and each fetch goes through `after_fetch`; with a
+/// `[report]` schedule, the first fetch after a report falls due posts it. Runs until the listener fails
+/// or the task is dropped
+pub async fn serve(
+    listener: TcpListener,
+    token: Option<String>,
+    options: RunOptions,
+    format: FormatOptions,
+    interval: Duration,
+) -> Result<()> {
+    let outcome = fetch_all(options.clone()).await?;
+    after_fetch(&outcome, &options).await;
+    let mut data = outcome.results;
+    let fetched_at = chrono::Utc::now();
+    let (tx, rx) = watch::channel(Latest::new(&data, fetched_at, &format));
+    let (deck_tx, deck_rx) = watch::channel(streamdeck::deck(&data, &format));
+    let (refresh_tx, mut refresh_rx) = mpsc::channel::<Refresh>(16);
+    let redact = format.redact;
+    let slack_secret = options.config.slack.signing_secret().ok();
+    let mut report_due = options.config.report.next_due(Utc::now());
+
+    let accept = async move {
+        loop {
+            let (stream, _) = listener
+                .accept()
+                .await
+                .map_err(|e| QuotaError::ApiError(format!("HTTP server: {}", e)))?;
+            let (token, slack_secret) = (token.clone(), slack_secret.clone());
+            let (rx, deck_rx, refresh_tx) = (rx.clone(), deck_rx.clone(), refresh_tx.clone());
+            tokio::spawn(async move {
+                // A client that hangs up mid-request needs no answer
+                let _ = handle(stream, token.as_deref(), slack_secret.as_deref(), rx, deck_rx, refresh_tx, redact).await;
+            });
+        }
+    };
+    let refresh = async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            let (providers, reply) = tokio::select! {
+                _ = ticker.tick() => (Vec::new(), None),
+                Some(request) = refresh_rx.recv() => (request.providers, Some(request.reply)),
+            };
+            let request = RunOptions {
+                providers: if providers.is_empty() { options.providers.clone() } else { providers },
+                ..options.clone()
+            };
+            let fetched = match fetch_all(request.clone()).await {
+                Ok(outcome) => {
+                    after_fetch(&outcome, &request).await;
+                    let fetched_at = chrono::Utc::now();
+                    merge(&mut data, &outcome.results);
+                    tx.send_replace(Latest::new(&data, fetched_at, &format));
+                    // Keys only redraw when usage changed, not on every refresh
+                    let deck = streamdeck::deck(&data, &format);
+                    deck_tx.send_if_modified(|current| {
+                        let changed = *current != deck;
+                        *current = deck;
+                        changed
+                    });
+                    Ok(report(&outcome.results, fetched_at, &format))
+                }
+                Err(e) => Err(e),
+            };
+            if let Some(reply) = reply {
+                let _ = reply.send(fetched);
+            }
+            if report_due.is_some_and(|due| due <= Utc::now()) {
+                let report = &options.config.report;
+                match report.post_scheduled(Utc::now(), &format.thresholds, format.redact, options.fetch.timeout).await {
+                    Ok(()) => report_due = report.next_due(Utc::now()),
+                    // Tried again after the next fetch
+                    Err(e) => eprintln!("Warning: could not post the scheduled report: {}", e),
+                }
+            }
+        }
+    };
+    // Refreshing never ends; only the listener can fail
+    tokio::select! {
+        result = accept => result,
+        () = refresh => Ok(()),
+    }
+}
+
+fn report(data: &[ProviderData], fetched_at: DateTime<Utc>, format: &FormatOptions) -> String {
+    let options = FormatOptions {
+        fetched_at: Some(fetched_at),
+        ..format.clone()
+    };
+    format_json_line(data, &options)
+}
+
+/// Replace each provider's entry with its fresh one, keeping the order
+fn merge(data: &mut Vec<ProviderData>, fresh: &[ProviderData]) {
+    for entry in fresh {
+        match data.iter_mut().find(|d| d.provider_name() == entry.provider_name()) {
+            Some(slot) => *slot = entry.clone(),
+            None => data.push(entry.clone()),
+        }
+    }
+    let order = provider_names(&[]);
+    data.sort_by_key(|d| order.iter().position(|name| *name == d.provider_name()));
+}
+
+/// Body of a Grafana `POST /search`
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SearchRequest {
+    /// Text the series names must contain; empty lists them all
+    target: String,
+}
+
+/// Body of a Grafana `POST /query`
+#[derive(Deserialize)]
+struct QueryRequest {
+    range: QueryRange,
+    #[serde(default)]
+    targets: Vec<QueryTarget>,
+}
+
+#[derive(Deserialize)]
+struct QueryRange {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct QueryTarget {
+    #[serde(default)]
+    target: String,
+}
+
+/// Series name of a window in the usage history
+fn series_name(window: &SampleWindow, redact: bool) -> String {
+    match &window.account {
+        Some(account) if redact => format!("{}/{}/{}", window.provider, mask_email(account), window.label),
+        Some(account) => format!("{}/{}/{}", window.provider, account, window.label),
+        None => format!("{}/{}", window.provider, window.label),
+    }
+}
+
+/// Names of every series in the history containing `filter`, case-insensitively, sorted
+fn search_series(samples: &[UsageSample], filter: &str, redact: bool) -> Vec<String> {
+    let filter = filter.to_lowercase();
+    let names: BTreeSet<String> = samples
+        .iter()
+        .flat_map(|sample| sample.windows.iter().map(|w| series_name(w, redact)))
+        .filter(|name| name.to_lowercase().contains(&filter))
+        .collect();
+    names.into_iter().collect()
+}
+
+/// The requested series over the query's time range, as Grafana's `[value, unix_ms]` datapoints
+fn query_series(samples: &[UsageSample], query: &QueryRequest, redact: bool) -> serde_json::Value {
+    let in_range: Vec<&UsageSample> = samples
+        .iter()
+        .filter(|s| s.at >= query.range.from && s.at <= query.range.to)
+        .collect();
+    let series: Vec<serde_json::Value> = query
+        .targets
+        .iter()
+        .filter(|t| !t.target.is_empty())
+        .map(|t| {
+            let datapoints: Vec<(f64, i64)> = in_range
+                .iter()
+                .flat_map(|sample| {
+                    sample
+                        .windows
+                        .iter()
+                        .filter(|w| series_name(w, redact) == t.target)
+                        .map(|w| (w.used_percent, sample.at.timestamp_millis()))
+                })
+                .collect();
+            json!({ "target": t.target, "datapoints": datapoints })
+        })
+        .collect();
+    json!(series)
+}
+
+/// `provider=` values of the query string; Err names the first unknown one
+fn requested_providers(query: &str) -> std::result::Result<Vec<ProviderArg>, String> {
+    let known = [ProviderArg::Gemini, ProviderArg::Codex, ProviderArg::Copilot, ProviderArg::Claude];
+    form_urlencoded::parse(query.as_bytes())
+        .filter(|(key, _)| key == "provider")
+        .map(|(_, name)| {
+            known
+                .into_iter()
+                .find(|p| p.name() == name)
+                .ok_or_else(|| name.into_owned())
+        })
+        .collect()
+}
+
+async fn handle(
+    mut stream: TcpStream,
+    token: Option<&str>,
+    slack_secret: Option<&str>,
+    latest: watch::Receiver<Latest>,
+    deck: watch::Receiver<String>,
+    refresh: mpsc::Sender<Refresh>,
+    redact: bool,
+) -> std::io::Result<()> {
+    let Some(request) = read_request(&mut stream).await? else {
+        return Ok(());
+    };
+    let error = |message: &str| json!({ "error": message }).to_string();
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/v1/usage") => {
+            let body = latest.borrow().report.clone();
+            respond(&mut stream, "200 OK", "application/json", body.as_bytes()).await
+        }
+        ("POST", "/v1/refresh") => {
+            if token.is_some_and(|token| !request.has_bearer(token)) {
+                let body = error("missing or wrong bearer token");
+                return respond(&mut stream, "401 Unauthorized", "application/json", body.as_bytes()).await;
+            }
+            let providers = match requested_providers(&request.query) {
+                Ok(providers) => providers,
+                Err(name) => {
+                    let body = error(&format!("unknown provider {:?}", name));
+                    return respond(&mut stream, "400 Bad Request", "application/json", body.as_bytes()).await;
+                }
+            };
+            let (reply, fetched) = oneshot::channel();
+            let _ = refresh.send(Refresh { providers, reply }).await;
+            match fetched.await {
+                Ok(Ok(body)) => respond(&mut stream, "200 OK", "application/json", body.as_bytes()).await,
+                Ok(Err(e)) => {
+                    let body = error(&e.to_string());
+                    respond(&mut stream, "503 Service Unavailable", "application/json", body.as_bytes()).await
+                }
+                Err(_) => Ok(()),
+            }
+        }
+        // Grafana's datasource check
+        ("GET", "/") => respond(&mut stream, "200 OK", "text/plain", b"ocu").await,
+        ("POST", "/search" | "/query") => {
+            let samples = match UsageHistory::load_since(None) {
+                Ok(samples) => samples,
+                Err(e) => {
+                    let body = error(&format!("could not read usage history: {}", e));
+                    return respond(&mut stream, "500 Internal Server Error", "application/json", body.as_bytes()).await;
+                }
+            };
+            // Grafana sends an empty body when testing /search
+            let body = if request.body.is_empty() { b"{}".as_slice() } else { &request.body };
+            let answer = if request.path == "/search" {
+                serde_json::from_slice(body).map(|search: SearchRequest| json!(search_series(&samples, &search.target, redact)))
+            } else {
+                serde_json::from_slice(body).map(|query: QueryRequest| query_series(&samples, &query, redact))
+            };
+            match answer {
+                Ok(answer) => respond(&mut stream, "200 OK", "application/json", answer.to_string().as_bytes()).await,
+                Err(e) => {
+                    let body = error(&format!("not a Grafana {} request: {}", request.path, e));
+                    respond(&mut stream, "400 Bad Request", "application/json", body.as_bytes()).await
+                }
+            }
+        }
+        ("GET", "/buttons") => {
+            let body = deck.borrow().clone();
+            respond(&mut stream, "200 OK", "application/json", body.as_bytes()).await
+        }
+        ("GET", "/events") => streamdeck::events(&mut stream, deck).await,
+        ("POST", "/slack") => match slack_secret {
+            Some(secret) => {
+                let table = latest.borrow().table.clone();
+                slack::answer(&mut stream, &request, secret, &table).await
+            }
+            None => respond(&mut stream, "404 Not Found", "text/plain", b"").await,
+        },
+        (_, "/v1/usage" | "/v1/refresh" | "/" | "/search" | "/query" | "/slack" | "/buttons" | "/events") => {
+            respond(&mut stream, "405 Method Not Allowed", "text/plain", b"").await
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", b"").await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_empty_tokens() {
+        let members = |token: &str| BTreeMap::from([("alice".to_string(), token.to_string())]);
+        let config = ServeConfig {
+            members: members(""),
+            ..ServeConfig::default()
+        };
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("serve.members.alice"), "{}", err);
+        let config = ServeConfig {
+            token: Some(String::new()),
+            members: members("alice-token"),
+            ..ServeConfig::default()
+        };
+        assert!(config.validate().is_err());
+        let config = ServeConfig {
+            token: Some("viewer-token".to_string()),
+            members: members("alice-token"),
+            ..ServeConfig::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    fn sample(at: &str, windows: &[(&str, Option<&str>, &str, f64)]) -> UsageSample {
+        UsageSample {
+            at: at.parse().unwrap(),
+            windows: windows
+                .iter()
+                .map(|&(provider, account, label, used_percent)| SampleWindow {
+                    provider: provider.to_string(),
+                    account: account.map(str::to_string),
+                    label: label.to_string(),
+                    used_percent,
+                })
+                .collect(),
+            failed: BTreeMap::new(),
+            cost_usd: BTreeMap::new(),
+        }
+    }
+
+    fn history() -> Vec<UsageSample> {
+        vec![
+            sample("2026-10-16T08:00:00Z", &[("claude", None, "5h Window", 10.0)]),
+            sample(
+                "2026-10-16T09:00:00Z",
+                &[("claude", None, "5h Window", 25.0), ("gemini", Some("jane@example.com"), "Gemini Flash", 40.0)],
+            ),
+            sample("2026-10-16T10:00:00Z", &[("claude", None, "5h Window", 60.0)]),
+        ]
+    }
+
+    #[test]
+    fn lists_series_matching_the_search() {
+        let samples = history();
+        assert_eq!(
+            search_series(&samples, "", false),
+            ["claude/5h Window", "gemini/jane@example.com/Gemini Flash"]
+        );
+        assert_eq!(search_series(&samples, "FLASH", true), ["gemini/j***@example.com/Gemini Flash"]);
+    }
+
+    #[test]
+    fn answers_queries_within_the_range() {
+        let query: QueryRequest = serde_json::from_value(json!({
+            "range": { "from": "2026-10-16T08:30:00.000Z", "to": "2026-10-16T10:00:00.000Z" },
+            "targets": [{ "target": "claude/5h Window", "refId": "A" }, { "target": "codex/Primary", "refId": "B" }]
+        }))
+        .unwrap();
+        let nine = "2026-10-16T09:00:00Z".parse::<DateTime<Utc>>().unwrap().timestamp_millis();
+        assert_eq!(
+            query_series(&history(), &query, false),
+            json!([
+                { "target": "claude/5h Window", "datapoints": [[25.0, nine], [60.0, nine + 3_600_000]] },
+                { "target": "codex/Primary", "datapoints": [] }
+            ])
+        );
+    }
+}
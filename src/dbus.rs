@@ -0,0 +1,157 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use zbus::object_server::SignalEmitter;
+
+use crate::error::{QuotaError, Result};
+use crate::output::FormatOptions;
+use crate::providers::{ProviderData, ProviderStatus};
+use crate::rpc::Snapshot;
+use crate::run::RunOptions;
+
+/// Well-known name claimed on the session bus
+pub const BUS_NAME: &str = "net.smux.Ocu";
+/// Path of the usage object
+pub const OBJECT_PATH: &str = "/net/smux/Ocu";
+
+/// The `net.smux.Ocu1` interface: the latest results as properties, a `Changed` signal and `Refresh()`
+struct Usage {
+    text: String,
+    severity: String,
+    report: String,
+    providers: HashMap<String, (String, f64, i64)>,
+    refresh: Arc<Notify>,
+}
+
+#[zbus::interface(name = "net.smux.Ocu1")]
+impl Usage {
+    /// Fetch now instead of waiting for the next interval
+    async fn refresh(&self) {
+        self.refresh.notify_one();
+    }
+
+    /// Short summary, as `--format summary`
+    #[zbus(property)]
+    fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    /// "ok", "warning" or "error", as `--format statusline`
+    #[zbus(property)]
+    fn severity(&self) -> String {
+        self.severity.clone()
+    }
+
+    /// Per-provider details as JSON, the `details` object of `--format statusline`
+    #[zbus(property)]
+    fn report(&self) -> String {
+        self.report.clone()
+    }
+
+    /// Per provider: status ("ok", "warning", "error" or "failed"), highest used percentage
+    /// (-1 when unknown) and soonest reset as Unix seconds (0 when unknown)
+    #[zbus(property)]
+    fn providers(&self) -> HashMap<String, (String, f64, i64)> {
+        self.providers.clone()
+    }
+
+    /// Usage changed; the properties already hold the new values
+    #[zbus(signal)]
+    async fn changed(emitter: &SignalEmitter<'_>, text: &str, severity: &str) -> zbus::Result<()>;
+}
+
+fn bus_error(e: zbus::Error) -> QuotaError {
+    QuotaError::ApiError(format!("D-Bus: {}", e))
+}
+
+/// Serve usage on the session bus, fetching on start, every `interval` and on `Refresh()`
+/// Runs until the connection fails or the task is dropped
+pub async fn serve(options: RunOptions, format: FormatOptions, interval: Duration) -> Result<()> {
+    let refresh = Arc::new(Notify::new());
+    let usage = Usage {
+        text: String::new(),
+        severity: "ok".to_string(),
+        report: String::new(),
+        providers: HashMap::new(),
+        refresh: refresh.clone(),
+    };
+    let connection = zbus::connection::Builder::session()
+        .and_then(|b| b.name(BUS_NAME))
+        .and_then(|b| b.serve_at(OBJECT_PATH, usage))
+        .map_err(bus_error)?
+        .build()
+        .await
+        .map_err(bus_error)?;
+    let iface = connection
+        .object_server()
+        .interface::<_, Usage>(OBJECT_PATH)
+        .await
+        .map_err(bus_error)?;
+
+    let mut snapshot = Snapshot::default();
+    loop {
+        let changed = snapshot.refresh(&options, &format).await;
+        if changed {
+            let text = snapshot.document["text"].as_str().unwrap_or_default().to_string();
+            let severity = snapshot.document["severity"].as_str().unwrap_or("ok").to_string();
+            {
+                let mut usage = iface.get_mut().await;
+                usage.text = text.clone();
+                usage.severity = severity.clone();
+                usage.report = snapshot.document["details"].to_string();
+                usage.providers = snapshot
+                    .data
+                    .iter()
+                    .map(|d| (d.provider_name().to_string(), provider_summary(d, &format)))
+                    .collect();
+            }
+            let emitter = iface.signal_emitter();
+            let usage = iface.get().await;
+            usage.text_changed(emitter).await.map_err(bus_error)?;
+            usage.severity_changed(emitter).await.map_err(bus_error)?;
+            usage.report_changed(emitter).await.map_err(bus_error)?;
+            usage.providers_changed(emitter).await.map_err(bus_error)?;
+            drop(usage);
+            Usage::changed(emitter, &text, &severity).await.map_err(bus_error)?;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = refresh.notified() => {}
+        }
+    }
+}
+
+fn provider_summary(data: &ProviderData, format: &FormatOptions) -> (String, f64, i64) {
+    let status = match data {
+        ProviderData::Failed { .. } => "failed",
+        _ => match data.status_with(&format.thresholds) {
+            ProviderStatus::Ok => "ok",
+            ProviderStatus::Warning => "warning",
+            ProviderStatus::Error => "error",
+        },
+    };
+    (
+        status.to_string(),
+        data.worst_used_percent().unwrap_or(-1.0),
+        data.soonest_reset().map_or(0, |t| t.timestamp()),
+    )
+}
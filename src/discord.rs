@@ -0,0 +1,301 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Local, NaiveTime};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::cache::CachedResults;
+use crate::cli::UsageView;
+use crate::daemon::latest_report;
+use crate::error::{QuotaError, Result};
+use crate::export::deliver;
+use crate::output::statusline::build_statusline;
+use crate::output::time::describe_reset;
+use crate::output::{prepare, FormatOptions};
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::{ProviderData, ProviderStatus};
+use crate::server::{decode_hex, read_request, respond};
+
+const API: &str = "https://discord.com/api/v10";
+/// How long a reply waits on `ocu serve` before falling back to the cache; Discord wants an answer within three seconds
+const SERVE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Discord cuts embed field values at 1024 characters
+const MAX_FIELD: usize = 1024;
+
+/// `[discord]` section: the application `ocu bot discord` runs as
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    /// Bot token; `DISCORD_BOT_TOKEN` is used when unset
+    pub token: Option<String>,
+    /// Application ID, to register the `/usage` command
+    pub application_id: Option<String>,
+    /// Application public key, to verify interactions
+    pub public_key: Option<String>,
+    /// Channel receiving a daily summary; none is posted when unset
+    pub channel_id: Option<String>,
+    /// Local time of the daily summary, "HH:MM" (default "09:00")
+    pub summary_at: Option<String>,
+}
+
+/// Answers the `/usage` slash command with an embed and posts a daily summary
+pub struct DiscordBot {
+    token: String,
+    application_id: String,
+    public_key: Vec<u8>,
+    channel_id: Option<String>,
+    summary_at: NaiveTime,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl DiscordBot {
+    pub fn from_config(config: &DiscordConfig) -> Result<Self> {
+        let missing = |what: &str| QuotaError::ConfigError(format!("no Discord {}: set [discord] {}", what, what));
+        let token = config
+            .token
+            .clone()
+            .or_else(|| std::env::var("DISCORD_BOT_TOKEN").ok().filter(|v| !v.is_empty()))
+            .ok_or_else(|| QuotaError::ConfigError("no Discord bot token: set DISCORD_BOT_TOKEN or [discord] token".to_string()))?;
+        let application_id = config.application_id.clone().ok_or_else(|| missing("application_id"))?;
+        let public_key = config.public_key.as_deref().ok_or_else(|| missing("public_key"))?;
+        let public_key = decode_hex(public_key)
+            .ok_or_else(|| QuotaError::ConfigError("[discord] public_key is not a hex key".to_string()))?;
+        let summary_at = match config.summary_at.as_deref() {
+            Some(time) => NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| {
+                QuotaError::ConfigError(format!("[discord] summary_at must be \"HH:MM\", got \"{}\"", time))
+            })?,
+            None => NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+        };
+        Ok(Self {
+            token,
+            application_id,
+            public_key,
+            channel_id: config.channel_id.clone(),
+            summary_at,
+            transport: Arc::new(ReqwestTransport::default()),
+        })
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Whether an interaction carries a valid `X-Signature-Ed25519` from Discord
+    pub fn verify(&self, timestamp: &str, body: &[u8], signature: &str) -> bool {
+        let Some(signature) = decode_hex(signature) else {
+            return false;
+        };
+        let mut message = timestamp.as_bytes().to_vec();
+        message.extend_from_slice(body);
+        UnparsedPublicKey::new(&ED25519, &self.public_key)
+            .verify(&message, &signature)
+            .is_ok()
+    }
+
+    /// Create (or update) the global `/usage` command
+    pub async fn register_command(&self, timeout: Duration) -> Result<()> {
+        let request = reqwest::Client::new()
+            .put(format!("{}/applications/{}/commands", API, self.application_id))
+            .header("Authorization", format!("Bot {}", self.token))
+            .json(&json!([{ "name": "usage", "description": "Current AI quota", "type": 1 }]));
+        deliver("discord", self.transport.as_ref(), request, timeout).await
+    }
+
+    /// Post an embed to the summary channel
+    async fn post(&self, channel_id: &str, embed: &Value, timeout: Duration) -> Result<()> {
+        let request = reqwest::Client::new()
+            .post(format!("{}/channels/{}/messages", API, channel_id))
+            .header("Authorization", format!("Bot {}", self.token))
+            .json(&json!({ "embeds": [embed] }));
+        deliver("discord", self.transport.as_ref(), request, timeout).await
+    }
+
+    /// Answer interactions on `listener`, registering `/usage` first
+    /// Discord reaches the server through a reverse proxy terminating HTTPS (the application's
+    /// Interactions Endpoint URL). Replies and summaries show the latest results of the `ocu serve`
+    /// listening on `source`, or else the cached ones, so the bot never queries the providers itself;
+    /// with `channel_id` set, the quotas are also posted daily at `summary_at`. Discord calls time out
+    /// after `timeout`; runs until the listener fails or the task is dropped
+    pub async fn serve(self, listener: TcpListener, source: SocketAddr, format: FormatOptions, timeout: Duration) -> Result<()> {
+        self.register_command(timeout).await?;
+        let bot = Arc::new(self);
+
+        let accept = {
+            let (bot, format) = (bot.clone(), format.clone());
+            async move {
+                loop {
+                    let (stream, _) = listener
+                        .accept()
+                        .await
+                        .map_err(|e| QuotaError::ApiError(format!("Discord server: {}", e)))?;
+                    let (bot, format) = (bot.clone(), format.clone());
+                    tokio::spawn(async move {
+                        // A client that hangs up mid-request needs no answer
+                        let _ = bot.handle(stream, source, &format).await;
+                    });
+                }
+            }
+        };
+        let summaries = async move {
+            let Some(channel_id) = &bot.channel_id else {
+                return std::future::pending().await;
+            };
+            loop {
+                tokio::time::sleep(until(bot.summary_at, Local::now())).await;
+                let embed = embed(latest(source).await.as_ref(), &format);
+                if let Err(e) = bot.post(channel_id, &embed, timeout).await {
+                    eprintln!("Warning: could not post the Discord summary: {}", e);
+                }
+            }
+        };
+        // Summaries never end; only the listener can fail
+        tokio::select! {
+            result = accept => result,
+            () = summaries => Ok(()),
+        }
+    }
+
+    async fn handle(&self, mut stream: TcpStream, source: SocketAddr, format: &FormatOptions) -> std::io::Result<()> {
+        let Some(request) = read_request(&mut stream).await? else {
+            return Ok(());
+        };
+        if request.method != "POST" {
+            return respond(&mut stream, "405 Method Not Allowed", "text/plain", b"").await;
+        }
+        let timestamp = request.header("x-signature-timestamp").unwrap_or_default();
+        let signature = request.header("x-signature-ed25519").unwrap_or_default();
+        // Discord sends badly signed requests on purpose and disables endpoints that accept them
+        if !self.verify(timestamp, &request.body, signature) {
+            return respond(&mut stream, "401 Unauthorized", "text/plain", b"invalid request signature").await;
+        }
+
+        let interaction: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+        let reply = match interaction["type"].as_u64() {
+            // PING, sent when the endpoint URL is saved
+            Some(1) => json!({ "type": 1 }),
+            Some(2) if interaction["data"]["name"] == "usage" => {
+                let embed = embed(latest(source).await.as_ref(), format);
+                json!({ "type": 4, "data": { "embeds": [embed] } })
+            }
+            _ => json!({ "type": 4, "data": { "content": "Unknown command", "flags": 64 } }),
+        };
+        respond(&mut stream, "200 OK", "application/json", reply.to_string().as_bytes()).await
+    }
+}
+
+/// Time left until the next `at` (local time) after `now`
+fn until(at: NaiveTime, now: DateTime<Local>) -> Duration {
+    let today = now.date_naive().and_time(at);
+    let next = if today > now.naive_local() {
+        today
+    } else {
+        today + chrono::Duration::days(1)
+    };
+    (next - now.naive_local()).to_std().unwrap_or_default()
+}
+
+/// The latest results of the `ocu serve` on `source`, or else the cached ones; None when there are neither
+async fn latest(source: SocketAddr) -> Option<CachedResults> {
+    match latest_report(source, SERVE_TIMEOUT).await {
+        Ok(served) => Some(served),
+        Err(_) => CachedResults::load().ok().flatten(),
+    }
+}
+
+/// The quota embed: one field per provider, one line per window, colored by the worst status,
+/// timestamped with the fetch it shows
+fn embed(results: Option<&CachedResults>, format: &FormatOptions) -> Value {
+    let Some(results) = results else {
+        return json!({
+            "title": "AI quota",
+            "description": "No quota data yet: run `ocu serve`, or `ocu` now and then to fill the cache",
+        });
+    };
+    let data = prepare(&results.providers, format);
+    let statusline = build_statusline(&data, format);
+    let color = match statusline.severity {
+        "error" => 0xe74c3c,
+        "warning" => 0xf39c12,
+        _ => 0x2ecc71,
+    };
+    let fields: Vec<Value> = data
+        .iter()
+        .map(|d| json!({ "name": d.provider_name(), "value": field(d, format), "inline": false }))
+        .collect();
+    json!({
+        "title": "AI quota",
+        "description": statusline.text,
+        "color": color,
+        "fields": fields,
+        "timestamp": results.fetched_at.to_rfc3339(),
+    })
+}
+
+fn field(data: &ProviderData, format: &FormatOptions) -> String {
+    if let ProviderData::Failed { error, .. } = data {
+        let error = error.split('{').next().unwrap_or(error).trim().trim_end_matches(':');
+        return truncate(format!("✗ query failed: {}", error));
+    }
+    let provider = data.provider_name();
+    let lines: Vec<String> = data
+        .windows()
+        .iter()
+        .filter(|w| format.shows_model(provider, &w.label))
+        .map(|w| {
+            let name = match &w.account {
+                Some(account) => format!("{} ({})", w.label, account),
+                None => w.label.clone(),
+            };
+            let Some(used) = w.used_percent() else {
+                return format!("**{}** unlimited", name);
+            };
+            let glyph = match format.thresholds.status(provider, &w.label, used) {
+                ProviderStatus::Ok => "🟢",
+                ProviderStatus::Warning => "🟡",
+                ProviderStatus::Error => "🔴",
+            };
+            let usage = match format.show {
+                UsageView::Used => format!("{:.0}% used", used),
+                UsageView::Remaining => format!("{:.0}% left", (100.0 - used).max(0.0)),
+            };
+            match w.resets_at {
+                Some(t) => format!("{} **{}** {}, resets {}", glyph, name, usage, describe_reset(t, format)),
+                None => format!("{} **{}** {}", glyph, name, usage),
+            }
+        })
+        .collect();
+    if lines.is_empty() {
+        return "no windows".to_string();
+    }
+    truncate(lines.join("\n"))
+}
+
+fn truncate(mut text: String) -> String {
+    if text.chars().count() > MAX_FIELD {
+        text = text.chars().take(MAX_FIELD - 1).collect();
+        text.push('…');
+    }
+    text
+}
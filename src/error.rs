@@ -15,25 +15,72 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use thiserror::Error;
 
+use crate::providers::ProviderLinks;
+
 #[derive(Error, Debug)]
 pub enum QuotaError {
     #[error("Authentication file not found: {0}")]
     AuthFileNotFound(String),
 
+    #[error("Could not parse {path} at byte {offset}: {message}")]
+    AuthFileInvalid {
+        path: String,
+        offset: usize,
+        message: String,
+    },
+
     #[error("Provider not configured: {0}")]
     ProviderNotConfigured(String),
 
     #[error("API request failed: {0}")]
     ApiError(String),
 
+    #[error("API request failed: {message}")]
+    HttpError { status: u16, message: String },
+
     #[error("Token refresh failed: {0}")]
     TokenRefreshError(String),
 
+    #[error("{message}")]
+    TokenExpired {
+        provider: String,
+        expired_at: DateTime<Utc>,
+        message: String,
+    },
+
+    #[error("{message}")]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+        message: String,
+    },
+
+    #[error("Timed out after {0}s")]
+    Timeout(u64),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
     #[error("Network error: {0}")]
     NetworkError(#[from] reqwest::Error),
 
+    #[error("{message}")]
+    DecodeError {
+        provider: String,
+        /// Path of the field that failed to decode, e.g. "rate_limit.primary_window"
+        path: String,
+        message: String,
+    },
+
+    #[error("{provider}: non-JSON response ({content_type}), are you behind a proxy?")]
+    UnexpectedContentType { provider: String, content_type: String },
+
+    #[error("{provider}: response larger than {limit} bytes")]
+    ResponseTooLarge { provider: String, limit: usize },
+
     #[error("JSON parse error: {0}")]
     JsonError(#[from] serde_json::Error),
 
@@ -41,4 +88,164 @@ pub enum QuotaError {
     IoError(#[from] std::io::Error),
 }
 
+impl QuotaError {
+    /// An expired token that can't be refreshed, with the command that fixes it
+    /// e.g. "claude token expired 3h 5m ago — run `opencode auth login anthropic`"
+    pub fn token_expired(provider: &str, expired_at: DateTime<Utc>) -> Self {
+        let ago = crate::output::time::format_duration(Utc::now() - expired_at, Default::default());
+        Self::TokenExpired {
+            provider: provider.to_string(),
+            expired_at,
+            message: format!(
+                "{} token expired {} ago — run `{}`",
+                provider,
+                ago,
+                ProviderLinks::for_provider(provider).map_or("opencode auth login", |l| l.login)
+            ),
+        }
+    }
+
+    /// A 429 that outlasted our retry, e.g. "rate limited, retry in 45s"
+    pub fn rate_limited(retry_after: Option<std::time::Duration>) -> Self {
+        let message = match retry_after {
+            Some(wait) if wait.as_secs() < 60 => format!("rate limited, retry in {}s", wait.as_secs().max(1)),
+            Some(wait) => format!(
+                "rate limited, retry in {}",
+                crate::output::time::format_duration(
+                    chrono::Duration::seconds(wait.as_secs() as i64),
+                    Default::default()
+                )
+            ),
+            None => "rate limited".to_string(),
+        };
+        Self::RateLimited { retry_after, message }
+    }
+
+    /// Whether the provider turned the request away with 429
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, QuotaError::RateLimited { .. })
+    }
+
+    /// How long the provider asked us to wait, for `rate_limited` errors
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            QuotaError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// A response body that doesn't match the expected shape, naming the field that broke
+    /// e.g. "codex: missing field `reset_after_seconds` at rate_limit.primary_window"
+    pub fn decode(provider: &str, error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = error.path().to_string();
+        // The field path locates the problem better than serde_json's line/column
+        let inner = error.into_inner().to_string();
+        // serde quotes the offending value, which may be a token
+        let inner = crate::output::redact::scrub_secrets(&inner);
+        let reason = inner.split(" at line ").next().unwrap_or(&inner);
+        let message = if path == "." {
+            format!("{}: {}", provider, reason)
+        } else {
+            format!("{}: {} at {}", provider, reason, path)
+        };
+        Self::DecodeError {
+            provider: provider.to_string(),
+            path,
+            message,
+        }
+    }
+
+    /// Whether the provider needs a new `opencode auth login` before it can be queried again
+    pub fn needs_login(&self) -> bool {
+        matches!(self, QuotaError::TokenExpired { .. } | QuotaError::TokenRefreshError(_))
+            || self.http_status() == Some(401)
+    }
+
+    /// When the token behind the error expired, if that was the cause
+    pub fn expired_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            QuotaError::TokenExpired { expired_at, .. } => Some(*expired_at),
+            _ => None,
+        }
+    }
+
+    /// Stable machine-readable error kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QuotaError::AuthFileNotFound(_) => "auth_file_not_found",
+            QuotaError::AuthFileInvalid { .. } => "auth_file_invalid",
+            QuotaError::ProviderNotConfigured(_) => "not_configured",
+            QuotaError::ApiError(_) => "api",
+            QuotaError::HttpError { .. } => "http",
+            QuotaError::TokenRefreshError(_) => "token_refresh",
+            QuotaError::TokenExpired { .. } => "token_expired",
+            QuotaError::RateLimited { .. } => "rate_limited",
+            QuotaError::Timeout(_) => "timeout",
+            QuotaError::ConfigError(_) => "config",
+            QuotaError::NetworkError(_) => "network",
+            QuotaError::DecodeError { .. } | QuotaError::JsonError(_) => "json",
+            QuotaError::UnexpectedContentType { .. } => "unexpected_content_type",
+            QuotaError::ResponseTooLarge { .. } => "response_too_large",
+            QuotaError::IoError(_) => "io",
+        }
+    }
+
+    /// HTTP status code returned by the provider, if any
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            QuotaError::HttpError { status, .. } => Some(*status),
+            QuotaError::RateLimited { .. } => Some(429),
+            QuotaError::NetworkError(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Whether retrying later may succeed (rate limits, server errors, timeouts)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            QuotaError::NetworkError(e) => {
+                // fetch doesn't tell connection failures apart from other request errors
+                #[cfg(not(target_arch = "wasm32"))]
+                let connect = e.is_connect();
+                #[cfg(target_arch = "wasm32")]
+                let connect = false;
+                e.is_timeout() || connect || e.status().is_some_and(|s| s.is_server_error())
+            }
+            QuotaError::Timeout(_) => true,
+            _ => self.http_status().is_some_and(|s| s == 429 || s >= 500),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, QuotaError>;
+
+/// Structured description of an error for `--errors json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorRecord {
+    /// Provider the error belongs to, if any
+    pub provider: Option<String>,
+    pub kind: &'static str,
+    pub message: String,
+    pub http_status: Option<u16>,
+    pub retryable: bool,
+    /// When the provider's token expired, for `token_expired` errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expired_at: Option<DateTime<Utc>>,
+    /// Seconds the provider asked us to wait, for `rate_limited` errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_seconds: Option<u64>,
+}
+
+impl ErrorRecord {
+    pub fn new(provider: Option<&str>, error: &QuotaError) -> Self {
+        Self {
+            provider: provider.map(str::to_string),
+            kind: error.kind(),
+            message: crate::output::redact::scrub_secrets(&error.to_string()),
+            http_status: error.http_status(),
+            retryable: error.is_retryable(),
+            expired_at: error.expired_at(),
+            retry_after_seconds: error.retry_after().map(|d| d.as_secs()),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use ring::{digest, hmac};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{QuotaError, Result};
+use crate::export::deliver;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::{error_body, send, ProviderData};
+
+/// EC2 instance metadata service, for instance role credentials and the region
+const IMDS_URL: &str = "http://169.254.169.254/latest";
+
+/// CloudWatch accepts at most this many metrics per PutMetricData call
+const MAX_METRICS_PER_CALL: usize = 1000;
+
+/// `[cloudwatch]` section: publish usage to CloudWatch after each run
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CloudWatchConfig {
+    /// Metric namespace, e.g. "Agents/Quota"; setting it enables publishing
+    pub namespace: Option<String>,
+    /// AWS region (default: `AWS_REGION`, `AWS_DEFAULT_REGION`, the profile's region or the instance's)
+    pub region: Option<String>,
+    /// Profile in the shared credentials and config files (default: `AWS_PROFILE`, or "default")
+    pub profile: Option<String>,
+}
+
+/// AWS access key, as found in the environment, the shared credentials file or the instance role
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Publishes `UsedPercent` per window and `ProviderUp` per provider with PutMetricData
+pub struct CloudWatchExporter {
+    namespace: String,
+    region: Option<String>,
+    profile: String,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl CloudWatchExporter {
+    pub fn new(namespace: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            region: None,
+            profile: env("AWS_PROFILE").unwrap_or_else(|| "default".to_string()),
+            transport: Arc::new(ReqwestTransport::default()),
+        }
+    }
+
+    /// Take the region and profile from the config section
+    pub fn with_config(mut self, config: &CloudWatchConfig) -> Self {
+        self.region = config.region.clone().or(self.region);
+        if let Some(profile) = &config.profile {
+            self.profile = profile.clone();
+        }
+        self
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Resolve credentials and region the way the AWS CLI does (environment, shared files,
+    /// then the EC2 instance role), and publish one data point per metric
+    pub async fn export(&self, data: &[ProviderData], timeout: Duration) -> Result<()> {
+        let credentials = match credentials_from_env().or_else(|| credentials_from_file(&self.profile)) {
+            Some(credentials) => credentials,
+            None => self.credentials_from_instance(timeout).await?,
+        };
+        let region = match self.region.clone().or_else(|| region_from_env_or_file(&self.profile)) {
+            Some(region) => region,
+            None => self.imds_get("meta-data/placement/region", timeout).await?,
+        };
+
+        let now = Utc::now();
+        let metrics = metric_data(data);
+        for chunk in metrics.chunks(MAX_METRICS_PER_CALL) {
+            let body = put_metric_data_body(&self.namespace, chunk, now);
+            let host = format!("monitoring.{}.amazonaws.com", region);
+            let mut request = reqwest::Client::new()
+                .post(format!("https://{}/", host))
+                .header("content-type", "application/x-www-form-urlencoded; charset=utf-8");
+            for (name, value) in sign(&credentials, &region, "monitoring", &host, &body, now) {
+                request = request.header(name, value);
+            }
+            deliver("cloudwatch", self.transport.as_ref(), request.body(body), timeout).await?;
+        }
+        Ok(())
+    }
+
+    /// Instance role credentials over IMDSv2
+    async fn credentials_from_instance(&self, timeout: Duration) -> Result<AwsCredentials> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct RoleCredentials {
+            access_key_id: String,
+            secret_access_key: String,
+            token: Option<String>,
+        }
+
+        let roles = self.imds_get("meta-data/iam/security-credentials/", timeout).await?;
+        let role = roles.lines().next().filter(|r| !r.is_empty()).ok_or_else(no_credentials)?;
+        let body = self.imds_get(&format!("meta-data/iam/security-credentials/{}", role), timeout).await?;
+        let role: RoleCredentials = serde_json::from_str(&body)?;
+        Ok(AwsCredentials {
+            access_key_id: role.access_key_id,
+            secret_access_key: role.secret_access_key,
+            session_token: role.token,
+        })
+    }
+
+    /// GET an instance metadata path with an IMDSv2 session token
+    async fn imds_get(&self, path: &str, timeout: Duration) -> Result<String> {
+        let client = reqwest::Client::new();
+        let token = client
+            .put(format!("{}/api/token", IMDS_URL))
+            .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
+            .timeout(timeout);
+        let token = send("cloudwatch", self.transport.as_ref(), None, token)
+            .await
+            .map_err(|_| no_credentials())?;
+        if !token.status().is_success() {
+            return Err(no_credentials());
+        }
+        let token = token.text().await?;
+
+        let request = client
+            .get(format!("{}/{}", IMDS_URL, path))
+            .header("X-aws-ec2-metadata-token", token)
+            .timeout(timeout);
+        let response = send("cloudwatch", self.transport.as_ref(), None, request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = error_body("cloudwatch", response).await;
+            return Err(QuotaError::HttpError {
+                status: status.as_u16(),
+                message: format!("instance metadata {} ({}): {}", path, status, body),
+            });
+        }
+        Ok(response.text().await?)
+    }
+}
+
+fn no_credentials() -> QuotaError {
+    QuotaError::ConfigError(
+        "no AWS credentials: set AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY, configure ~/.aws/credentials or run on EC2 with an instance role"
+            .to_string(),
+    )
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn credentials_from_env() -> Option<AwsCredentials> {
+    Some(AwsCredentials {
+        access_key_id: env("AWS_ACCESS_KEY_ID")?,
+        secret_access_key: env("AWS_SECRET_ACCESS_KEY")?,
+        session_token: env("AWS_SESSION_TOKEN"),
+    })
+}
+
+/// `$AWS_SHARED_CREDENTIALS_FILE`, or ~/.aws/credentials
+fn credentials_from_file(profile: &str) -> Option<AwsCredentials> {
+    let path = env("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("credentials")))?;
+    let section = ini_section(&std::fs::read_to_string(path).ok()?, profile);
+    let value = |key: &str| section.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+    Some(AwsCredentials {
+        access_key_id: value("aws_access_key_id")?,
+        secret_access_key: value("aws_secret_access_key")?,
+        session_token: value("aws_session_token"),
+    })
+}
+
+/// Region from the environment or the profile in `$AWS_CONFIG_FILE` (~/.aws/config)
+fn region_from_env_or_file(profile: &str) -> Option<String> {
+    env("AWS_REGION").or_else(|| env("AWS_DEFAULT_REGION")).or_else(|| {
+        let path = env("AWS_CONFIG_FILE")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("config")))?;
+        let contents = std::fs::read_to_string(path).ok()?;
+        // The config file names non-default profiles "profile NAME"
+        let section = if profile == "default" {
+            ini_section(&contents, profile)
+        } else {
+            ini_section(&contents, &format!("profile {}", profile))
+        };
+        section.into_iter().find(|(k, _)| k == "region").map(|(_, v)| v)
+    })
+}
+
+/// Key/value pairs of one `[section]` of an INI file
+fn ini_section(contents: &str, name: &str) -> Vec<(String, String)> {
+    let mut current = None;
+    let mut pairs = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = Some(header.trim().to_string());
+        } else if current.as_deref() == Some(name) {
+            if let Some((key, value)) = line.split_once('=') {
+                pairs.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+    }
+    pairs
+}
+
+/// One CloudWatch datum: name, unit, value and dimensions
+struct Datum {
+    name: &'static str,
+    unit: &'static str,
+    value: f64,
+    dimensions: Vec<(&'static str, String)>,
+}
+
+fn metric_data(data: &[ProviderData]) -> Vec<Datum> {
+    let mut metrics = Vec::new();
+    for d in data {
+        for w in d.windows() {
+            let Some(used) = w.used_percent() else { continue };
+            let mut dimensions = vec![("Provider", w.provider.clone()), ("Window", w.label.clone())];
+            if let Some(account) = &w.account {
+                dimensions.push(("Account", account.clone()));
+            }
+            metrics.push(Datum { name: "UsedPercent", unit: "Percent", value: used, dimensions });
+        }
+        metrics.push(Datum {
+            name: "ProviderUp",
+            unit: "None",
+            value: if matches!(d, ProviderData::Failed { .. }) { 0.0 } else { 1.0 },
+            dimensions: vec![("Provider", d.provider_name().to_string())],
+        });
+    }
+    metrics
+}
+
+/// Form-encoded PutMetricData query API request
+fn put_metric_data_body(namespace: &str, metrics: &[Datum], now: DateTime<Utc>) -> String {
+    let timestamp = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let mut params = vec![
+        ("Action".to_string(), "PutMetricData".to_string()),
+        ("Version".to_string(), "2010-08-01".to_string()),
+        ("Namespace".to_string(), namespace.to_string()),
+    ];
+    for (i, m) in metrics.iter().enumerate() {
+        let prefix = format!("MetricData.member.{}", i + 1);
+        params.push((format!("{}.MetricName", prefix), m.name.to_string()));
+        params.push((format!("{}.Unit", prefix), m.unit.to_string()));
+        params.push((format!("{}.Value", prefix), m.value.to_string()));
+        params.push((format!("{}.Timestamp", prefix), timestamp.clone()));
+        for (j, (name, value)) in m.dimensions.iter().enumerate() {
+            params.push((format!("{}.Dimensions.member.{}.Name", prefix, j + 1), name.to_string()));
+            params.push((format!("{}.Dimensions.member.{}.Value", prefix, j + 1), value.clone()));
+        }
+    }
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", uri_encode(k), uri_encode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters, as SigV4 requires
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes()).as_ref().to_vec()
+}
+
+/// Signature Version 4 headers for a POST to `/` with a form-encoded body
+fn sign(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    host: &str,
+    body: &str,
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex(digest::digest(&digest::SHA256, body.as_bytes()).as_ref());
+
+    let mut headers = vec![
+        ("content-type", "application/x-www-form-urlencoded; charset=utf-8".to_string()),
+        ("host", host.to_string()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v)).collect();
+    let signed_headers = headers.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(";");
+    let canonical_request = format!("POST\n/\n\n{}\n{}\n{}", canonical_headers, signed_headers, payload_hash);
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+    );
+    let key = [region, service, "aws4_request"].iter().fold(
+        hmac_sha256(format!("AWS4{}", credentials.secret_access_key).as_bytes(), &date),
+        |key, part| hmac_sha256(&key, part),
+    );
+    let signature = hex(&hmac_sha256(&key, &string_to_sign));
+
+    // host and content-type are set by the request itself
+    let mut signed = vec![
+        ("x-amz-date", amz_date),
+        (
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                credentials.access_key_id, scope, signed_headers, signature
+            ),
+        ),
+    ];
+    if let Some(token) = &credentials.session_token {
+        signed.push(("x-amz-security-token", token.clone()));
+    }
+    signed
+}
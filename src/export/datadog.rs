@@ -0,0 +1,164 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{QuotaError, Result};
+use crate::export::deliver;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::{ProviderData, ProviderStatus, Thresholds};
+
+/// `[datadog]` section: submit metrics and service checks after each run
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatadogConfig {
+    /// API key; `DD_API_KEY` is used when unset, and setting either here enables submission
+    pub api_key: Option<String>,
+    /// Datadog site, e.g. "datadoghq.eu" (default: `DD_SITE`, or "datadoghq.com")
+    pub site: Option<String>,
+    /// Host reported with the service checks (default: `HOSTNAME`, or "ocu")
+    pub host: Option<String>,
+    /// Tags added to every metric and check, e.g. ["team:ml", "env:ci"]
+    pub tags: Vec<String>,
+    /// Submit even without `api_key`, taking the key from `DD_API_KEY`; set by `--datadog`
+    #[serde(skip)]
+    pub enabled: bool,
+}
+
+/// Submits `ocu.*` gauges and an `ocu.provider.status` service check per provider
+pub struct DatadogExporter {
+    api_key: String,
+    site: String,
+    host: String,
+    tags: Vec<String>,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl DatadogExporter {
+    /// Build from the config section, falling back to `DD_API_KEY`/`DD_SITE`
+    /// Fails when no API key is configured anywhere
+    pub fn from_config(config: &DatadogConfig) -> Result<Self> {
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| env("DD_API_KEY"))
+            .ok_or_else(|| QuotaError::ConfigError("no Datadog API key: set DD_API_KEY or [datadog] api_key".to_string()))?;
+        Ok(Self {
+            api_key,
+            site: config.site.clone().or_else(|| env("DD_SITE")).unwrap_or_else(|| "datadoghq.com".to_string()),
+            host: config.host.clone().or_else(|| env("HOSTNAME")).unwrap_or_else(|| "ocu".to_string()),
+            tags: config.tags.clone(),
+            transport: Arc::new(ReqwestTransport::default()),
+        })
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Submit the series, then the service checks
+    pub async fn export(&self, data: &[ProviderData], thresholds: &Thresholds, timeout: Duration) -> Result<()> {
+        let client = reqwest::Client::new();
+        let now = Utc::now().timestamp();
+
+        let series = client
+            .post(format!("https://api.{}/api/v2/series", self.site))
+            .header("DD-API-KEY", &self.api_key)
+            .json(&series_body(data, &self.tags, now));
+        deliver("datadog", self.transport.as_ref(), series, timeout).await?;
+
+        let checks = client
+            .post(format!("https://api.{}/api/v1/check_run", self.site))
+            .header("DD-API-KEY", &self.api_key)
+            .json(&checks_body(data, thresholds, &self.host, &self.tags, now));
+        deliver("datadog", self.transport.as_ref(), checks, timeout).await
+    }
+}
+
+/// Metrics API v2 payload: `ocu.window.used_percent` per window and `ocu.provider.up` per provider
+pub fn series_body(data: &[ProviderData], tags: &[String], timestamp: i64) -> Value {
+    let gauge = |metric: &str, value: f64, extra: Vec<String>| {
+        json!({
+            "metric": metric,
+            // MetricIntakeType::GAUGE
+            "type": 3,
+            "points": [{ "timestamp": timestamp, "value": value }],
+            "tags": extra.into_iter().chain(tags.iter().cloned()).collect::<Vec<_>>(),
+        })
+    };
+
+    let mut series = Vec::new();
+    for d in data {
+        for w in d.windows() {
+            let Some(used) = w.used_percent() else { continue };
+            let mut window_tags = vec![tag("provider", &w.provider), tag("window", &w.label)];
+            if let Some(account) = &w.account {
+                window_tags.push(tag("account", account));
+            }
+            series.push(gauge("ocu.window.used_percent", used, window_tags));
+        }
+        let up = if matches!(d, ProviderData::Failed { .. }) { 0.0 } else { 1.0 };
+        series.push(gauge("ocu.provider.up", up, vec![tag("provider", d.provider_name())]));
+    }
+    json!({ "series": series })
+}
+
+/// Service checks: OK, WARNING or CRITICAL by the thresholds, UNKNOWN when the query failed
+pub fn checks_body(data: &[ProviderData], thresholds: &Thresholds, host: &str, tags: &[String], timestamp: i64) -> Value {
+    let checks: Vec<Value> = data
+        .iter()
+        .map(|d| {
+            let (status, message) = match d {
+                ProviderData::Failed { error, .. } => (3, error.clone()),
+                _ => {
+                    let worst = d
+                        .worst_used_percent()
+                        .map(|p| format!("highest window at {:.0}%", p))
+                        .unwrap_or_default();
+                    match d.status_with(thresholds) {
+                        ProviderStatus::Ok => (0, worst),
+                        ProviderStatus::Warning => (1, worst),
+                        ProviderStatus::Error => (2, worst),
+                    }
+                }
+            };
+            json!({
+                "check": "ocu.provider.status",
+                "host_name": host,
+                "status": status,
+                "timestamp": timestamp,
+                "message": message,
+                "tags": std::iter::once(tag("provider", d.provider_name()))
+                    .chain(tags.iter().cloned())
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    Value::Array(checks)
+}
+
+/// Datadog tag, lowercased with spaces as underscores ("window:7d_sonnet")
+fn tag(key: &str, value: &str) -> String {
+    format!("{}:{}", key, value.to_lowercase().replace(' ', "_"))
+}
@@ -0,0 +1,48 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cloudwatch;
+pub mod datadog;
+pub mod otlp;
+pub mod team;
+
+use std::time::Duration;
+
+use crate::error::{QuotaError, Result};
+use crate::providers::transport::HttpTransport;
+use crate::providers::{error_body, send};
+
+/// Send a request to an export target, failing on any non-success status
+/// `target` names the destination in errors, e.g. "otlp"
+pub(crate) async fn deliver(
+    target: &str,
+    transport: &dyn HttpTransport,
+    request: reqwest::RequestBuilder,
+    timeout: Duration,
+) -> Result<()> {
+    let response = send(target, transport, None, request.timeout(timeout)).await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = error_body(target, response).await;
+    Err(QuotaError::HttpError {
+        status: status.as_u16(),
+        message: format!("{} export failed ({}): {}", target, status, body),
+    })
+}
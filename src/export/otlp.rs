@@ -0,0 +1,193 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::export::deliver;
+use crate::providers::events::RequestTiming;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::ProviderData;
+
+/// Upper bounds (ms) of the fetch latency histogram buckets
+const LATENCY_BOUNDS_MS: [f64; 8] = [50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0];
+
+/// `[otlp]` section: where to push usage metrics after each run
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OtlpConfig {
+    /// OTLP/HTTP collector base URL, e.g. "http://localhost:4318" (`/v1/metrics` is appended)
+    pub endpoint: Option<String>,
+    /// Extra request headers, e.g. an API key for a hosted backend
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Pushes usage gauges and fetch latency histograms to an OpenTelemetry collector
+/// over OTLP/HTTP with the JSON encoding
+pub struct OtlpExporter {
+    url: String,
+    headers: BTreeMap<String, String>,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl OtlpExporter {
+    pub fn new(endpoint: &str) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        let url = if endpoint.ends_with("/v1/metrics") {
+            endpoint.to_string()
+        } else {
+            format!("{}/v1/metrics", endpoint)
+        };
+        Self {
+            url,
+            headers: BTreeMap::new(),
+            transport: Arc::new(ReqwestTransport::default()),
+        }
+    }
+
+    /// Send these headers with every export
+    pub fn with_headers(mut self, headers: BTreeMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// URL the metrics are posted to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Push one snapshot of the results and the request timings of the run that produced them
+    pub async fn export(&self, data: &[ProviderData], timings: &[RequestTiming], timeout: Duration) -> Result<()> {
+        let mut request = reqwest::Client::new().post(&self.url).json(&metrics_body(data, timings, Utc::now()));
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        deliver("otlp", self.transport.as_ref(), request, timeout).await
+    }
+}
+
+/// `ExportMetricsServiceRequest` in OTLP's JSON encoding:
+/// - `ocu.window.used_percent` gauge per window (provider, window and account attributes)
+/// - `ocu.provider.up` gauge per provider, 0 when its query failed
+/// - `ocu.fetch.duration` histogram of request latencies per provider, in milliseconds
+pub fn metrics_body(data: &[ProviderData], timings: &[RequestTiming], now: DateTime<Utc>) -> Value {
+    let now_nanos = now.timestamp_nanos_opt().unwrap_or_default().to_string();
+
+    let usage: Vec<Value> = data
+        .iter()
+        .flat_map(|d| d.windows())
+        .filter_map(|w| {
+            let used = w.used_percent()?;
+            let mut attributes = vec![attribute("provider", &w.provider), attribute("window", &w.label)];
+            if let Some(account) = &w.account {
+                attributes.push(attribute("account", account));
+            }
+            Some(json!({ "attributes": attributes, "timeUnixNano": now_nanos, "asDouble": used }))
+        })
+        .collect();
+
+    let up: Vec<Value> = data
+        .iter()
+        .map(|d| {
+            let up = if matches!(d, ProviderData::Failed { .. }) { "0" } else { "1" };
+            json!({ "attributes": [attribute("provider", d.provider_name())], "timeUnixNano": now_nanos, "asInt": up })
+        })
+        .collect();
+
+    // One histogram point per provider, covering this run's requests (delta temporality)
+    let mut latencies: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for t in timings {
+        latencies.entry(&t.provider).or_default().push(t.elapsed_ms as f64);
+    }
+    let started_nanos = timings
+        .iter()
+        .map(|t| t.elapsed_ms)
+        .max()
+        .map(|ms| (now - chrono::Duration::milliseconds(ms as i64)).timestamp_nanos_opt().unwrap_or_default())
+        .unwrap_or_default()
+        .to_string();
+    let durations: Vec<Value> = latencies
+        .iter()
+        .map(|(provider, values)| {
+            let mut counts = vec![0u64; LATENCY_BOUNDS_MS.len() + 1];
+            for v in values {
+                let bucket = LATENCY_BOUNDS_MS.iter().position(|bound| v <= bound).unwrap_or(LATENCY_BOUNDS_MS.len());
+                counts[bucket] += 1;
+            }
+            json!({
+                "attributes": [attribute("provider", provider)],
+                "startTimeUnixNano": started_nanos,
+                "timeUnixNano": now_nanos,
+                "count": values.len().to_string(),
+                "sum": values.iter().sum::<f64>(),
+                "min": values.iter().copied().fold(f64::INFINITY, f64::min),
+                "max": values.iter().copied().fold(0.0, f64::max),
+                "bucketCounts": counts.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+                "explicitBounds": LATENCY_BOUNDS_MS,
+            })
+        })
+        .collect();
+
+    let mut metrics = vec![
+        json!({
+            "name": "ocu.window.used_percent",
+            "description": "Share of the window's quota consumed",
+            "unit": "%",
+            "gauge": { "dataPoints": usage },
+        }),
+        json!({
+            "name": "ocu.provider.up",
+            "description": "Whether the provider's quota query succeeded",
+            "gauge": { "dataPoints": up },
+        }),
+    ];
+    if !durations.is_empty() {
+        metrics.push(json!({
+            "name": "ocu.fetch.duration",
+            "description": "Latency of provider HTTP requests",
+            "unit": "ms",
+            // AGGREGATION_TEMPORALITY_DELTA: each run reports only its own requests
+            "histogram": { "aggregationTemporality": 1, "dataPoints": durations },
+        }));
+    }
+
+    json!({
+        "resourceMetrics": [{
+            "resource": { "attributes": [attribute("service.name", "ocu")] },
+            "scopeMetrics": [{
+                "scope": { "name": "opencode-usage-companion", "version": env!("CARGO_PKG_VERSION") },
+                "metrics": metrics,
+            }],
+        }],
+    })
+}
+
+/// OTLP string attribute
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
@@ -0,0 +1,85 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{QuotaError, Result};
+use crate::export::deliver;
+use crate::output::json::format_json_line;
+use crate::output::FormatOptions;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::ProviderData;
+
+/// `[team]` section: the `ocu serve --aggregate` server this machine reports to
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TeamConfig {
+    /// Server base URL, e.g. `https://quota.example.com` (`/v1/snapshots` is appended)
+    pub url: Option<String>,
+    /// This member's token from the server's `[serve.members]`; `OCU_TEAM_TOKEN` is used when unset
+    pub token: Option<String>,
+}
+
+/// Pushes each run's report to a team aggregation server
+pub struct TeamPusher {
+    url: String,
+    token: String,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl TeamPusher {
+    pub fn new(url: &str, config: &TeamConfig) -> Result<Self> {
+        let token = config
+            .token
+            .clone()
+            .or_else(|| std::env::var("OCU_TEAM_TOKEN").ok().filter(|v| !v.is_empty()))
+            .ok_or_else(|| QuotaError::ConfigError("no team token: set OCU_TEAM_TOKEN or [team] token".to_string()))?;
+        let url = url.trim_end_matches('/');
+        let url = if url.ends_with("/v1/snapshots") {
+            url.to_string()
+        } else {
+            format!("{}/v1/snapshots", url)
+        };
+        Ok(Self {
+            url,
+            token,
+            transport: Arc::new(ReqwestTransport::default()),
+        })
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Post the `--format json` report; `options` decides redaction
+    pub async fn push(&self, data: &[ProviderData], options: &FormatOptions, timeout: Duration) -> Result<()> {
+        let request = reqwest::Client::new()
+            .post(&self.url)
+            .bearer_auth(&self.token)
+            .header("content-type", "application/json")
+            .body(format_json_line(data, options));
+        deliver("team", self.transport.as_ref(), request, timeout).await
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::ffi::{c_char, CString};
+use std::time::Duration;
+
+use crate::cli::OutputFormat;
+use crate::output::{format_output, FormatOptions};
+use crate::providers::{FetchOptions, ProviderData};
+
+/// Fetch every configured provider and render the results, or None if no runtime could start
+/// A panic must not unwind into the C caller, so it also gives None; this needs the library
+/// built with `panic = "unwind"`, as the release profile aborts instead
+fn fetch_formatted(timeout_secs: u32, format: OutputFormat) -> Option<CString> {
+    std::panic::catch_unwind(|| {
+        let options = FetchOptions {
+            timeout: Duration::from_secs(u64::from(timeout_secs.max(1))),
+            ..Default::default()
+        };
+        let data: Vec<ProviderData> = crate::blocking::fetch_all(options)
+            .ok()?
+            .into_iter()
+            .map(|r| r.into_data())
+            .collect();
+        let format_options = FormatOptions {
+            no_color: true,
+            ..Default::default()
+        };
+        // Rendered text never contains NUL bytes; strip any from provider strings anyway
+        CString::new(format_output(&data, format, &format_options).replace('\0', "")).ok()
+    })
+    .ok()
+    .flatten()
+}
+
+/// Fetch every configured provider and return the `--format json` document
+/// Returns NULL on failure; free the string with `ocu_string_free`
+#[no_mangle]
+pub extern "C" fn ocu_fetch_json(timeout_secs: u32) -> *mut c_char {
+    fetch_formatted(timeout_secs, OutputFormat::Json).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Fetch every configured provider and return the one-line summary (`--summary`), uncolored
+/// Returns NULL on failure; free the string with `ocu_string_free`
+#[no_mangle]
+pub extern "C" fn ocu_fetch_summary(timeout_secs: u32) -> *mut c_char {
+    fetch_formatted(timeout_secs, OutputFormat::Summary).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Free a string returned by this library
+///
+/// # Safety
+/// `s` must be NULL or a pointer returned by an `ocu_fetch_*` function, freed only once
+#[no_mangle]
+pub unsafe extern "C" fn ocu_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
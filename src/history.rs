@@ -0,0 +1,140 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use crate::cache::cache_dir;
+use crate::error::{QuotaError, Result};
+use crate::providers::{CostModel, ProviderData};
+
+/// Samples older than this are dropped once the oldest one passes `PRUNE_AFTER_DAYS`,
+/// so the file is only rewritten now and then rather than on every run
+const KEEP_DAYS: i64 = 62;
+const PRUNE_AFTER_DAYS: i64 = 93;
+
+/// One window's usage in a sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleWindow {
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    pub label: String,
+    pub used_percent: f64,
+}
+
+/// One run's results, as kept in the usage history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageSample {
+    pub at: DateTime<Utc>,
+    /// Every limited window; unlimited ones are left out
+    pub windows: Vec<SampleWindow>,
+    /// Error by provider, for providers whose query failed
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed: BTreeMap<String, String>,
+    /// Estimated spend beyond the plan by provider, as in the JSON report
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cost_usd: BTreeMap<String, f64>,
+}
+
+impl UsageSample {
+    pub fn new(results: &[ProviderData], cost: &CostModel, at: DateTime<Utc>) -> Self {
+        let mut sample = Self {
+            at,
+            windows: Vec::new(),
+            failed: BTreeMap::new(),
+            cost_usd: BTreeMap::new(),
+        };
+        for data in results {
+            if let ProviderData::Failed { provider, error, .. } = data {
+                sample.failed.insert(provider.clone(), error.clone());
+            }
+            if let Some(usd) = cost.estimate(data) {
+                sample.cost_usd.insert(data.provider_name().to_string(), usd);
+            }
+            sample.windows.extend(data.windows().into_iter().filter_map(|w| {
+                Some(SampleWindow {
+                    used_percent: w.used_percent()?,
+                    provider: w.provider,
+                    account: w.account,
+                    label: w.label,
+                })
+            }));
+        }
+        sample
+    }
+}
+
+/// Results of past runs, one JSON line per run (~/.cache/ocu/history.jsonl)
+pub struct UsageHistory;
+
+impl UsageHistory {
+    pub fn default_path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("history.jsonl"))
+    }
+
+    fn path() -> Result<PathBuf> {
+        Self::default_path().ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))
+    }
+
+    /// Add a sample, dropping old ones when the history has grown past what reports look at
+    pub fn append(sample: &UsageSample) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Samples are appended in order, so the first line is the oldest
+        let oldest = std::fs::File::open(&path).ok().and_then(|file| {
+            let mut line = String::new();
+            BufReader::new(file).read_line(&mut line).ok()?;
+            serde_json::from_str::<UsageSample>(&line).ok().map(|s| s.at)
+        });
+        if oldest.is_some_and(|at| at < sample.at - Duration::days(PRUNE_AFTER_DAYS)) {
+            let kept = Self::load_since(Some(sample.at - Duration::days(KEEP_DAYS)))?;
+            let mut content = String::new();
+            for kept in kept {
+                content.push_str(&serde_json::to_string(&kept)?);
+                content.push('\n');
+            }
+            let tmp = path.with_extension("jsonl.tmp");
+            std::fs::write(&tmp, content)?;
+            std::fs::rename(&tmp, &path)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(sample)?)?;
+        Ok(())
+    }
+
+    /// Samples taken at or after `since`, oldest first; lines that don't parse are skipped
+    pub fn load_since(since: Option<DateTime<Utc>>) -> Result<Vec<UsageSample>> {
+        let content = match std::fs::read_to_string(Self::path()?) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut samples: Vec<UsageSample> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<UsageSample>(line).ok())
+            .filter(|s| since.map_or(true, |since| s.at >= since))
+            .collect();
+        samples.sort_by_key(|s| s.at);
+        Ok(samples)
+    }
+}
@@ -16,10 +16,49 @@
  */
 
 pub mod auth;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod blocking;
+pub mod budget;
+#[doc(hidden)]
+pub mod cache;
 pub mod cli;
+pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod daemon;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod discord;
+#[cfg(feature = "dbus")]
+pub mod dbus;
 pub mod error;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod glob;
+pub mod history;
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+pub mod sessions;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod slack;
+pub mod notify;
 pub mod output;
+mod paths;
+/// The supported library surface: names exported here follow semver,
+/// other public items may change in any minor release
+pub mod prelude;
+pub mod report;
 pub mod providers;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod rpc;
+pub mod run;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod streamdeck;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod team;
 
-pub use cli::{Args, OutputFormat, ProviderArg};
+#[cfg(feature = "cli")]
+pub use cli::Args;
+pub use cli::{OutputFormat, ProviderArg};
+pub use config::Config;
 pub use error::{QuotaError, Result};
+pub use output::time::{humanize_duration, humanize_relative, DurationStyle, Locale};
@@ -17,182 +17,787 @@
 
 use clap::Parser;
 use colored::{control, Colorize};
-use opencode_usage_companion::cli::{Args, ProviderArg};
-use opencode_usage_companion::output::format_output;
-use opencode_usage_companion::providers::{claude::ClaudeProvider, codex::CodexProvider, copilot::CopilotProvider, gemini::GeminiProvider, Provider, ProviderData};
+use opencode_usage_companion::cache::CachedResults;
+use opencode_usage_companion::auth::{AuthManager, AuthSource, AuthStatus};
+use chrono::{DateTime, Utc};
+use opencode_usage_companion::cli::{Args, AuthAction, BotPlatform, Command, ErrorFormat, Exit, OutputFormat, ProviderArg, ReportFormat, ReportPeriod, SessionGroup};
+use opencode_usage_companion::error::ErrorRecord;
+use opencode_usage_companion::config::Config;
+use opencode_usage_companion::daemon::latest_report;
+use opencode_usage_companion::discord::DiscordBot;
+use opencode_usage_companion::output::{
+    format_output, gha::write_step_summary, json::{format_errors, format_json_line, json_schema}, limits::{format_limits_json, format_limits_table}, metrics::format_metrics, nvim::format_nvim_status, sessions::{format_sessions_json, format_sessions_table}, summary::format_summary, time::format_duration,
+    FormatOptions,
+};
+use opencode_usage_companion::providers::{api_host, clock_skew, events::{EventHook, FetchEvent}, transport::ReqwestTransport, FetchOptions, ProviderLinks, Thresholds};
+use opencode_usage_companion::report::{post_report, UsageReport};
+use opencode_usage_companion::rpc::serve;
+use opencode_usage_companion::sessions::{self, SessionFilter};
+use opencode_usage_companion::team::serve_aggregate;
+use opencode_usage_companion::run::{after_fetch, fetch_all, fetch_all_until, provider_names, RunOptions};
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::process::ExitCode;
 use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> ExitCode {
     let args = Args::parse();
-
-    // Disable colors if requested
-    if args.no_color {
-        control::set_override(false);
+    if let Err(e) = args.validate() {
+        e.exit();
+    }
+    if args.print_schema {
+        println!("{}", json_schema());
+        return ExitCode::from(Exit::Success);
     }
 
-    // Determine which providers to query
-    let provider_names = if args.provider.is_empty() || args.provider.contains(&ProviderArg::All) {
-        vec!["gemini", "codex", "copilot", "claude"]
-    } else {
-        args.provider
-            .iter()
-            .map(|p| match p {
-                ProviderArg::Gemini => "gemini",
-                ProviderArg::Codex => "codex",
-                ProviderArg::Copilot => "copilot",
-                ProviderArg::Claude => "claude",
-                ProviderArg::All => unreachable!(),
-            })
-            .collect()
+    // `--warn-at` and `--critical-at` are held to the same rules as `[thresholds]`
+    let config = match Config::load(args.config.as_deref())
+        .and_then(|config| thresholds(&args, &config).validate().map(|()| config))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            if args.errors == ErrorFormat::Json {
+                eprintln!("{}", format_errors(&[ErrorRecord::new(None, &e)], true));
+            } else {
+                eprintln!("Error: {}", e);
+            }
+            return ExitCode::from(Exit::NoProviders);
+        }
     };
 
-    // Build provider instances
-    let mut providers: Vec<Box<dyn Provider>> = Vec::new();
-    let mut configured_count = 0;
+    // Resolve colors once (flag, NO_COLOR/CLICOLOR conventions, TTY detection)
+    let no_color = !args.use_color();
+    control::set_override(!no_color);
 
-    for name in &provider_names {
-        let provider: Box<dyn Provider> = match *name {
-            "gemini" => {
-                let p = GeminiProvider::new();
-                if p.is_configured() {
-                    configured_count += 1;
-                }
-                Box::new(p)
-            }
-            "codex" => {
-                let p = CodexProvider::new();
-                if p.is_configured() {
-                    configured_count += 1;
-                }
-                Box::new(p)
-            }
-            "copilot" => {
-                let p = CopilotProvider::new();
-                if p.is_configured() {
-                    configured_count += 1;
-                }
-                Box::new(p)
+    match &args.command {
+        Some(Command::Prompt) => return print_prompt(&args, &config, no_color).await,
+        Some(Command::NvimStatus) => return print_nvim_status(&args, &config),
+        Some(Command::Auth { action: AuthAction::Status }) => return print_auth_status(no_color),
+        Some(Command::Doctor) => return print_doctor(&args, no_color).await,
+        Some(Command::Report { period, format, webhook }) => {
+            return print_report(&args, &config, *period, *format, webhook.as_deref()).await
+        }
+        Some(Command::Limits) => return print_limits(&args, &config).await,
+        Some(Command::Sessions { since, until, by }) => return print_sessions(&args, &config, *since, *until, *by),
+        Some(Command::Zellij { interval, name }) => return run_zellij(&args, &config, *interval, name).await,
+        Some(Command::Rpc { interval }) => return run_rpc(&args, &config, *interval).await,
+        Some(Command::Serve { interval, listen, aggregate }) => {
+            let listen = listen.unwrap_or_else(|| config.serve.listen());
+            return run_serve(&args, &config, *interval, listen, *aggregate).await
+        }
+        Some(Command::Bot { platform: BotPlatform::Discord { listen } }) => return run_discord(&args, &config, *listen).await,
+        #[cfg(feature = "dbus")]
+        Some(Command::Dbus { interval }) => return run_dbus(&args, &config, *interval).await,
+        None => {}
+    }
+    if let Some(interval) = args.listen_json {
+        return run_listen_json(&args, &config, interval).await;
+    }
+
+    // Progress message only for interactive use, so piped output stays clean;
+    // the one-line summary is embedded in greetings and prompts, so it stays bare;
+    // shown once the first provider starts, so a run with nothing configured goes straight to the error
+    let format = args.output_format();
+    let one_line = matches!(
+        format,
+        OutputFormat::Summary
+            | OutputFormat::Statusline
+            | OutputFormat::Raycast
+            | OutputFormat::Alfred
+            | OutputFormat::Plasma
+    );
+    let announce = std::io::stdout().is_terminal() && !one_line;
+    let announced = AtomicBool::new(false);
+    let on_event: EventHook = Arc::new(move |event| {
+        if announce && matches!(event, FetchEvent::Started { .. }) && !announced.swap(true, Ordering::Relaxed) {
+            println!("Fetching quota information...");
+        }
+    });
+
+    let verbose = args.verbose;
+    let options = run_options(&args, &config, Some(on_event));
+
+    // Ctrl-C stops waiting on outstanding providers (dropping their requests);
+    // whatever already arrived is still shown, marked as incomplete
+    let outcome = match fetch_all_until(options.clone(), tokio::signal::ctrl_c()).await {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            if args.errors == ErrorFormat::Json {
+                eprintln!("{}", format_errors(&[ErrorRecord::new(None, &error)], true));
+                return ExitCode::from(Exit::NoProviders);
             }
-            "claude" => {
-                let p = ClaudeProvider::new();
-                if p.is_configured() {
-                    configured_count += 1;
+            eprintln!("Error: No AI providers configured.");
+            eprintln!("Please authenticate with OpenCode first:");
+            for name in provider_names(&[]) {
+                if let Some(links) = ProviderLinks::for_provider(name) {
+                    eprintln!("  - {}: {}", name, links.login);
                 }
-                Box::new(p)
             }
-            _ => continue,
+            return ExitCode::from(Exit::NoProviders);
+        }
+    };
+
+    if verbose {
+        for t in &outcome.timings {
+            let size = t.bytes.map(|b| format!(", {} bytes", b)).unwrap_or_default();
+            eprintln!("[{}] {} {} in {}ms{}", t.provider, t.status, t.url, t.elapsed_ms, size);
+        }
+    }
+
+    if args.errors == ErrorFormat::Text && !outcome.errors.is_empty() {
+        eprintln!();
+        for record in &outcome.errors {
+            print_warning(record.provider.as_deref().unwrap_or_default(), &record.message, no_color);
+        }
+    }
+
+    let results = &outcome.results;
+    if outcome.interrupted {
+        eprintln!(
+            "\nInterrupted: showing {} of {} providers",
+            results.len(),
+            outcome.queried
+        );
+        if results.is_empty() {
+            return ExitCode::from(Exit::Interrupted);
+        }
+    }
+
+    let all_failed = outcome.all_failed();
+    let thresholds = thresholds(&args, &config);
+
+    // History, spend, notifications, the prompt cache and the exporters, as in every refresh loop
+    let budgets = after_fetch(&outcome, &options).await;
+
+    if args.errors == ErrorFormat::Json && !outcome.errors.is_empty() {
+        eprintln!("{}", format_errors(&outcome.errors, all_failed));
+    }
+    // Written even when everything failed, so the report records providers going down
+    if let Some(path) = &args.metrics_file {
+        let options = FormatOptions {
+            redact: args.redact,
+            models: args.model.clone(),
+            ..Default::default()
         };
-        providers.push(provider);
+        if let Err(e) = std::fs::write(path, format_metrics(results, &options)) {
+            eprintln!("Warning: could not write {}: {}", path.display(), e);
+        }
+    }
+    if all_failed {
+        if args.errors == ErrorFormat::Text {
+            eprintln!("\nError: All provider queries failed.");
+        }
+        return ExitCode::from(outcome.exit(&thresholds));
+    }
+
+    // Output results (with blank line before for separation)
+    if !one_line {
+        println!();
+    }
+    let format_options = FormatOptions {
+        no_color,
+        hyperlinks: args.use_hyperlinks(),
+        colors: config.colors.clone(),
+        group_by: args.group_by,
+        style: args.style.or(config.display.style).unwrap_or_default(),
+        columns: if args.columns.is_empty() {
+            config.display.columns.clone().unwrap_or_default()
+        } else {
+            args.columns.clone()
+        },
+        width: args.width.map(usize::from),
+        no_bars: args.no_bars,
+        no_summary: args.no_summary,
+        show_thresholds: args.show_thresholds,
+        thresholds: thresholds.clone(),
+        cost: config.cost.clone(),
+        budgets,
+        redact: args.redact,
+        pace: args.pace,
+        no_emoji: args.no_emoji || config.display.emoji == Some(false),
+        show: args.show.or(config.display.show).unwrap_or_default(),
+        counts: args.counts,
+        hide_model_windows: args.hide_model_windows,
+        models: args.model.clone(),
+        detailed: args.detailed,
+        absolute_times: args.absolute_times,
+        utc: args.utc,
+        time_format: config.display.time_format.clone(),
+        duration_style: config.display.duration_style.unwrap_or_default(),
+        locale: config.display.locale.unwrap_or_default(),
+        sort: args.sort,
+        sort_order: args
+            .sort_order
+            .or(args.sort.map(|key| key.default_order()))
+            .unwrap_or_default(),
+        // Live fetch; only cached data carries an age
+        fetched_at: None,
+        incomplete: outcome.interrupted,
+        timings: outcome.timings.clone(),
+    };
+    let output = format_output(results, format, &format_options);
+    println!("{}", output);
+    // Raycast shows a script that exits non-zero as failed instead of its line,
+    // so quotas past a threshold and partial failures only show in the line itself
+    if format == OutputFormat::Raycast {
+        return ExitCode::from(Exit::Success);
+    }
+    // In GitHub Actions, also put the table in the run summary
+    if format == OutputFormat::Gha {
+        if let Err(e) = write_step_summary(results, &format_options) {
+            eprintln!("Warning: could not write the job summary: {}", e);
+        }
     }
 
-    if providers.is_empty() {
-        eprintln!("Error: No providers specified.");
-        return ExitCode::from(2);
+    ExitCode::from(outcome.exit(&thresholds))
+}
+
+/// What to fetch and how, from the command line and the config
+/// The command line's thresholds and export targets override the config's, for `after_fetch` too
+fn run_options(args: &Args, config: &Config, on_event: Option<EventHook>) -> RunOptions {
+    let mut overridden = config.clone();
+    overridden.thresholds = thresholds(args, config);
+    if let Some(endpoint) = &args.otlp_endpoint {
+        overridden.otlp.endpoint = Some(endpoint.clone());
+    }
+    if let Some(namespace) = &args.cloudwatch_namespace {
+        overridden.cloudwatch.namespace = Some(namespace.clone());
+    }
+    if let Some(url) = &args.push {
+        overridden.team.url = Some(url.clone());
     }
+    overridden.datadog.enabled |= args.datadog;
+    RunOptions {
+        providers: args.provider.clone(),
+        config: overridden,
+        accounts: args.account.clone(),
+        gemini_active_only: args.gemini_active_only(),
+        dump_body_on_error: args.dump_body_on_error,
+        redact: args.redact,
+        fetch: FetchOptions {
+            timeout: Duration::from_secs(args.timeout),
+            concurrent: args.concurrent,
+            verbose: args.verbose,
+            on_event,
+        },
+    }
+}
 
-    if configured_count == 0 {
-        eprintln!("Error: No AI providers configured.");
-        eprintln!("Please authenticate with OpenCode first:");
-        eprintln!("  - gemini: opencode auth login gemini");
-        eprintln!("  - codex: opencode auth login openai");
-        eprintln!("  - copilot: opencode auth login github-copilot");
-        eprintln!("  - claude: opencode auth login anthropic");
-        return ExitCode::from(2);
+/// The config's `[thresholds]`, with `--warn-at` and `--critical-at` applied
+fn thresholds(args: &Args, config: &Config) -> Thresholds {
+    let mut thresholds = config.thresholds.clone();
+    if let Some(warning) = args.warn_at {
+        thresholds.warning = warning;
     }
+    if let Some(critical) = args.critical_at {
+        thresholds.critical = critical;
+    }
+    thresholds
+}
 
+/// `ocu zellij`: fetch every `interval` seconds and send the status line to the plugins
+/// listening on the `name` pipe, until Ctrl-C
+/// Each fetch is recorded and exported as a plain run's is, so `ocu prompt` stays current
+async fn run_zellij(args: &Args, config: &Config, interval: u64, name: &str) -> ExitCode {
+    let options = status_options(args, config);
     let timeout = Duration::from_secs(args.timeout);
-    let mut results = Vec::new();
-    let mut has_errors = false;
-    let mut first_warning = true;
-    let no_color = args.no_color;
 
-    println!("Fetching quota information...");
+    let run = run_options(args, config, None);
 
-    let verbose = args.verbose;
+    loop {
+        let outcome = match fetch_all_until(run.clone(), tokio::signal::ctrl_c()).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(Exit::NoProviders);
+            }
+        };
+        if outcome.interrupted {
+            return ExitCode::from(Exit::Interrupted);
+        }
+        after_fetch(&outcome, &run).await;
 
-    if args.concurrent {
-        // Concurrent fetching - only fetch configured providers
-        let futures = providers.iter()
-            .filter(|p| p.is_configured())
-            .map(|provider| {
-                let timeout = timeout;
-                async move {
-                    let name = provider.name();
-                    match provider.fetch(timeout, verbose).await {
-                        Ok(data) => Ok(data),
-                        Err(e) => Err((name, e)),
-                    }
-                }
-            });
-
-        let outcomes = futures::future::join_all(futures).await;
-
-        for outcome in outcomes {
-            match outcome {
-                Ok(data) => results.push(data),
-                Err((name, e)) => {
-                    if first_warning {
-                        eprintln!();
-                        first_warning = false;
-                    }
-                    print_warning(name, &e.to_string(), no_color);
-                    results.push(ProviderData::Failed {
-                        provider: name.to_string(),
-                        error: e.to_string(),
-                    });
-                    has_errors = true;
-                }
+        let payload = format_output(&outcome.results, OutputFormat::Statusline, &options);
+        let mut pipe = tokio::process::Command::new("zellij");
+        pipe.args(["pipe", "--name", name, "--", &payload]).kill_on_drop(true);
+        // `zellij pipe` waits for a plugin to release it, which a plugin that only reads never does
+        match tokio::time::timeout(timeout, pipe.output()).await {
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                eprintln!("Error: zellij not found in PATH");
+                return ExitCode::from(Exit::Failure);
             }
+            Ok(Err(e)) => eprintln!("Warning: zellij pipe failed: {}", e),
+            Ok(Ok(output)) if !output.status.success() => eprintln!(
+                "Warning: zellij pipe failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            _ => {}
         }
-    } else {
-        // Sequential fetching
-        for provider in &providers {
-            let name = provider.name();
-            if !provider.is_configured() {
-                continue;
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+            _ = tokio::signal::ctrl_c() => return ExitCode::from(Exit::Interrupted),
+        }
+    }
+}
+
+/// `--listen-json`: fetch every `interval` seconds and print the JSON report as one line, until Ctrl-C
+/// Each fetch is recorded and exported as a plain run's is, so `ocu prompt` stays current
+async fn run_listen_json(args: &Args, config: &Config, interval: u64) -> ExitCode {
+    let options = status_options(args, config);
+
+    let run = run_options(args, config, None);
+
+    loop {
+        let outcome = match fetch_all_until(run.clone(), tokio::signal::ctrl_c()).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(Exit::NoProviders);
             }
+        };
+        if outcome.interrupted {
+            return ExitCode::from(Exit::Interrupted);
+        }
+        after_fetch(&outcome, &run).await;
 
-            match provider.fetch(timeout, verbose).await {
-                Ok(data) => results.push(data),
-                Err(e) => {
-                    if first_warning {
-                        eprintln!();
-                        first_warning = false;
-                    }
-                    print_warning(name, &e.to_string(), no_color);
-                    results.push(ProviderData::Failed {
-                        provider: name.to_string(),
-                        error: e.to_string(),
-                    });
-                    has_errors = true;
-                }
+        // stdout is line buffered, so each snapshot reaches the reader as soon as it is printed
+        println!("{}", format_json_line(&outcome.results, &options));
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+            _ = tokio::signal::ctrl_c() => return ExitCode::from(Exit::Interrupted),
+        }
+    }
+}
+
+/// `ocu rpc`: answer editor extensions on stdin/stdout until they exit or close the pipe
+/// Exits the process directly, as the runtime would otherwise wait on the pending stdin read
+async fn run_rpc(args: &Args, config: &Config, interval: u64) -> ExitCode {
+    let served = serve(
+        run_options(args, config, None),
+        status_options(args, config),
+        Duration::from_secs(interval),
+    )
+    .await;
+    match served {
+        Ok(()) => std::process::exit(Exit::Success as i32),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(Exit::Failure as i32)
+        }
+    }
+}
+
+/// `ocu serve`: answer API requests on `listen` until Ctrl-C
+/// With `aggregate`, collect team members' reports instead of fetching
+async fn run_serve(args: &Args, config: &Config, interval: u64, listen: SocketAddr, aggregate: bool) -> ExitCode {
+    if aggregate && config.serve.members.is_empty() {
+        eprintln!("Error: no team members configured; add name = \"token\" pairs to [serve.members]");
+        return ExitCode::from(Exit::Failure);
+    }
+    let listener = match tokio::net::TcpListener::bind(listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: cannot listen on {}: {}", listen, e);
+            return ExitCode::from(Exit::Failure);
+        }
+    };
+    let service = async {
+        if aggregate {
+            serve_aggregate(
+                listener,
+                config.serve.members.clone(),
+                config.serve.token(),
+                status_options(args, config),
+            )
+            .await
+        } else {
+            opencode_usage_companion::daemon::serve(
+                listener,
+                config.serve.token(),
+                run_options(args, config, None),
+                status_options(args, config),
+                Duration::from_secs(interval),
+            )
+            .await
+        }
+    };
+    tokio::select! {
+        served = service => {
+            if let Err(e) = served {
+                eprintln!("Error: {}", e);
             }
+            ExitCode::from(Exit::Failure)
         }
+        _ = tokio::signal::ctrl_c() => ExitCode::from(Exit::Interrupted),
     }
+}
 
-    if results.is_empty() {
-        eprintln!("\nError: All provider queries failed.");
-        return ExitCode::from(1);
+/// `ocu bot discord`: answer interactions on `listen` and post daily summaries until Ctrl-C
+async fn run_discord(args: &Args, config: &Config, listen: SocketAddr) -> ExitCode {
+    let bot = match DiscordBot::from_config(&config.discord) {
+        Ok(bot) => bot,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(Exit::Failure);
+        }
+    };
+    let listener = match tokio::net::TcpListener::bind(listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: cannot listen on {}: {}", listen, e);
+            return ExitCode::from(Exit::Failure);
+        }
+    };
+    let service = bot.serve(
+        listener,
+        config.serve.listen(),
+        status_options(args, config),
+        Duration::from_secs(args.timeout),
+    );
+    tokio::select! {
+        served = service => {
+            if let Err(e) = served {
+                eprintln!("Error: {}", e);
+            }
+            ExitCode::from(Exit::Failure)
+        }
+        _ = tokio::signal::ctrl_c() => ExitCode::from(Exit::Interrupted),
     }
+}
 
-    // Check if all results are failures
-    let all_failed = results.iter().all(|r| matches!(r, ProviderData::Failed { .. }));
-    if all_failed {
-        eprintln!("\nError: All provider queries failed.");
-        return ExitCode::from(1);
+/// `ocu dbus`: serve usage on the session bus until Ctrl-C
+#[cfg(feature = "dbus")]
+async fn run_dbus(args: &Args, config: &Config, interval: u64) -> ExitCode {
+    let service = opencode_usage_companion::dbus::serve(
+        run_options(args, config, None),
+        status_options(args, config),
+        Duration::from_secs(interval),
+    );
+    tokio::select! {
+        served = service => {
+            if let Err(e) = served {
+                eprintln!("Error: {}", e);
+            }
+            ExitCode::from(Exit::Failure)
+        }
+        _ = tokio::signal::ctrl_c() => ExitCode::from(Exit::Interrupted),
     }
+}
 
-    // Output results (with blank line before for separation)
-    println!();
-    let output = format_output(&results, args.format, no_color);
-    println!("{}", output);
+/// Rendering for the status documents pushed by `ocu zellij`, `ocu rpc`, `ocu serve`, `ocu bot` and `ocu dbus`
+fn status_options(args: &Args, config: &Config) -> FormatOptions {
+    FormatOptions {
+        no_color: true,
+        thresholds: thresholds(args, config),
+        cost: config.cost.clone(),
+        redact: args.redact,
+        models: args.model.clone(),
+        show: args.show.or(config.display.show).unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+/// The cached results of the `--provider` selection, or None when there is no usable cache
+fn cached_results(args: &Args) -> Option<CachedResults> {
+    let mut cached = match CachedResults::load() {
+        Ok(cached) => cached?,
+        Err(e) => {
+            if args.verbose {
+                eprintln!("[cache] could not read cache: {}", e);
+            }
+            return None;
+        }
+    };
+    let selected = provider_names(&args.provider);
+    cached.providers.retain(|d| selected.contains(&d.provider_name()));
+    Some(cached)
+}
+
+/// How long `ocu prompt` waits on `ocu serve` before falling back to the cache; prompts can't afford more
+const PROMPT_SERVE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// `ocu prompt`: render the latest results of a local `ocu serve`, or else the cached ones, as one short line
+/// Prints nothing when neither is available, so a prompt never shows error text
+async fn print_prompt(args: &Args, config: &Config, no_color: bool) -> ExitCode {
+    let served = match latest_report(config.serve.listen(), PROMPT_SERVE_TIMEOUT).await {
+        Ok(mut served) => {
+            let selected = provider_names(&args.provider);
+            served.providers.retain(|d| selected.contains(&d.provider_name()));
+            Some(served)
+        }
+        Err(e) => {
+            if args.verbose {
+                eprintln!("[serve] {}; using the cache", e);
+            }
+            None
+        }
+    };
+    let Some(cached) = served.or_else(|| cached_results(args)) else {
+        return ExitCode::from(Exit::Failure);
+    };
+    let data = cached.providers;
+
+    let options = FormatOptions {
+        no_color,
+        colors: config.colors.clone(),
+        show: args.show.or(config.display.show).unwrap_or_default(),
+        models: args.model.clone(),
+        fetched_at: Some(cached.fetched_at),
+        ..Default::default()
+    };
+    println!("{}", format_summary(&data, &options));
+    ExitCode::from(Exit::Success)
+}
+
+/// `ocu nvim-status`: the cached results as a Lua table, without touching the network
+/// Prints nothing when there is no usable cache, like `ocu prompt`
+fn print_nvim_status(args: &Args, config: &Config) -> ExitCode {
+    let Some(cached) = cached_results(args) else {
+        return ExitCode::from(Exit::Failure);
+    };
+    let options = FormatOptions {
+        fetched_at: Some(cached.fetched_at),
+        ..status_options(args, config)
+    };
+    println!("{}", format_nvim_status(&cached.providers, &options));
+    ExitCode::from(Exit::Success)
+}
+
+/// `ocu report`: summarize the runs recorded over the last week or month, optionally posting the result
+async fn print_report(
+    args: &Args,
+    config: &Config,
+    period: ReportPeriod,
+    format: ReportFormat,
+    webhook: Option<&str>,
+) -> ExitCode {
+    let mut report = match UsageReport::load(period, &thresholds(args, config), Utc::now()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error: could not read the usage history: {}", e);
+            return ExitCode::from(Exit::Failure);
+        }
+    };
+    if args.redact {
+        report = report.redacted();
+    }
+    let (document, content_type) = report.render(format);
+    print!("{}", document);
+
+    if let Some(url) = webhook {
+        if let Err(e) = post_report(url, &document, content_type, Duration::from_secs(args.timeout)).await {
+            eprintln!("Error: could not post the report: {}", e);
+            return ExitCode::from(Exit::Failure);
+        }
+    }
+    ExitCode::from(Exit::Success)
+}
+
+/// `ocu limits`: the published quotas of each detected plan next to current usage
+async fn print_limits(args: &Args, config: &Config) -> ExitCode {
+    let outcome = match fetch_all(run_options(args, config, None)).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(Exit::NoProviders);
+        }
+    };
+    match args.format {
+        OutputFormat::Json => println!("{}", format_limits_json(&outcome.results)),
+        _ => println!(
+            "{}",
+            format_limits_table(&outcome.results, args.style.or(config.display.style).unwrap_or_default())
+        ),
+    }
+    ExitCode::from(Exit::Success)
+}
+
+/// `ocu sessions`: OpenCode's recorded token usage over a time range, attributed to projects, sessions or models
+fn print_sessions(
+    args: &Args,
+    config: &Config,
+    since: DateTime<Utc>,
+    until: Option<DateTime<Utc>>,
+    group: SessionGroup,
+) -> ExitCode {
+    let Some(storage) = sessions::storage_dir() else {
+        eprintln!("Error: could not determine OpenCode's data directory");
+        return ExitCode::from(Exit::Failure);
+    };
+    // Without --provider every OpenCode provider counts, including ones ocu doesn't query
+    let providers = if args.provider.is_empty() || args.provider.contains(&ProviderArg::All) {
+        Vec::new()
+    } else {
+        provider_names(&args.provider)
+            .into_iter()
+            .flat_map(|name| sessions::opencode_provider_ids(name).iter().copied())
+            .collect()
+    };
+    let filter = SessionFilter {
+        since: Some(since),
+        until,
+        providers,
+    };
+    let messages = match sessions::load_messages(&storage, &filter) {
+        Ok(messages) => messages,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(Exit::Failure);
+        }
+    };
+    let totals = sessions::attribute(&messages, group, &config.cost);
+    match args.format {
+        OutputFormat::Json => println!("{}", format_sessions_json(&totals, group, since, until.unwrap_or_else(Utc::now))),
+        _ => println!(
+            "{}",
+            format_sessions_table(&totals, group, args.style.or(config.display.style).unwrap_or_default())
+        ),
+    }
+    ExitCode::from(Exit::Success)
+}
+
+/// `ocu auth status`: one line per provider describing its stored credentials
+/// Exits with 5 when a configured provider needs a new login
+fn print_auth_status(no_color: bool) -> ExitCode {
+    let statuses = AuthManager::new().auth_status();
+    let mut needs_login = false;
+
+    for status in &statuses {
+        let (symbol, detail) = describe_auth(status);
+        print_check(status.provider, symbol, &detail, no_color);
+        needs_login |= status.configured && status.needs_login();
+    }
+
+    if needs_login {
+        ExitCode::from(Exit::AuthExpired)
+    } else {
+        ExitCode::from(Exit::Success)
+    }
+}
+
+/// Clock differences above this make reset times visibly wrong
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
 
-    if has_errors {
-        ExitCode::from(1)
+/// `ocu doctor`: check the files ocu reads and compare the local clock with each provider's
+/// Exits with 1 when any check fails or warns
+async fn print_doctor(args: &Args, no_color: bool) -> ExitCode {
+    let auth = AuthManager::new();
+    let mut checks: Vec<(&str, &str, String)> = Vec::new();
+
+    let config_path = args.config.clone().or_else(Config::default_path);
+    checks.push(match config_path {
+        Some(path) if path.exists() => ("config", "✓", path.display().to_string()),
+        Some(path) => ("config", "-", format!("{} not found, using defaults", path.display())),
+        None => ("config", "-", "no home directory, using defaults".to_string()),
+    });
+    checks.push(match auth.opencode_auth_file() {
+        Some(path) => ("auth", "✓", path.display().to_string()),
+        None => ("auth", "-", "OpenCode auth.json not found".to_string()),
+    });
+    checks.push(match auth.antigravity_accounts_file() {
+        Some(path) => ("accounts", "✓", path.display().to_string()),
+        None => ("accounts", "-", "antigravity-accounts.json not found".to_string()),
+    });
+    // Credentials that need a new login, with the command that fixes them
+    let selected = provider_names(&args.provider);
+    for status in auth.auth_status() {
+        if selected.contains(&status.provider) && status.configured && status.needs_login() {
+            let (symbol, detail) = describe_auth(&status);
+            checks.push((status.provider, symbol, detail));
+        }
+    }
+
+    let timeout = Duration::from_secs(args.timeout);
+    let hosts: Vec<&str> = selected.into_iter().filter_map(api_host).collect();
+    let transport = ReqwestTransport::default();
+    let skews = futures::future::join_all(hosts.iter().map(|host| clock_skew(&transport, host, timeout))).await;
+    for (host, skew) in hosts.iter().zip(skews) {
+        checks.push(match skew {
+            Ok(skew) if skew.num_seconds().abs() <= MAX_CLOCK_SKEW_SECS => {
+                ("clock", "✓", format!("{} within {}s", host, skew.num_seconds().abs()))
+            }
+            Ok(skew) => {
+                let direction = if skew > chrono::Duration::zero() { "ahead of" } else { "behind" };
+                (
+                    "clock",
+                    "⚠",
+                    format!(
+                        "local clock is {} {} {}; reset times will be off",
+                        format_duration(chrono::Duration::seconds(skew.num_seconds().abs()), Default::default()),
+                        direction,
+                        host
+                    ),
+                )
+            }
+            Err(e) => ("clock", "✗", format!("{}: {}", host, e)),
+        });
+    }
+
+    let mut healthy = true;
+    for (name, symbol, detail) in &checks {
+        print_check(name, symbol, detail, no_color);
+        healthy &= *symbol != "✗" && *symbol != "⚠";
+    }
+
+    if healthy {
+        ExitCode::from(Exit::Success)
     } else {
-        ExitCode::from(0)
+        ExitCode::from(Exit::Failure)
+    }
+}
+
+/// One line of `ocu auth status` / `ocu doctor`, colored by its status symbol
+fn print_check(name: &str, symbol: &str, detail: &str, no_color: bool) {
+    let line = format!("{:<8} {} {}", name, symbol, detail);
+    if no_color {
+        println!("{}", line);
+    } else if symbol == "✗" {
+        println!("{}", line.red());
+    } else if symbol == "⚠" {
+        println!("{}", line.yellow());
+    } else if symbol == "-" {
+        println!("{}", line.bright_black());
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Status symbol and description of a provider's credentials
+fn describe_auth(status: &AuthStatus) -> (&'static str, String) {
+    let login = ProviderLinks::for_provider(status.provider).map_or("opencode auth login", |l| l.login);
+    let login = format!("run `{}`", login);
+    if !status.configured {
+        return ("-", format!("not configured — {}", login));
+    }
+    if let Some(accounts) = status.accounts {
+        let plural = if accounts == 1 { "" } else { "s" };
+        return ("✓", format!("{} account{}", accounts, plural));
+    }
+
+    let now = chrono::Utc::now();
+    let refresh = if status.can_refresh { " (refresh token stored)" } else { "" };
+    match status.expires_at {
+        None => ("✓", format!("token stored, no expiry{}", refresh)),
+        Some(t) if t > now => (
+            "✓",
+            format!("token expires in {}{}", format_duration(t - now, Default::default()), refresh),
+        ),
+        Some(t) if status.can_refresh => (
+            "⚠",
+            format!(
+                "token expired {} ago, refreshed on the next run",
+                format_duration(now - t, Default::default())
+            ),
+        ),
+        Some(t) => (
+            "✗",
+            format!("token expired {} ago — {}", format_duration(now - t, Default::default()), login),
+        ),
     }
 }
 
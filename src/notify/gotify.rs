@@ -0,0 +1,87 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::export::deliver;
+use crate::notify::{Notification, Notifier};
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::ProviderStatus;
+
+/// `[gotify]` section: a self-hosted Gotify server and the application token to post with
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GotifyConfig {
+    /// Server base URL, e.g. `https://gotify.example.net`
+    pub url: String,
+    /// Application token created in the Gotify web UI
+    pub token: String,
+}
+
+/// Posts notifications as Gotify messages, with the priority taken from the status
+pub struct GotifyNotifier {
+    url: String,
+    token: String,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl GotifyNotifier {
+    pub fn new(config: &GotifyConfig) -> Self {
+        Self {
+            url: format!("{}/message", config.url.trim_end_matches('/')),
+            token: config.token.clone(),
+            transport: Arc::new(ReqwestTransport::default()),
+        }
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Notifier for GotifyNotifier {
+    fn name(&self) -> &'static str {
+        "gotify"
+    }
+
+    async fn send(&self, notification: &Notification, timeout: Duration) -> Result<()> {
+        // Gotify's Android app only shows an icon below 4 and alerts loudest from 8
+        let priority = match notification.status {
+            ProviderStatus::Ok => 2,
+            ProviderStatus::Warning => 5,
+            ProviderStatus::Error => 8,
+        };
+        let request = reqwest::Client::new()
+            .post(&self.url)
+            .header("X-Gotify-Key", &self.token)
+            .json(&json!({
+                "title": notification.title,
+                "message": notification.message,
+                "priority": priority,
+            }));
+        deliver("gotify", self.transport.as_ref(), request, timeout).await
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{QuotaError, Result};
+use crate::export::deliver;
+use crate::notify::{Notification, Notifier};
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+
+/// `[matrix]` section: the homeserver, an access token of the posting account and the room to post in
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatrixConfig {
+    /// Homeserver base URL, e.g. `https://matrix.example.org`
+    pub homeserver: String,
+    /// Access token of the account posting the messages
+    pub access_token: String,
+    /// Room ID (`!abc123:example.org`) the account has joined
+    pub room_id: String,
+}
+
+/// Posts notifications as `m.notice` messages, which clients show without pinging anyone
+pub struct MatrixNotifier {
+    homeserver: String,
+    access_token: String,
+    room_id: String,
+    /// Makes transaction IDs unique within the process
+    sent: AtomicU64,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl MatrixNotifier {
+    pub fn new(config: &MatrixConfig) -> Self {
+        Self {
+            homeserver: config.homeserver.trim_end_matches('/').to_string(),
+            access_token: config.access_token.clone(),
+            room_id: config.room_id.clone(),
+            sent: AtomicU64::new(0),
+            transport: Arc::new(ReqwestTransport::default()),
+        }
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// `PUT /_matrix/client/v3/rooms/{room}/send/m.room.message/{txn}`, with the room ID escaped
+    fn send_url(&self) -> Result<Url> {
+        let mut url = Url::parse(&self.homeserver)
+            .map_err(|e| QuotaError::ConfigError(format!("invalid matrix.homeserver: {}", e)))?;
+        // The homeserver drops a repeated transaction ID as a duplicate, so every message gets its own
+        let txn = format!(
+            "ocu-{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            self.sent.fetch_add(1, Ordering::Relaxed)
+        );
+        url.path_segments_mut()
+            .map_err(|_| QuotaError::ConfigError("invalid matrix.homeserver: not a base URL".to_string()))?
+            .pop_if_empty()
+            .extend(["_matrix", "client", "v3", "rooms", &self.room_id, "send", "m.room.message", &txn]);
+        Ok(url)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Notifier for MatrixNotifier {
+    fn name(&self) -> &'static str {
+        "matrix"
+    }
+
+    async fn send(&self, notification: &Notification, timeout: Duration) -> Result<()> {
+        let request = reqwest::Client::new()
+            .put(self.send_url()?)
+            .bearer_auth(&self.access_token)
+            .json(&json!({
+                "msgtype": "m.notice",
+                "body": format!("{}\n{}", notification.title, notification.message),
+                "format": "org.matrix.custom.html",
+                "formatted_body": format!(
+                    "<strong>{}</strong><br>{}",
+                    escape_html(&notification.title),
+                    escape_html(&notification.message).replace('\n', "<br>")
+                ),
+            }));
+        deliver("matrix", self.transport.as_ref(), request, timeout).await
+    }
+}
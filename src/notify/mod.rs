@@ -0,0 +1,308 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+pub mod gotify;
+pub mod matrix;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::budget::{BudgetUsage, TOTAL};
+use crate::cache::cache_dir;
+use crate::config::Config;
+use crate::error::{QuotaError, Result};
+use crate::providers::{ProviderData, ProviderStatus, Thresholds};
+use gotify::GotifyNotifier;
+use matrix::MatrixNotifier;
+
+/// `[notify]` section: settings shared by every notification target
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// Also send a summary of every provider once a day, on the first run after this local time ("HH:MM")
+    pub summary_at: Option<String>,
+}
+
+impl NotifyConfig {
+    /// `summary_at` parsed, or None when no daily summary is wanted
+    pub fn summary_time(&self) -> Result<Option<NaiveTime>> {
+        self.summary_at
+            .as_deref()
+            .map(|time| {
+                NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| {
+                    QuotaError::ConfigError(format!("notify.summary_at must be \"HH:MM\", got \"{}\"", time))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Budget percentages that trigger a notification, once each per month
+const BUDGET_LEVELS: [u32; 3] = [50, 80, 100];
+
+/// A provider whose status changed since the previous run, a budget level reached, or the daily summary
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// Empty for the daily summary and the overall budget
+    pub provider: String,
+    /// New status; `Ok` means the provider recovered
+    pub status: ProviderStatus,
+    pub title: String,
+    pub message: String,
+}
+
+/// Somewhere notifications are pushed to
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait Notifier: Send + Sync {
+    /// Target name for warnings, e.g. "gotify"
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, notification: &Notification, timeout: Duration) -> Result<()>;
+}
+
+/// Every notification target configured in the config file
+pub fn notifiers(config: &Config) -> Vec<Box<dyn Notifier>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    if let Some(gotify) = &config.gotify {
+        notifiers.push(Box::new(GotifyNotifier::new(gotify)));
+    }
+    if let Some(matrix) = &config.matrix {
+        notifiers.push(Box::new(MatrixNotifier::new(matrix)));
+    }
+    notifiers
+}
+
+/// How a provider stood when last notified; failing is distinct from an exhausted quota
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum State {
+    Ok,
+    Warning,
+    Error,
+    Failed,
+}
+
+impl State {
+    fn of(data: &ProviderData, thresholds: &Thresholds) -> Self {
+        match data {
+            ProviderData::Failed { .. } => State::Failed,
+            _ => match data.status_with(thresholds) {
+                ProviderStatus::Ok => State::Ok,
+                ProviderStatus::Warning => State::Warning,
+                ProviderStatus::Error => State::Error,
+            },
+        }
+    }
+}
+
+/// Last notified state per provider, kept between runs so each change is reported once
+/// (~/.cache/ocu/notified.json)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NotifyState {
+    providers: BTreeMap<String, State>,
+    /// Day the last daily summary was sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summarized: Option<NaiveDate>,
+    /// Highest budget level notified per budget scope, with the month it was reached in
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    budgets: BTreeMap<String, (String, u32)>,
+}
+
+impl NotifyState {
+    fn default_path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("notified.json"))
+    }
+
+    /// The stored state, or an empty one when missing or unreadable
+    pub fn load() -> Self {
+        Self::default_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn store(&self) -> Result<()> {
+        let path = Self::default_path()
+            .ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Providers that crossed a threshold (either way), failed or recovered since the last run,
+    /// recording their new state; providers seen for the first time are only reported when not OK
+    pub fn changes(&mut self, current: &[ProviderData], thresholds: &Thresholds) -> Vec<Notification> {
+        current
+            .iter()
+            .filter_map(|d| {
+                let provider = d.provider_name();
+                let now = State::of(d, thresholds);
+                let before = self.providers.insert(provider.to_string(), now).unwrap_or(State::Ok);
+                if now == before {
+                    return None;
+                }
+
+                let (status, title, message) = match (now, d) {
+                    (State::Failed, ProviderData::Failed { error, .. }) => {
+                        (ProviderStatus::Error, format!("{} query failed", provider), error.clone())
+                    }
+                    (State::Ok, _) => (
+                        ProviderStatus::Ok,
+                        format!("{} back to normal", provider),
+                        format!("{} is under its usage thresholds again", provider),
+                    ),
+                    (State::Warning, _) => (
+                        ProviderStatus::Warning,
+                        format!("{} quota running low", provider),
+                        describe_windows(d, thresholds),
+                    ),
+                    _ => (
+                        ProviderStatus::Error,
+                        format!("{} quota exhausted", provider),
+                        describe_windows(d, thresholds),
+                    ),
+                };
+                Some(Notification {
+                    provider: provider.to_string(),
+                    status,
+                    title,
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    /// Budgets that reached a new level (50%, 80% or 100%) during `month`
+    /// Each level is reported once a month; a budget that jumps past several levels reports the highest
+    pub fn budget_alerts(&mut self, usage: &[BudgetUsage], month: &str) -> Vec<Notification> {
+        usage
+            .iter()
+            .filter_map(|budget| {
+                let level = BUDGET_LEVELS
+                    .into_iter()
+                    .rev()
+                    .find(|&level| budget.used_percent >= level as f64)?;
+                let notified = match self.budgets.get(&budget.scope) {
+                    Some((notified_month, notified)) if notified_month == month => *notified,
+                    _ => 0,
+                };
+                if level <= notified {
+                    return None;
+                }
+                self.budgets.insert(budget.scope.clone(), (month.to_string(), level));
+
+                let name = if budget.scope == TOTAL { "Monthly" } else { budget.scope.as_str() };
+                let (status, title) = if level >= 100 {
+                    (ProviderStatus::Error, format!("{} budget exceeded", name))
+                } else {
+                    (ProviderStatus::Warning, format!("{} budget {}% used", name, level))
+                };
+                Some(Notification {
+                    provider: if budget.scope == TOTAL { String::new() } else { budget.scope.clone() },
+                    status,
+                    title,
+                    message: format!("Estimated spend this month: {}", budget.describe()),
+                })
+            })
+            .collect()
+    }
+
+    /// The daily summary, when `now` is past `at` and none was sent today yet
+    pub fn summary(
+        &mut self,
+        current: &[ProviderData],
+        thresholds: &Thresholds,
+        at: NaiveTime,
+        now: DateTime<Local>,
+    ) -> Option<Notification> {
+        let today = now.date_naive();
+        if now.time() < at || self.summarized == Some(today) {
+            return None;
+        }
+        self.summarized = Some(today);
+
+        let lines: Vec<String> = current
+            .iter()
+            .map(|d| match d {
+                ProviderData::Failed { .. } => format!("{}: query failed", d.provider_name()),
+                _ => {
+                    let windows: Vec<String> = d
+                        .windows()
+                        .iter()
+                        .filter_map(|w| {
+                            let used = w.used_percent()?;
+                            Some(match &w.account {
+                                Some(account) => format!("{} ({}) {:.0}%", w.label, account, used),
+                                None => format!("{} {:.0}%", w.label, used),
+                            })
+                        })
+                        .collect();
+                    format!("{}: {}", d.provider_name(), windows.join(", "))
+                }
+            })
+            .collect();
+        let status = current
+            .iter()
+            .map(|d| match State::of(d, thresholds) {
+                State::Ok => ProviderStatus::Ok,
+                State::Warning => ProviderStatus::Warning,
+                State::Error | State::Failed => ProviderStatus::Error,
+            })
+            .max_by_key(|status| match status {
+                ProviderStatus::Ok => 0,
+                ProviderStatus::Warning => 1,
+                ProviderStatus::Error => 2,
+            })
+            .unwrap_or(ProviderStatus::Ok);
+        Some(Notification {
+            provider: String::new(),
+            status,
+            title: "Daily AI quota summary".to_string(),
+            message: lines.join("\n"),
+        })
+    }
+}
+
+/// Windows past their warning level, e.g. "7d Window at 85% (warning at 80%)"
+fn describe_windows(data: &ProviderData, thresholds: &Thresholds) -> String {
+    data.windows()
+        .iter()
+        .filter_map(|w| {
+            let used = w.used_percent()?;
+            let (warning, critical) = thresholds.levels(&w.provider, &w.label);
+            let label = match &w.account {
+                Some(account) => format!("{} ({})", w.label, account),
+                None => w.label.clone(),
+            };
+            if used >= critical {
+                Some(format!("{} at {:.0}% (limit at {:.0}%)", label, used, critical))
+            } else if used >= warning {
+                Some(format!("{} at {:.0}% (warning at {:.0}%)", label, used, warning))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
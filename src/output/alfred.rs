@@ -0,0 +1,131 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Serialize;
+
+use crate::cli::UsageView;
+use crate::output::time::{describe_age, describe_reset};
+use crate::output::FormatOptions;
+use crate::providers::{ProviderData, ProviderLinks, ProviderStatus};
+
+/// macOS system icons, so the workflow needs no image files of its own
+const ICON_OK: &str = "/System/Library/CoreServices/CoreTypes.bundle/Contents/Resources/ToolbarInfo.icns";
+const ICON_WARNING: &str = "/System/Library/CoreServices/CoreTypes.bundle/Contents/Resources/AlertCautionIcon.icns";
+const ICON_ERROR: &str = "/System/Library/CoreServices/CoreTypes.bundle/Contents/Resources/AlertStopIcon.icns";
+
+/// Alfred script filter document
+#[derive(Serialize)]
+struct ScriptFilter {
+    items: Vec<Item>,
+}
+
+#[derive(Serialize)]
+struct Item {
+    uid: String,
+    title: String,
+    subtitle: String,
+    /// Passed on when the item is actioned: the provider's usage dashboard
+    arg: String,
+    /// Text Alfred filters on when "Alfred filters results" is enabled
+    #[serde(rename = "match")]
+    match_text: String,
+    icon: Icon,
+    valid: bool,
+}
+
+#[derive(Serialize)]
+struct Icon {
+    path: &'static str,
+}
+
+fn icon(status: ProviderStatus) -> Icon {
+    Icon {
+        path: match status {
+            ProviderStatus::Ok => ICON_OK,
+            ProviderStatus::Warning => ICON_WARNING,
+            ProviderStatus::Error => ICON_ERROR,
+        },
+    }
+}
+
+/// Format data as Alfred script filter JSON: one item per window, or per failed provider,
+/// opening the provider's dashboard when actioned; subtitles of cached data end with its age
+pub fn format_alfred(data: &[ProviderData], options: &FormatOptions) -> String {
+    let age = options.fetched_at.map(|t| describe_age(t, options));
+    let with_age = |subtitle: String| match &age {
+        Some(age) => format!("{} ({})", subtitle, age),
+        None => subtitle,
+    };
+    let mut items = Vec::new();
+    for d in data {
+        let provider = d.provider_name();
+        let dashboard = ProviderLinks::for_provider(provider).map_or("", |l| l.dashboard);
+
+        if let ProviderData::Failed { error, .. } = d {
+            if options.shows_model(provider, "") {
+                items.push(Item {
+                    uid: provider.to_string(),
+                    title: format!("{}: query failed", provider),
+                    subtitle: with_age(error.split('{').next().unwrap_or(error).trim().trim_end_matches(':').to_string()),
+                    arg: dashboard.to_string(),
+                    match_text: provider.to_string(),
+                    icon: icon(ProviderStatus::Error),
+                    valid: !dashboard.is_empty(),
+                });
+            }
+            continue;
+        }
+
+        for w in d.windows() {
+            if !options.shows_model(provider, &w.label) {
+                continue;
+            }
+            let name = match &w.account {
+                Some(account) => format!("{} {} ({})", provider, w.label, account),
+                None => format!("{} {}", provider, w.label),
+            };
+            let (usage, status) = match w.used_percent() {
+                Some(used) => {
+                    let usage = match options.show {
+                        UsageView::Used => format!("{:.0}% used", used),
+                        UsageView::Remaining => format!("{:.0}% left", (100.0 - used).max(0.0)),
+                    };
+                    (usage, options.thresholds.status(provider, &w.label, used))
+                }
+                None => ("unlimited".to_string(), ProviderStatus::Ok),
+            };
+            let subtitle = match w.resets_at {
+                Some(t) => format!("Resets {}", describe_reset(t, options)),
+                None => "No reset time".to_string(),
+            };
+            items.push(Item {
+                uid: format!("{}/{}/{}", provider, w.account.as_deref().unwrap_or_default(), w.label),
+                title: format!("{}: {}", name, usage),
+                subtitle: with_age(subtitle),
+                arg: dashboard.to_string(),
+                match_text: name,
+                icon: icon(status),
+                valid: !dashboard.is_empty(),
+            });
+        }
+    }
+
+    match serde_json::to_string(&ScriptFilter { items }) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
@@ -0,0 +1,163 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+#[cfg(feature = "cli")]
+use tabled::settings::Color;
+
+use crate::providers::{Overage, ProviderStatus};
+
+/// ANSI sequence that resets the foreground color
+const FG_RESET: &str = "\x1b[39m";
+
+/// Gray used for unknown values
+pub fn muted(text: &str) -> String {
+    format!("\x1b[90m{}{}", text, FG_RESET)
+}
+
+/// A terminal foreground color
+/// Accepts a named ANSI color ("green", "bright-red"), a 256-color index ("208")
+/// or a truecolor hex value ("#ff8800")
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ColorSpec {
+    /// SGR parameters, e.g. "32" or "38;5;208"
+    sgr: String,
+}
+
+impl ColorSpec {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+
+        if let Some(hex) = value.strip_prefix('#') {
+            if hex.len() != 6 {
+                return None;
+            }
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            let (r, g, b) = (channel(0)?, channel(2)?, channel(4)?);
+            return Some(Self { sgr: format!("38;2;{};{};{}", r, g, b) });
+        }
+
+        if let Ok(index) = value.parse::<u8>() {
+            return Some(Self { sgr: format!("38;5;{}", index) });
+        }
+
+        let (base, name) = match value
+            .strip_prefix("bright-")
+            .or_else(|| value.strip_prefix("bright_"))
+            .or_else(|| value.strip_prefix("bright "))
+        {
+            Some(name) => (90, name),
+            None => (30, value.as_str()),
+        };
+        let offset = match name {
+            "black" => 0,
+            "red" => 1,
+            "green" => 2,
+            "yellow" => 3,
+            "blue" => 4,
+            "magenta" => 5,
+            "cyan" => 6,
+            "white" => 7,
+            _ => return None,
+        };
+        Some(Self { sgr: (base + offset).to_string() })
+    }
+
+    /// Wrap text in this color
+    pub fn paint(&self, text: &str) -> String {
+        format!("\x1b[{}m{}{}", self.sgr, text, FG_RESET)
+    }
+
+    /// This color as a tabled cell color
+    #[cfg(feature = "cli")]
+    pub fn table_color(&self) -> Color {
+        Color::new(format!("\x1b[{}m", self.sgr), FG_RESET)
+    }
+}
+
+impl TryFrom<String> for ColorSpec {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value).ok_or_else(|| {
+            format!(
+                "invalid color {:?} (expected a color name, a 0-255 index or #rrggbb)",
+                value
+            )
+        })
+    }
+}
+
+/// Colors and usage thresholds, configurable through the `[colors]` config section
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorScheme {
+    /// Low usage and OK status
+    pub ok: ColorSpec,
+    /// Medium usage and WARNING status
+    pub warning: ColorSpec,
+    /// High usage and ERROR status
+    pub error: ColorSpec,
+    /// Usage percentage from which usage is shown in the warning color
+    pub usage_warning: f64,
+    /// Usage percentage from which usage is shown in the error color
+    pub usage_critical: f64,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            ok: ColorSpec { sgr: "32".to_string() },
+            warning: ColorSpec { sgr: "33".to_string() },
+            error: ColorSpec { sgr: "31".to_string() },
+            usage_warning: 50.0,
+            usage_critical: 80.0,
+        }
+    }
+}
+
+impl ColorScheme {
+    /// Color grading for a used percentage
+    pub fn usage_color(&self, percent: f64) -> &ColorSpec {
+        if percent < self.usage_warning {
+            &self.ok
+        } else if percent < self.usage_critical {
+            &self.warning
+        } else {
+            &self.error
+        }
+    }
+
+    /// Color for a status indicator
+    pub fn status_color(&self, status: ProviderStatus) -> &ColorSpec {
+        match status {
+            ProviderStatus::Ok => &self.ok,
+            ProviderStatus::Warning => &self.warning,
+            ProviderStatus::Error => &self.error,
+        }
+    }
+
+    /// Color for an overage indicator; paying or blocked stands out as an error
+    pub fn overage_color(&self, overage: Overage) -> Option<&ColorSpec> {
+        match overage {
+            Overage::Disabled => None,
+            Overage::Enabled => Some(&self.warning),
+            Overage::Billing { .. } | Overage::Blocked { .. } => Some(&self.error),
+        }
+    }
+}
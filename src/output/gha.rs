@@ -0,0 +1,141 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use crate::output::simple::format_simple;
+use crate::output::time::{capitalize_first, describe_age, describe_reset};
+use crate::output::{prepare, FormatOptions};
+use crate::providers::{ProviderData, ProviderStatus};
+
+/// Format data as GitHub Actions workflow commands, one annotation per provider:
+/// `::notice` while under the warning level, `::warning` past it and `::error` past critical or on failure
+pub fn format_gha(data: &[ProviderData], options: &FormatOptions) -> String {
+    if data.is_empty() {
+        return "::warning title=ocu::No provider data available".to_string();
+    }
+    let plain = FormatOptions {
+        no_color: true,
+        ..options.clone()
+    };
+
+    data.iter()
+        .filter_map(|d| {
+            let text = format_simple(std::slice::from_ref(d), &plain);
+            if text.is_empty() {
+                return None;
+            }
+            let command = match d.status_with(&options.thresholds) {
+                ProviderStatus::Ok => "notice",
+                ProviderStatus::Warning => "warning",
+                ProviderStatus::Error => "error",
+            };
+            Some(format!(
+                "::{} title={}::{}",
+                command,
+                escape_property(&format!("{} quota", d.provider_name())),
+                escape_data(&text)
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Markdown table of every window, for the job summary, under the age of cached data
+pub fn format_step_summary(data: &[ProviderData], options: &FormatOptions) -> String {
+    let data = prepare(data, options);
+    let mut lines = vec!["### AI quota".to_string(), String::new()];
+    if let Some(fetched_at) = options.fetched_at {
+        lines.push(format!("_{}_", capitalize_first(&describe_age(fetched_at, options))));
+        lines.push(String::new());
+    }
+    lines.push("| Provider | Window | Used | Resets | Status |".to_string());
+    lines.push("| --- | --- | ---: | --- | --- |".to_string());
+
+    for d in data.iter() {
+        let provider = d.provider_name();
+        if let ProviderData::Failed { error, .. } = d {
+            if options.shows_model(provider, "") {
+                let reason = error.split('{').next().unwrap_or(error).trim().trim_end_matches(':');
+                lines.push(format!("| {} | - | - | - | failed: {} |", provider, escape_cell(reason)));
+            }
+            continue;
+        }
+        for window in d.windows() {
+            if !options.shows_model(provider, &window.label) {
+                continue;
+            }
+            let name = match &window.account {
+                Some(account) => format!("{} ({})", provider, account),
+                None => provider.to_string(),
+            };
+            let (used, status) = match window.used_percent() {
+                Some(used) => {
+                    let status = match options.thresholds.status(provider, &window.label, used) {
+                        ProviderStatus::Ok => "ok",
+                        ProviderStatus::Warning => "warning",
+                        ProviderStatus::Error => "critical",
+                    };
+                    (format!("{:.0}%", used), status)
+                }
+                None => ("unlimited".to_string(), "ok"),
+            };
+            let resets = window
+                .resets_at
+                .map(|t| describe_reset(t, options))
+                .unwrap_or_else(|| "-".to_string());
+            lines.push(format!(
+                "| {} | {} | {} | {} | {} |",
+                escape_cell(&name),
+                escape_cell(&window.label),
+                used,
+                escape_cell(&resets),
+                status
+            ));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Append the markdown table to `$GITHUB_STEP_SUMMARY`
+/// Returns false outside GitHub Actions, where the variable is unset
+pub fn write_step_summary(data: &[ProviderData], options: &FormatOptions) -> std::io::Result<bool> {
+    let path = match std::env::var_os("GITHUB_STEP_SUMMARY") {
+        Some(path) if !path.is_empty() => path,
+        _ => return Ok(false),
+    };
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", format_step_summary(data, options))?;
+    Ok(true)
+}
+
+/// Escape a workflow command message
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value, which also ends at `,` and `:`
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Keep a value inside one markdown table cell
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
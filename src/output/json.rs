@@ -15,22 +15,117 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use crate::budget::BudgetUsage;
+use crate::error::{ErrorRecord, Result};
+use crate::output::{prepare, FormatOptions};
+use crate::providers::events::RequestTiming;
 use crate::providers::ProviderData;
 use chrono::Utc;
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-/// JSON output structure
+/// JSON output structure, as written by `--format json` and read back by `parse_json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct JsonReport {
+    pub timestamp: String,
+    /// When the data was fetched; older than `timestamp` for cached data
+    pub fetched_at: String,
+    pub age_seconds: i64,
+    /// Set when the run was interrupted and providers are missing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub incomplete: bool,
+    /// Failed providers and accounts, so consumers can detect degraded data
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    pub providers: Vec<ProviderData>,
+    /// Estimated spend beyond the plan by provider (USD), for providers that can bill it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub estimated_cost_usd: BTreeMap<String, f64>,
+    /// Month-to-date estimated spend against the `[budget]` limits
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budgets: Vec<BudgetUsage>,
+    /// How the run went, for live fetches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ReportMeta>,
+}
+
+/// Diagnostics about the run that produced a report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReportMeta {
+    /// Every HTTP request made, in the order responses arrived
+    pub timings: Vec<RequestTiming>,
+}
+
+/// Parse a document previously written by `format_json`
+pub fn parse_json(json: &str) -> Result<JsonReport> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// JSON Schema of the `--format json` document, for generating typed bindings
+pub fn json_schema() -> String {
+    let schema = schemars::schema_for!(JsonReport);
+    match serde_json::to_string_pretty(&schema) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
+
+/// Format data as JSON
+pub fn format_json(data: &[ProviderData], options: &FormatOptions) -> String {
+    match serde_json::to_string_pretty(&build_report(data, options)) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
+
+/// The `format_json` report on a single line, for streams with one document per line
+/// Unlike `format_json`, this isn't reached through `format_output`, so it applies `--redact` and `--sort` itself
+pub fn format_json_line(data: &[ProviderData], options: &FormatOptions) -> String {
+    let data = prepare(data, options);
+    match serde_json::to_string(&build_report(&data, options)) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
+
+/// The document `format_json` writes
+pub(crate) fn build_report(data: &[ProviderData], options: &FormatOptions) -> JsonReport {
+    let now = Utc::now();
+    let fetched_at = options.fetched_at.unwrap_or(now);
+    JsonReport {
+        timestamp: now.to_rfc3339(),
+        fetched_at: fetched_at.to_rfc3339(),
+        age_seconds: now.signed_duration_since(fetched_at).num_seconds().max(0),
+        incomplete: options.incomplete,
+        warnings: collect_warnings(data),
+        providers: data.to_vec(),
+        estimated_cost_usd: data
+            .iter()
+            .filter_map(|d| Some((d.provider_name().to_string(), options.cost.estimate(d)?)))
+            .collect(),
+        budgets: options.budgets.clone(),
+        meta: (!options.timings.is_empty()).then(|| ReportMeta {
+            timings: options.timings.clone(),
+        }),
+    }
+}
+
+/// Structured error document for `--errors json`
 #[derive(Serialize)]
-struct JsonOutput<'a> {
+struct JsonErrors<'a> {
     timestamp: String,
-    providers: &'a [ProviderData],
+    /// True when no provider returned data
+    all_failed: bool,
+    errors: &'a [ErrorRecord],
 }
 
-/// Format data as JSON
-pub fn format_json(data: &[ProviderData]) -> String {
-    let output = JsonOutput {
+/// Format errors as a JSON document
+pub fn format_errors(errors: &[ErrorRecord], all_failed: bool) -> String {
+    let output = JsonErrors {
         timestamp: Utc::now().to_rfc3339(),
-        providers: data,
+        all_failed,
+        errors,
     };
 
     match serde_json::to_string_pretty(&output) {
@@ -38,3 +133,161 @@ pub fn format_json(data: &[ProviderData]) -> String {
         Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
     }
 }
+
+/// One message per failed provider or Gemini account
+fn collect_warnings(data: &[ProviderData]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for provider in data {
+        match provider {
+            ProviderData::Failed { provider, error, .. } => {
+                warnings.push(format!("{}: {}", provider, error));
+            }
+            ProviderData::Gemini(gemini) => {
+                for account in &gemini.accounts {
+                    if let Some(error) = &account.error {
+                        warnings.push(format!("gemini ({}): {}", account.email, error));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::OutputFormat;
+    use crate::output::format_output;
+    use crate::providers::{
+        ClaudeData, CodexData, CopilotData, CopilotQuota, GeminiAccountData, GeminiData, GeminiModelQuota, WindowQuota,
+        WindowUsage,
+    };
+    use chrono::{DateTime, Duration};
+
+    fn every_variant() -> Vec<ProviderData> {
+        let reset = "2026-10-16T12:30:45.123456789Z".parse::<DateTime<Utc>>().ok();
+        vec![
+            ProviderData::Gemini(GeminiData {
+                accounts: vec![
+                    GeminiAccountData {
+                        email: "user@example.com".to_string(),
+                        is_active: true,
+                        models: vec![GeminiModelQuota::new("Claude Models".to_string(), 40.0, reset)],
+                        all_models: vec![
+                            GeminiModelQuota::new("Claude Opus 4.5".to_string(), 40.0, reset),
+                            GeminiModelQuota::new("Gemini 3 Flash".to_string(), 100.0, None),
+                        ],
+                        error: None,
+                    },
+                    GeminiAccountData {
+                        email: "old@example.com".to_string(),
+                        is_active: false,
+                        models: Vec::new(),
+                        all_models: Vec::new(),
+                        error: Some("API error (403 Forbidden)".to_string()),
+                    },
+                ],
+            }),
+            ProviderData::Codex(CodexData {
+                plan: "plus".to_string(),
+                primary_window: Some(WindowQuota {
+                    window_seconds: Some(18000),
+                    ..WindowQuota::new(9, 7260)
+                }),
+                secondary_window: None,
+            }),
+            // Over the entitlement and billing overage
+            ProviderData::Copilot(CopilotData {
+                plan: "individual".to_string(),
+                premium_entitlement: 300,
+                premium_remaining: -20,
+                premium_used_percent: 100,
+                premium_unlimited: false,
+                overage_permitted: true,
+                overage_count: 20,
+                chat: Some(CopilotQuota {
+                    entitlement: 0,
+                    remaining: 0,
+                    unlimited: true,
+                    used_percent: None,
+                }),
+                completions: Some(CopilotQuota {
+                    entitlement: 2000,
+                    remaining: 1500,
+                    unlimited: false,
+                    used_percent: Some(25),
+                }),
+                quota_reset_date: "2026-11-01".to_string(),
+            }),
+            // Unlimited Enterprise seat
+            ProviderData::Copilot(CopilotData {
+                plan: "enterprise".to_string(),
+                premium_entitlement: 0,
+                premium_remaining: 0,
+                premium_used_percent: 0,
+                premium_unlimited: true,
+                overage_permitted: false,
+                overage_count: 0,
+                chat: None,
+                completions: None,
+                quota_reset_date: "2026-11-01".to_string(),
+            }),
+            ProviderData::Claude(ClaudeData {
+                five_hour: WindowUsage::new(23.5, reset),
+                seven_day: WindowUsage::new(85.0, reset.map(|t| t + Duration::days(3))),
+                seven_day_sonnet: Some(WindowUsage::new(12.0, None)),
+                seven_day_opus: None,
+                extra_usage_enabled: true,
+                extra_usage_spent_usd: Some(12.5),
+            }),
+            ProviderData::Failed {
+                provider: "codex".to_string(),
+                error: "Codex API error (401 Unauthorized): {\"detail\":\"bad token\"}".to_string(),
+                rate_limited: false,
+            },
+            ProviderData::Failed {
+                provider: "claude".to_string(),
+                error: "rate limited, retry in 45s".to_string(),
+                rate_limited: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn parses_its_own_output() {
+        let data = every_variant();
+        let json = format_output(&data, OutputFormat::Json, &FormatOptions::default());
+        let report = parse_json(&json).unwrap();
+        assert_eq!(report.providers, data);
+        assert_eq!(report.warnings.len(), 3);
+        assert_eq!(report.estimated_cost_usd.get("copilot"), Some(&0.8));
+    }
+
+    #[test]
+    fn every_text_format_shows_the_age_of_cached_data() {
+        let data = every_variant();
+        let options = FormatOptions {
+            no_color: true,
+            fetched_at: Some(Utc::now() - Duration::minutes(5)),
+            ..FormatOptions::default()
+        };
+        let formats = [
+            OutputFormat::Table,
+            OutputFormat::Simple,
+            OutputFormat::Summary,
+            OutputFormat::Statusline,
+            OutputFormat::Raycast,
+            OutputFormat::Alfred,
+            OutputFormat::Plasma,
+            OutputFormat::Gha,
+        ];
+        for format in formats {
+            let output = format_output(&data, format, &options);
+            assert!(output.to_lowercase().contains("as of 5m ago"), "{:?}: {}", format, output);
+        }
+        let summary = crate::output::gha::format_step_summary(&data, &options);
+        assert!(summary.contains("_As of 5m ago_"), "{}", summary);
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Serialize;
+
+use crate::providers::plans::{plan_usage, plans_for, PlanUsage};
+use crate::providers::ProviderData;
+
+/// `ocu limits --format json` document
+#[derive(Serialize)]
+struct LimitsReport {
+    limits: Vec<PlanUsage>,
+    notes: Vec<String>,
+}
+
+/// Why a provider's rows are missing or ambiguous, one line per provider
+fn limit_notes(results: &[ProviderData]) -> Vec<String> {
+    results
+        .iter()
+        .filter_map(|data| {
+            let provider = data.provider_name();
+            let note = match data {
+                ProviderData::Failed { .. } => "query failed, so the plan is unknown".to_string(),
+                ProviderData::Claude(_) => "the usage API doesn't report the plan, so every plan is listed".to_string(),
+                _ if !plans_for(data).is_empty() => return None,
+                _ => match data.reported_plan() {
+                    Some(plan) => format!("no published quotas for plan {:?}", plan),
+                    None => "no published quotas per plan".to_string(),
+                },
+            };
+            Some(format!("{}: {}", provider, note))
+        })
+        .collect()
+}
+
+/// Plan quotas next to current usage as a JSON document
+pub fn format_limits_json(results: &[ProviderData]) -> String {
+    let report = LimitsReport {
+        limits: results.iter().flat_map(plan_usage).collect(),
+        notes: limit_notes(results),
+    };
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
+
+/// Plan quotas next to current usage as a table, followed by notes and the pricing pages to check them against
+#[cfg(feature = "cli")]
+pub fn format_limits_table(results: &[ProviderData], style: crate::cli::TableStyle) -> String {
+    use crate::providers::ProviderLinks;
+    use tabled::builder::Builder;
+
+    let rows: Vec<PlanUsage> = results.iter().flat_map(plan_usage).collect();
+    let mut out = String::new();
+    if !rows.is_empty() {
+        let mut builder = Builder::default();
+        builder.push_record(["Provider", "Plan", "Window", "Included", "Used", "Approx. used"]);
+        for row in &rows {
+            let e = &row.entitlement;
+            builder.push_record([
+                row.provider.clone(),
+                row.plan.to_string(),
+                e.window.to_string(),
+                format!("{} / {}", e.amount.describe(e.unit), e.per),
+                row.used_percent.map(|p| format!("{:.0}%", p)).unwrap_or_else(|| "-".to_string()),
+                row.used_percent
+                    .and_then(|p| e.amount.used(p))
+                    .unwrap_or_else(|| "-".to_string()),
+            ]);
+        }
+        let mut table = builder.build();
+        table.with(super::table::build_theme(style, &[]));
+        out.push_str(&table.to_string());
+        out.push('\n');
+    }
+
+    for note in limit_notes(results) {
+        out.push_str(&format!("\n{}", note));
+    }
+    let mut providers: Vec<&str> = rows.iter().map(|r| r.provider.as_str()).collect();
+    providers.dedup();
+    if !providers.is_empty() {
+        out.push_str("\n\nQuotas as published by the providers, which revise them without notice:");
+        for provider in providers {
+            if let Some(links) = ProviderLinks::for_provider(provider) {
+                out.push_str(&format!("\n  {}: {}", provider, links.pricing));
+            }
+        }
+    }
+    out.trim_start().to_string()
+}
@@ -0,0 +1,66 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::output::{prepare, FormatOptions};
+use crate::providers::ProviderData;
+
+/// Format data as OpenMetrics text, as read by GitLab's `artifacts:reports:metrics`:
+/// the used percentage of every window and whether each provider answered
+pub fn format_metrics(data: &[ProviderData], options: &FormatOptions) -> String {
+    let data = prepare(data, options);
+    let mut lines = vec![
+        "# TYPE ocu_window_used_percent gauge".to_string(),
+        "# HELP ocu_window_used_percent Share of the window's quota consumed".to_string(),
+    ];
+    for d in data.iter() {
+        let provider = d.provider_name();
+        for window in d.windows() {
+            if !options.shows_model(provider, &window.label) {
+                continue;
+            }
+            // Unlimited windows have nothing to trend
+            let Some(used) = window.used_percent() else { continue };
+            let account = window
+                .account
+                .as_deref()
+                .map(|a| format!(",account=\"{}\"", escape_label(a)))
+                .unwrap_or_default();
+            lines.push(format!(
+                "ocu_window_used_percent{{provider=\"{}\"{},window=\"{}\"}} {:.1}",
+                provider,
+                account,
+                escape_label(&window.label),
+                used
+            ));
+        }
+    }
+
+    lines.push("# TYPE ocu_provider_up gauge".to_string());
+    lines.push("# HELP ocu_provider_up Whether the provider's quota query succeeded".to_string());
+    for d in data.iter() {
+        let up = if matches!(d, ProviderData::Failed { .. }) { 0 } else { 1 };
+        lines.push(format!("ocu_provider_up{{provider=\"{}\"}} {}", d.provider_name(), up));
+    }
+    lines.push("# EOF".to_string());
+
+    lines.join("\n") + "\n"
+}
+
+/// Escape a label value (backslash, quote and newline)
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
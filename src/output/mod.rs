@@ -15,18 +15,164 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+pub mod alfred;
+pub mod colors;
+pub mod gha;
 pub mod json;
+pub mod limits;
+pub mod metrics;
+pub mod nvim;
+pub mod pace;
+pub mod plasma;
+pub mod redact;
+pub mod report;
+pub mod sessions;
 pub mod simple;
+pub mod sort;
+pub mod statusline;
+pub mod summary;
+#[cfg(feature = "cli")]
 pub mod table;
+pub mod time;
 
-use crate::cli::OutputFormat;
-use crate::providers::ProviderData;
+use crate::budget::BudgetUsage;
+use crate::cli::{GroupBy, OutputFormat, SortKey, SortOrder, TableColumn, TableStyle, UsageView};
+use crate::glob::matches_filter;
+use crate::providers::events::RequestTiming;
+use crate::providers::{CostModel, Overage, ProviderData, Thresholds};
+use chrono::{DateTime, Utc};
+use colors::ColorScheme;
+use std::borrow::Cow;
+use time::{DurationStyle, Locale};
+
+/// Rendering options shared by the output formatters
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    /// Disable colored output
+    pub no_color: bool,
+    /// Link provider names to their usage dashboards (OSC 8)
+    pub hyperlinks: bool,
+    /// Colors and usage thresholds
+    pub colors: ColorScheme,
+    /// Disable inline usage bars in the table
+    pub no_bars: bool,
+    /// Plain status words instead of ✓/⚠️/✗ glyphs
+    pub no_emoji: bool,
+    /// Omit the headline above the table
+    pub no_summary: bool,
+    /// Mask account emails and hide plan identifiers
+    pub redact: bool,
+    /// Show how far through each window we are next to its usage
+    pub pace: bool,
+    /// Add a column with the warning/critical usage limits applied to each row
+    pub show_thresholds: bool,
+    /// Usage levels that make a row a warning or an error
+    pub thresholds: Thresholds,
+    /// Prices behind the estimated cost column and JSON field
+    pub cost: CostModel,
+    /// Month-to-date spend against the configured budgets
+    pub budgets: Vec<BudgetUsage>,
+    /// Table border style
+    pub style: TableStyle,
+    /// Table columns to show; empty shows the default set
+    pub columns: Vec<TableColumn>,
+    /// Truncate the table to this width in characters
+    pub width: Option<usize>,
+    /// Group table rows by provider or model family
+    pub group_by: GroupBy,
+    /// Show percentages as used or remaining
+    pub show: UsageView,
+    /// Show remaining counts instead of percentages where providers expose them
+    pub counts: bool,
+    /// Hide Claude's per-model 7-day windows (Sonnet/Opus) in table and simple output
+    pub hide_model_windows: bool,
+    /// Only show models/windows matching one of these patterns (substring or glob)
+    pub models: Vec<String>,
+    /// List every Gemini model individually instead of quota buckets
+    pub detailed: bool,
+    /// Render reset times as absolute timestamps instead of relative durations
+    pub absolute_times: bool,
+    /// Render absolute timestamps in UTC instead of the local timezone
+    pub utc: bool,
+    /// strftime pattern for absolute timestamps (defaults to "%Y-%m-%d %H:%M")
+    pub time_format: Option<String>,
+    /// Style for relative durations
+    pub duration_style: DurationStyle,
+    /// Language for humanized durations ("in 2 days", "dans 2 jours")
+    pub locale: Locale,
+    /// Sort providers and rows by this key (original order when unset)
+    pub sort: Option<SortKey>,
+    /// Direction applied when sorting
+    pub sort_order: SortOrder,
+    /// When the data was fetched, for data served from a cache
+    /// Fresh fetches leave this unset and show no freshness indicator
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// The run was interrupted before every provider answered
+    pub incomplete: bool,
+    /// Per-request timings of a live fetch, reported under `meta` in JSON
+    pub timings: Vec<RequestTiming>,
+}
+
+impl FormatOptions {
+    /// Whether a model or window passes the `--model` filter
+    /// Patterns are matched against the window name alone and prefixed by the provider
+    /// ("7d Opus" and "claude 7d Opus"), so `--model claude` keeps every Claude row
+    pub fn shows_model(&self, provider: &str, model: &str) -> bool {
+        if self.models.is_empty() {
+            return true;
+        }
+        let qualified = format!("{} {}", provider, model);
+        self.models
+            .iter()
+            .any(|p| matches_filter(p, model) || matches_filter(p, &qualified))
+    }
+}
+
+/// Overage indicator text, e.g. "paying 20 reqs ($0.80)"
+/// `cost` is the provider's `CostModel::estimate`, so `[cost]` prices apply here too
+pub fn describe_overage(overage: Overage, cost: Option<f64>) -> String {
+    match overage {
+        Overage::Disabled => "off".to_string(),
+        Overage::Enabled => "enabled".to_string(),
+        Overage::Billing { requests } => match cost {
+            Some(usd) => format!("paying {} reqs (${:.2})", requests, usd),
+            None => format!("paying {} reqs", requests),
+        },
+        Overage::Blocked { requests } => format!("blocked, {} over", requests),
+    }
+}
+
+/// Apply `--redact` and `--sort` before rendering
+pub(crate) fn prepare<'a>(data: &'a [ProviderData], options: &FormatOptions) -> Cow<'a, [ProviderData]> {
+    let data = if options.redact {
+        Cow::Owned(redact::redact_providers(data))
+    } else {
+        Cow::Borrowed(data)
+    };
+    match options.sort {
+        Some(key) => Cow::Owned(sort::sort_providers(&data, key, options.sort_order)),
+        None => data,
+    }
+}
 
 /// Format provider data according to the specified format
-pub fn format_output(data: &[ProviderData], format: OutputFormat, no_color: bool) -> String {
+pub fn format_output(data: &[ProviderData], format: OutputFormat, options: &FormatOptions) -> String {
+    let data = prepare(data, options);
+    let data = data.as_ref();
+
     match format {
-        OutputFormat::Table => table::format_table(data, no_color),
-        OutputFormat::Json => json::format_json(data),
-        OutputFormat::Simple => simple::format_simple(data, no_color),
+        #[cfg(feature = "cli")]
+        OutputFormat::Table => table::format_table(data, options),
+        // The table needs tabled; library builds without the `cli` feature render one line per provider
+        #[cfg(not(feature = "cli"))]
+        OutputFormat::Table => simple::format_simple(data, options),
+        OutputFormat::Json => json::format_json(data, options),
+        OutputFormat::Simple => simple::format_simple(data, options),
+        OutputFormat::Summary => summary::format_summary(data, options),
+        OutputFormat::Statusline => statusline::format_statusline(data, options),
+        OutputFormat::Raycast => summary::format_summary(data, options),
+        OutputFormat::Alfred => alfred::format_alfred(data, options),
+        OutputFormat::Plasma => plasma::format_plasma(data, options),
+        OutputFormat::Gha => gha::format_gha(data, options),
     }
 }
@@ -0,0 +1,82 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Write;
+
+use crate::output::statusline::build_statusline;
+use crate::output::FormatOptions;
+use crate::providers::{ProviderData, ProviderStatus};
+
+/// A Lua string literal, escaped so `load` reads back the same bytes
+fn lua_string(text: &str) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            c if c.is_ascii_control() => {
+                let _ = write!(literal, "\\{:03}", c as u32);
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Format data as a Lua table literal for Neovim statusline components,
+/// read with `load("return " .. output)()`:
+/// `{text="…",severity="warning",age=42,providers={{name="claude",status="warning",used=85,resets=1760000000},…}}`
+/// `used` and `resets` (Unix seconds) are left out when unknown, `age` when the data is fresh
+pub fn format_nvim_status(data: &[ProviderData], options: &FormatOptions) -> String {
+    let statusline = build_statusline(data, options);
+    let mut out = format!(
+        "{{text={},severity={}",
+        lua_string(&statusline.text),
+        lua_string(statusline.severity)
+    );
+    if let Some(fetched_at) = options.fetched_at {
+        let _ = write!(out, ",age={}", (chrono::Utc::now() - fetched_at).num_seconds().max(0));
+    }
+
+    out.push_str(",providers={");
+    for (i, d) in data.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let status = match d {
+            ProviderData::Failed { .. } => "failed",
+            _ => match d.status_with(&options.thresholds) {
+                ProviderStatus::Ok => "ok",
+                ProviderStatus::Warning => "warning",
+                ProviderStatus::Error => "error",
+            },
+        };
+        let _ = write!(out, "{{name={},status={}", lua_string(d.provider_name()), lua_string(status));
+        if let Some(used) = d.worst_used_percent() {
+            let _ = write!(out, ",used={:.0}", used);
+        }
+        if let Some(resets) = d.soonest_reset() {
+            let _ = write!(out, ",resets={}", resets.timestamp());
+        }
+        out.push('}');
+    }
+    out.push_str("}}");
+    out
+}
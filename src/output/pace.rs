@@ -0,0 +1,67 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Length of Claude's short rolling window
+pub const FIVE_HOURS: i64 = 5 * 3600;
+
+/// Length of the weekly rolling windows (Claude 7d, Codex secondary)
+pub const SEVEN_DAYS: i64 = 7 * 24 * 3600;
+
+/// Usage may lead elapsed time by this many points before a window counts as ahead,
+/// so bursts early in a window aren't flagged
+const AHEAD_MARGIN: i32 = 5;
+
+/// How far through its window a quota is, compared with how much of it is used
+#[derive(Debug, Clone, Copy)]
+pub struct Pace {
+    /// Percentage of the window already elapsed (0-100)
+    pub elapsed_percent: i32,
+    /// Usage is running ahead of time: at this rate the quota runs out before the reset
+    pub ahead: bool,
+}
+
+impl Pace {
+    /// Pace of a window that started at `start` and resets at `resets_at`
+    pub fn new(used_percent: i32, start: DateTime<Utc>, resets_at: DateTime<Utc>) -> Option<Self> {
+        let length = (resets_at - start).num_seconds();
+        if length <= 0 {
+            return None;
+        }
+        let elapsed = (Utc::now() - start).num_seconds().clamp(0, length);
+        let elapsed_percent = (elapsed * 100 / length) as i32;
+        Some(Self {
+            elapsed_percent,
+            ahead: used_percent > elapsed_percent + AHEAD_MARGIN,
+        })
+    }
+
+    /// Pace of a rolling window of known length
+    pub fn rolling(used_percent: i32, window_seconds: i64, resets_at: DateTime<Utc>) -> Option<Self> {
+        Self::new(used_percent, resets_at - Duration::seconds(window_seconds), resets_at)
+    }
+
+    /// Short description, e.g. "40% elapsed" or "40% elapsed, ahead"
+    pub fn describe(&self) -> String {
+        if self.ahead {
+            format!("{}% elapsed, ahead", self.elapsed_percent)
+        } else {
+            format!("{}% elapsed", self.elapsed_percent)
+        }
+    }
+}
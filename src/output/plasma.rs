@@ -0,0 +1,176 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::cli::UsageView;
+use crate::output::statusline::build_statusline;
+use crate::output::time::{capitalize_first, describe_age, describe_reset};
+use crate::output::FormatOptions;
+use crate::providers::{ProviderData, ProviderStatus};
+
+/// Plasma widget document, read by QML with `JSON.parse(data.stdout)`
+#[derive(Serialize)]
+struct Plasma {
+    /// Short uncolored text for the panel, as `--format summary`
+    text: String,
+    /// One line per window, for the tooltip, and the age of cached data
+    /// (the text ends with it too)
+    tooltip: String,
+    #[serde(flatten)]
+    style: Style,
+    items: Vec<Item>,
+}
+
+/// How to draw something of a given severity
+#[derive(Serialize)]
+struct Style {
+    /// "ok", "warning" or "error"
+    severity: &'static str,
+    /// Breeze color, for widgets that don't follow the color scheme
+    color: &'static str,
+    /// Kirigami.Theme property to color by, e.g. `Kirigami.Theme[item.theme_color]`
+    theme_color: &'static str,
+    /// Freedesktop icon name
+    icon: &'static str,
+}
+
+#[derive(Serialize)]
+struct Item {
+    provider: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+    /// Window label, or null for a failed provider
+    window: Option<String>,
+    /// Null when the window is unlimited or the query failed
+    used_percent: Option<f64>,
+    resets_at: Option<DateTime<Utc>>,
+    /// "in 2h 30m", as the other formats describe resets
+    resets_in: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(flatten)]
+    style: Style,
+}
+
+fn style(severity: &str) -> Style {
+    match severity {
+        "error" => Style {
+            severity: "error",
+            color: "#da4453",
+            theme_color: "negativeTextColor",
+            icon: "dialog-error",
+        },
+        "warning" => Style {
+            severity: "warning",
+            color: "#f67400",
+            theme_color: "neutralTextColor",
+            icon: "dialog-warning",
+        },
+        _ => Style {
+            severity: "ok",
+            color: "#27ae60",
+            theme_color: "positiveTextColor",
+            icon: "dialog-information",
+        },
+    }
+}
+
+fn status_style(status: ProviderStatus) -> Style {
+    style(match status {
+        ProviderStatus::Ok => "ok",
+        ProviderStatus::Warning => "warning",
+        ProviderStatus::Error => "error",
+    })
+}
+
+/// Format data as JSON for a KDE Plasma widget reading it through the executable data engine:
+/// the panel text and tooltip, plus one item per window with explicit color and icon hints
+pub fn format_plasma(data: &[ProviderData], options: &FormatOptions) -> String {
+    let statusline = build_statusline(data, options);
+    let mut items = Vec::new();
+    let mut tooltip = Vec::new();
+    for d in data {
+        let provider = d.provider_name();
+
+        if let ProviderData::Failed { error, .. } = d {
+            if options.shows_model(provider, "") {
+                let error = error.split('{').next().unwrap_or(error).trim().trim_end_matches(':');
+                tooltip.push(format!("{}: {}", provider, error));
+                items.push(Item {
+                    provider: provider.to_string(),
+                    account: None,
+                    window: None,
+                    used_percent: None,
+                    resets_at: None,
+                    resets_in: None,
+                    error: Some(error.to_string()),
+                    style: status_style(ProviderStatus::Error),
+                });
+            }
+            continue;
+        }
+
+        for w in d.windows() {
+            if !options.shows_model(provider, &w.label) {
+                continue;
+            }
+            let used = w.used_percent();
+            let status = used.map_or(ProviderStatus::Ok, |u| options.thresholds.status(provider, &w.label, u));
+            let resets_in = w.resets_at.map(|t| describe_reset(t, options));
+            let name = match &w.account {
+                Some(account) => format!("{} {} ({})", provider, w.label, account),
+                None => format!("{} {}", provider, w.label),
+            };
+            let usage = match (used, options.show) {
+                (Some(used), UsageView::Used) => format!("{:.0}% used", used),
+                (Some(used), UsageView::Remaining) => format!("{:.0}% left", (100.0 - used).max(0.0)),
+                (None, _) => "unlimited".to_string(),
+            };
+            tooltip.push(match &resets_in {
+                Some(resets) if used.is_some() => format!("{}: {}, resets {}", name, usage, resets),
+                _ => format!("{}: {}", name, usage),
+            });
+            items.push(Item {
+                provider: provider.to_string(),
+                account: w.account.clone(),
+                window: Some(w.label.clone()),
+                used_percent: used,
+                resets_at: w.resets_at,
+                resets_in,
+                error: None,
+                style: status_style(status),
+            });
+        }
+    }
+
+    if let Some(fetched_at) = options.fetched_at {
+        tooltip.push(capitalize_first(&describe_age(fetched_at, options)));
+    }
+
+    let document = Plasma {
+        text: statusline.text,
+        tooltip: tooltip.join("\n"),
+        style: style(statusline.severity),
+        items,
+    };
+    match serde_json::to_string(&document) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
@@ -0,0 +1,184 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::providers::{GeminiAccountData, ProviderData};
+
+/// Placeholder for identifiers that are hidden entirely
+const REDACTED: &str = "redacted";
+
+/// Copy of the data with account emails masked and plan identifiers hidden,
+/// so output can be shared in screenshots and public issues
+pub fn redact_providers(data: &[ProviderData]) -> Vec<ProviderData> {
+    data.iter()
+        .cloned()
+        .map(|mut provider| {
+            match &mut provider {
+                ProviderData::Gemini(gemini) => {
+                    gemini.accounts.iter_mut().for_each(redact_account);
+                }
+                ProviderData::Codex(codex) => codex.plan = REDACTED.to_string(),
+                ProviderData::Copilot(copilot) => copilot.plan = REDACTED.to_string(),
+                ProviderData::Claude(_) => {}
+                ProviderData::Failed { error, .. } => *error = redact_text(error),
+            }
+            provider
+        })
+        .collect()
+}
+
+fn redact_account(account: &mut GeminiAccountData) {
+    account.email = mask_email(&account.email);
+    if let Some(error) = &mut account.error {
+        *error = redact_text(error);
+    }
+}
+
+/// Mask the local part of an email address: "jane@gmail.com" -> "j***@gmail.com"
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first = local.chars().next().map(String::from).unwrap_or_default();
+            format!("{}***@{}", first, domain)
+        }
+        None => mask_word(email),
+    }
+}
+
+/// Mask every email-looking word in free text (error messages)
+pub fn redact_text(text: &str) -> String {
+    text.split(' ')
+        .map(|word| if word.contains('@') { mask_email(word) } else { word.to_string() })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Keep only the first character of an identifier
+fn mask_word(word: &str) -> String {
+    match word.chars().next() {
+        Some(first) => format!("{}***", first),
+        None => String::new(),
+    }
+}
+
+/// Replacement for scrubbed credentials
+const SECRET: &str = "[redacted]";
+
+/// JSON fields and query parameters that hold credentials or account identifiers
+const SECRET_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "token",
+    "account_id",
+    "accountId",
+    "chatgpt_account_id",
+    "client_secret",
+    "api_key",
+    "key",
+];
+
+/// Prefixes of well-known token formats (OpenAI/Anthropic keys, GitHub, Google OAuth, JWTs)
+const TOKEN_PREFIXES: &[&str] = &[
+    "sk-", "ghu_", "gho_", "ghp_", "ghs_", "ghr_", "github_pat_", "ya29.", "1//", "eyJ",
+];
+
+/// Scrub bearer tokens, refresh tokens and account IDs from text that is about to be echoed
+/// Unlike `redact_text` this always applies: response bodies and error messages can quote credentials
+pub fn scrub_secrets(text: &str) -> String {
+    let mut text = text.to_string();
+    for field in SECRET_FIELDS {
+        text = scrub_after(&text, &format!("\"{}\"", field), json_string_value);
+        text = scrub_after(&text, &format!("{}=", field), query_value);
+    }
+    scrub_words(&text)
+}
+
+/// Replace the value following each whole-word occurrence of `marker`
+/// `value` locates it as a (start, length) byte range in the text after the marker
+fn scrub_after(text: &str, marker: &str, value: fn(&str) -> Option<(usize, usize)>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(marker) {
+        let whole_word = !rest[..pos]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let end = pos + marker.len();
+        out.push_str(&rest[..end]);
+        rest = &rest[end..];
+        if let Some((start, len)) = value(rest).filter(|_| whole_word) {
+            out.push_str(&rest[..start]);
+            out.push_str(SECRET);
+            rest = &rest[start + len..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// String value of a JSON field, given the text after its quoted key
+fn json_string_value(after_key: &str) -> Option<(usize, usize)> {
+    let colon = after_key.trim_start();
+    let value = colon.strip_prefix(':')?.trim_start();
+    let contents = value.strip_prefix('"')?;
+    let start = after_key.len() - contents.len();
+
+    let mut escaped = false;
+    for (i, c) in contents.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return (i > 0).then_some((start, i)),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Query-string or form value, given the text after `name=`
+fn query_value(after_name: &str) -> Option<(usize, usize)> {
+    let len = after_name
+        .find(|c: char| c.is_whitespace() || matches!(c, '&' | '"' | '\'' | '#' | ')'))
+        .unwrap_or(after_name.len());
+    (len > 0).then_some((0, len))
+}
+
+/// Mask the word after "Bearer" and any word shaped like a known token
+fn scrub_words(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut after_bearer = false;
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let core = word.trim_matches(|c: char| matches!(c, '"' | '\'' | ',' | ';' | '(' | ')' | '[' | ']' | '{' | '}'));
+        if !core.is_empty() && (after_bearer || looks_like_token(core)) {
+            out.push_str(&piece.replacen(core, SECRET, 1));
+        } else {
+            out.push_str(piece);
+        }
+        if !core.is_empty() {
+            after_bearer = core.eq_ignore_ascii_case("bearer");
+        }
+    }
+    out
+}
+
+fn looks_like_token(word: &str) -> bool {
+    word.len() >= 20
+        && TOKEN_PREFIXES.iter().any(|prefix| word.starts_with(prefix))
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '+' | '=' | '~'))
+}
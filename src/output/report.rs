@@ -0,0 +1,172 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::report::UsageReport;
+
+/// A titled table of the report; `empty` stands in when it has no rows
+struct Section {
+    title: &'static str,
+    header: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+    empty: &'static str,
+}
+
+fn local_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn title(report: &UsageReport) -> String {
+    format!(
+        "AI quota report: {} to {}",
+        report.since.with_timezone(&Local).format("%Y-%m-%d"),
+        report.until.with_timezone(&Local).format("%Y-%m-%d")
+    )
+}
+
+fn intro(report: &UsageReport) -> String {
+    match report.samples {
+        0 => "No runs were recorded in this period.".to_string(),
+        1 => "Based on 1 recorded run.".to_string(),
+        n => format!("Based on {} recorded runs.", n),
+    }
+}
+
+fn sections(report: &UsageReport) -> [Section; 3] {
+    [
+        Section {
+            title: "Quota windows",
+            header: &["Provider", "Window", "Latest", "Peak", "Average", "Runs over warning", "Runs exhausted"],
+            rows: report
+                .windows
+                .iter()
+                .map(|w| {
+                    vec![
+                        w.provider.clone(),
+                        match &w.account {
+                            Some(account) => format!("{} ({})", w.label, account),
+                            None => w.label.clone(),
+                        },
+                        format!("{:.0}%", w.latest),
+                        format!("{:.0}% on {}", w.peak, local_time(w.peak_at)),
+                        format!("{:.0}%", w.average),
+                        w.over_warning.to_string(),
+                        w.exhausted.to_string(),
+                    ]
+                })
+                .collect(),
+            empty: "No usage recorded.",
+        },
+        Section {
+            title: "Spend beyond the plan",
+            header: &["Provider", "Estimated"],
+            rows: report
+                .spend
+                .iter()
+                .map(|(provider, usd)| vec![provider.clone(), format!("${:.2}", usd)])
+                .collect(),
+            empty: "Nothing billed beyond the plans.",
+        },
+        Section {
+            title: "Failures",
+            header: &["Provider", "Failed runs", "Last failure", "Last error"],
+            rows: report
+                .failures
+                .iter()
+                .map(|f| {
+                    vec![
+                        f.provider.clone(),
+                        f.count.to_string(),
+                        local_time(f.last_at),
+                        f.last_error.clone(),
+                    ]
+                })
+                .collect(),
+            empty: "No failed queries.",
+        },
+    ]
+}
+
+/// Pipes would end a Markdown table cell and newlines the row
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// The report as a Markdown document
+pub fn format_report_markdown(report: &UsageReport) -> String {
+    let mut out = format!("# {}\n\n{}\n", title(report), intro(report));
+    for section in sections(report) {
+        out.push_str(&format!("\n## {}\n\n", section.title));
+        if section.rows.is_empty() {
+            out.push_str(section.empty);
+            out.push('\n');
+            continue;
+        }
+        out.push_str(&format!("| {} |\n", section.header.join(" | ")));
+        out.push_str(&format!("|{}\n", "---|".repeat(section.header.len())));
+        for row in &section.rows {
+            let cells: Vec<String> = row.iter().map(|cell| escape_cell(cell)).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The report as a self-contained HTML page
+pub fn format_report_html(report: &UsageReport) -> String {
+    let title = escape_html(&title(report));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         td, th {{ padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: left; }}\n\
+         </style></head>\n<body><h1>{}</h1>\n<p>{}</p>\n",
+        title,
+        title,
+        escape_html(&intro(report))
+    );
+    for section in sections(report) {
+        out.push_str(&format!("<h2>{}</h2>\n", section.title));
+        if section.rows.is_empty() {
+            out.push_str(&format!("<p>{}</p>\n", section.empty));
+            continue;
+        }
+        out.push_str("<table><tr>");
+        for name in section.header {
+            out.push_str(&format!("<th>{}</th>", name));
+        }
+        out.push_str("</tr>\n");
+        for row in &section.rows {
+            out.push_str("<tr>");
+            for cell in row {
+                out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body></html>\n");
+    out
+}
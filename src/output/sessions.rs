@@ -0,0 +1,103 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::cli::SessionGroup;
+use crate::sessions::UsageTotal;
+
+/// `ocu sessions --format json` document
+#[derive(Serialize)]
+struct SessionsReport<'a> {
+    since: String,
+    until: String,
+    group_by: &'static str,
+    totals: &'a [UsageTotal],
+}
+
+fn group_name(group: SessionGroup) -> &'static str {
+    match group {
+        SessionGroup::Project => "project",
+        SessionGroup::Session => "session",
+        SessionGroup::Model => "model",
+    }
+}
+
+/// Token count with a k/M suffix, e.g. "1.2M"
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..=999 => tokens.to_string(),
+        1_000..=999_999 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+/// The attributed usage as a JSON document
+pub fn format_sessions_json(
+    totals: &[UsageTotal],
+    group: SessionGroup,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> String {
+    let report = SessionsReport {
+        since: since.to_rfc3339(),
+        until: until.to_rfc3339(),
+        group_by: group_name(group),
+        totals,
+    };
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
+
+/// The attributed usage as a table, largest share first
+#[cfg(feature = "cli")]
+pub fn format_sessions_table(totals: &[UsageTotal], group: SessionGroup, style: crate::cli::TableStyle) -> String {
+    use tabled::builder::Builder;
+
+    if totals.is_empty() {
+        return "No OpenCode messages in this range.".to_string();
+    }
+    let mut builder = Builder::default();
+    builder.push_record([
+        match group {
+            SessionGroup::Project => "Project",
+            SessionGroup::Session => "Session",
+            SessionGroup::Model => "Model",
+        },
+        "Messages",
+        "Tokens",
+        "Cache reads",
+        "Share",
+        "Est. cost",
+    ]);
+    for total in totals {
+        builder.push_record([
+            total.name.clone(),
+            total.messages.to_string(),
+            format_tokens(total.tokens.total()),
+            format_tokens(total.tokens.cache_read),
+            format!("{:.0}%", total.share_percent),
+            total.cost_usd.map(|usd| format!("${:.2}", usd)).unwrap_or_else(|| "?".to_string()),
+        ]);
+    }
+    let mut table = builder.build();
+    table.with(super::table::build_theme(style, &[]));
+    table.to_string()
+}
@@ -15,171 +15,257 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::providers::{ClaudeData, CodexData, CopilotData, GeminiData, ProviderData};
-use chrono::Utc;
-use colored::Colorize;
+use crate::output::time::{describe_age, describe_reset};
+use crate::cli::UsageView;
+use crate::output::colors::muted;
+use crate::output::pace::{Pace, FIVE_HOURS, SEVEN_DAYS};
+use crate::output::{describe_overage, FormatOptions};
+use crate::providers::{ClaudeData, CodexData, CopilotData, GeminiData, Overage, ProviderData};
+use chrono::{DateTime, Utc};
 
 /// Format data as simple text (one line per provider)
-pub fn format_simple(data: &[ProviderData], no_color: bool) -> String {
+pub fn format_simple(data: &[ProviderData], options: &FormatOptions) -> String {
     if data.is_empty() {
         return "No provider data available.".to_string();
     }
 
+    let age = options.fetched_at.map(|t| describe_age(t, options));
+
+    // Providers without any model passing the --model filter render nothing
     data.iter()
-        .map(|d| format_provider_simple(d, no_color))
+        .map(|d| format_provider_simple(d, options))
+        .filter(|line| !line.is_empty())
+        .map(|line| match &age {
+            // Gemini may render several lines for one provider
+            Some(age) => line
+                .lines()
+                .map(|l| format!("{} ({})", l, age))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => line,
+        })
         .collect::<Vec<_>>()
         .join("\n")
 }
 
-fn format_provider_simple(data: &ProviderData, no_color: bool) -> String {
-    match data {
-        ProviderData::Gemini(gemini) => format_gemini_simple(gemini, no_color),
-        ProviderData::Codex(codex) => format_codex_simple(codex, no_color),
-        ProviderData::Copilot(copilot) => format_copilot_simple(copilot, no_color),
-        ProviderData::Claude(claude) => format_claude_simple(claude, no_color),
-        ProviderData::Failed { provider, .. } => format_failed_simple(provider, no_color),
+fn format_provider_simple(data: &ProviderData, options: &FormatOptions) -> String {
+    let no_color = options.no_color;
+    let line = match data {
+        ProviderData::Gemini(gemini) => format_gemini_simple(gemini, options),
+        ProviderData::Codex(codex) => format_codex_simple(codex, options),
+        ProviderData::Copilot(copilot) => format_copilot_simple(copilot, options),
+        ProviderData::Claude(claude) => format_claude_simple(claude, options),
+        ProviderData::Failed { provider, .. } if !options.shows_model(provider, "") => String::new(),
+        ProviderData::Failed { provider, error, rate_limited } => {
+            format_failed_simple(provider, error, *rate_limited, no_color)
+        }
+    };
+
+    // Flag providers that can bill beyond the plan, e.g. "[overage: paying 20 reqs ($0.80)]"
+    match data.overage() {
+        Some(overage) if overage != Overage::Disabled && !line.is_empty() => {
+            let text = format!("[overage: {}]", describe_overage(overage, options.cost.estimate(data)));
+            let text = match options.colors.overage_color(overage) {
+                Some(color) if !no_color => color.paint(&text),
+                _ => text,
+            };
+            format!("{} {}", line, text)
+        }
+        _ => line,
     }
 }
 
-fn colorize_usage(percent: i32, no_color: bool) -> String {
-    let s = format!("{}%", percent);
-    if no_color {
-        return s;
+fn colorize_usage(percent: i32, options: &FormatOptions) -> String {
+    let text = match options.show {
+        UsageView::Used => format!("{}%", percent),
+        UsageView::Remaining => format!("{}% left", 100 - percent),
+    };
+    colorize_by_usage(&text, percent, options)
+}
+
+/// Color text according to a used percentage
+fn colorize_by_usage(text: &str, percent: i32, options: &FormatOptions) -> String {
+    if options.no_color {
+        return text.to_string();
     }
-    if percent < 50 {
-        s.green().to_string()
-    } else if percent < 80 {
-        s.yellow().to_string()
-    } else {
-        s.red().to_string()
+    options.colors.usage_color(percent as f64).paint(text)
+}
+
+/// Append window progress (e.g. "62% (40% elapsed, ahead)") when `--pace` is set
+fn with_pace(usage: String, pace: Option<Pace>, options: &FormatOptions) -> String {
+    match pace {
+        Some(pace) if options.pace => {
+            let text = pace.describe();
+            let text = if pace.ahead && !options.no_color {
+                options.colors.warning.paint(&text)
+            } else {
+                text
+            };
+            format!("{} ({})", usage, text)
+        }
+        _ => usage,
     }
 }
 
-fn format_gemini_simple(data: &GeminiData, no_color: bool) -> String {
+/// Describe when a window resets, or "-" when unknown
+fn format_reset(dt: Option<DateTime<Utc>>, options: &FormatOptions) -> String {
+    dt.map(|t| describe_reset(t, options))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_gemini_simple(data: &GeminiData, options: &FormatOptions) -> String {
     data.accounts
         .iter()
-        .map(|account| {
+        .filter_map(|account| {
             let active_marker = if account.is_active { "" } else { " [inactive]" };
 
-            let models = account
-                .models
+            if let Some(error) = &account.error {
+                if !options.shows_model("gemini", "") {
+                    return None;
+                }
+                let label = format!("gemini ({}){}", account.email, active_marker);
+                return Some(format_failed_simple(&label, error, false, options.no_color));
+            }
+
+            let source: Vec<_> = if options.detailed { &account.all_models } else { &account.models }
+                .iter()
+                .filter(|m| options.shows_model("gemini", &m.model))
+                .collect();
+            if source.is_empty() && !options.models.is_empty() {
+                return None;
+            }
+            let models = source
                 .iter()
                 .map(|m| {
                     // Invert usage: 100% remaining -> 0% used
                     let used_percent = (100.0 - m.remaining_percent).round() as i32;
-                    let usage_str = colorize_usage(used_percent, no_color);
+                    let usage_str = colorize_usage(used_percent, options);
                     format!("{}: {}", m.model, usage_str)
                 })
                 .collect::<Vec<_>>()
                 .join(", ");
 
-            let reset = account
-                .models
-                .first()
-                .and_then(|m| m.reset_time)
-                .map(|t| {
-                    let now = Utc::now();
-                    let duration = t.signed_duration_since(now);
-                    if duration.num_hours() > 24 {
-                        format!("{} days", duration.num_days())
-                    } else if duration.num_hours() > 0 {
-                        format!("{}h {}m", duration.num_hours(), duration.num_minutes() % 60)
-                    } else {
-                        format!("{}m", duration.num_minutes())
-                    }
-                })
-                .unwrap_or_else(|| "-".to_string());
+            let reset = format_reset(source.first().and_then(|m| m.reset_time), options);
 
-            format!(
-                "Gemini ({}){}: {} - resets in {}",
+            Some(format!(
+                "Gemini ({}){}: {} - resets {}",
                 account.email, active_marker, models, reset
-            )
+            ))
         })
         .collect::<Vec<_>>()
         .join("\n")
 }
 
-fn format_codex_simple(data: &CodexData, no_color: bool) -> String {
-    let primary_reset = if data.primary_window.resets_in_seconds > 3600 {
-        format!("{}h", data.primary_window.resets_in_seconds / 3600)
-    } else {
-        format!("{}m", data.primary_window.resets_in_seconds / 60)
-    };
-
-    let primary_usage = colorize_usage(data.primary_window.used_percent, no_color);
-    let secondary_usage = colorize_usage(data.secondary_window.used_percent, no_color);
+fn format_codex_simple(data: &CodexData, options: &FormatOptions) -> String {
+    let windows = data
+        .windows()
+        .filter(|(label, _)| options.shows_model("codex", label))
+        .map(|(label, window)| {
+            let default_window = if label == "Primary" { FIVE_HOURS } else { SEVEN_DAYS };
+            let length = window.window_seconds.unwrap_or(default_window);
+            let pace = Pace::rolling(window.used_percent, length, window.resets_at);
+            let usage = with_pace(colorize_usage(window.used_percent, options), pace, options);
+            format!("{}: {}", label.to_lowercase(), usage)
+        })
+        .collect::<Vec<_>>();
+    if windows.is_empty() {
+        return String::new();
+    }
 
-    format!(
-        "Codex: primary: {}, secondary: {} - primary resets in {}",
-        primary_usage, secondary_usage, primary_reset
-    )
+    // The first window present (normally the primary) is the one about to reset
+    match data.windows().next() {
+        Some((label, window)) => format!(
+            "Codex: {} - {} resets {}",
+            windows.join(", "),
+            label.to_lowercase(),
+            describe_reset(window.resets_at, options)
+        ),
+        None => String::new(),
+    }
 }
 
-fn format_copilot_simple(data: &CopilotData, no_color: bool) -> String {
+fn format_copilot_simple(data: &CopilotData, options: &FormatOptions) -> String {
     let used = data.premium_entitlement - data.premium_remaining;
-    let overage_used = (-data.premium_remaining).max(0);
-
     // Calculate usage percentage for coloring
-    let used_percent = if data.premium_entitlement > 0 {
-        let remaining_fraction = data.premium_remaining as f64 / data.premium_entitlement as f64;
-        ((1.0 - remaining_fraction) * 100.0).clamp(0.0, 100.0) as i32
-    } else {
-        0
+    let used_percent = data.premium_used_percent;
+
+    let usage_text = match options.show {
+        _ if data.premium_unlimited => "unlimited".to_string(),
+        UsageView::Used => format!("used {}/{}", used, data.premium_entitlement),
+        UsageView::Remaining => format!("{}/{} left", data.premium_remaining.max(0), data.premium_entitlement),
     };
+    let pace = data
+        .quota_period_start()
+        .zip(data.quota_reset_at())
+        .filter(|_| !data.premium_unlimited)
+        .and_then(|(start, end)| Pace::new(used_percent, start, end));
+    let usage_display = with_pace(colorize_by_usage(&usage_text, used_percent, options), pace, options);
 
-    let usage_display = if no_color {
-        format!("{}/{}", used, data.premium_entitlement)
-    } else {
-        let s = format!("{}/{}", used, data.premium_entitlement);
-        if used_percent < 50 {
-            s.green().to_string()
-        } else if used_percent < 80 {
-            s.yellow().to_string()
-        } else {
-            s.red().to_string()
+    let show_premium = options.shows_model("copilot", "Premium Requests");
+    let mut secondary = String::new();
+    for (label, quota) in [("chat", &data.chat), ("completions", &data.completions)] {
+        if !options.shows_model("copilot", label) {
+            continue;
         }
-    };
+        if let Some(quota) = quota {
+            let usage = match quota.used_percent {
+                Some(percent) if options.counts => colorize_by_usage(
+                    &format!("{} of {} left", quota.remaining.max(0), quota.entitlement),
+                    percent,
+                    options,
+                ),
+                Some(percent) => colorize_usage(percent, options),
+                None => "unlimited".to_string(),
+            };
+            secondary.push_str(&format!(", {}: {}", label, usage));
+        }
+    }
 
-    if overage_used > 0 {
-        format!(
-            "Copilot: used {} ({} over entitlement, permitted: {}) - resets {}",
-            usage_display, overage_used, data.overage_permitted, data.quota_reset_date
-        )
-    } else {
-        format!(
-            "Copilot: used {} - resets {}",
-            usage_display, data.quota_reset_date
-        )
+    if !show_premium {
+        return match secondary.strip_prefix(", ") {
+            Some(quotas) => format!("Copilot: {} - resets {}", quotas, data.quota_reset_date),
+            None => String::new(),
+        };
     }
+
+    format!(
+        "Copilot: {}{} - resets {}",
+        usage_display, secondary, data.quota_reset_date
+    )
 }
 
-fn format_claude_simple(data: &ClaudeData, no_color: bool) -> String {
-    let five_h_reset = data
-        .five_hour
-        .resets_at
-        .map(|t| {
-            let now = Utc::now();
-            let duration = t.signed_duration_since(now);
-            if duration.num_hours() > 24 {
-                format!("{} days", duration.num_days())
-            } else if duration.num_hours() > 0 {
-                format!("{}h {}m", duration.num_hours(), duration.num_minutes() % 60)
-            } else {
-                format!("{}m", duration.num_minutes())
-            }
-        })
-        .unwrap_or_else(|| "-".to_string());
+fn format_claude_simple(data: &ClaudeData, options: &FormatOptions) -> String {
+    let five_h_reset = format_reset(data.five_hour.resets_at, options);
 
-    let five_h_usage = colorize_usage(data.five_hour.utilization as i32, no_color);
-    let seven_d_usage = colorize_usage(data.seven_day.utilization as i32, no_color);
+    // Labels match the table rows so --model filters both formats alike
+    let mut windows = vec![
+        ("5h Window", "5h", Some(&data.five_hour), FIVE_HOURS),
+        ("7d Window", "7d", Some(&data.seven_day), SEVEN_DAYS),
+    ];
+    if !options.hide_model_windows {
+        windows.push(("7d Sonnet", "7d sonnet", data.seven_day_sonnet.as_ref(), SEVEN_DAYS));
+        windows.push(("7d Opus", "7d opus", data.seven_day_opus.as_ref(), SEVEN_DAYS));
+    }
 
-    format!(
-        "Claude: 5h: {}, 7d: {} - 5h resets in {}",
-        five_h_usage, seven_d_usage, five_h_reset
-    )
+    let windows = windows
+        .into_iter()
+        .filter(|(label, _, _, _)| options.shows_model("claude", label))
+        .filter_map(|(_, short, window, length)| {
+            window.map(|w| {
+                let used = w.utilization as i32;
+                let pace = w.resets_at.and_then(|t| Pace::rolling(used, length, t));
+                format!("{}: {}", short, with_pace(colorize_usage(used, options), pace, options))
+            })
+        })
+        .collect::<Vec<_>>();
+    if windows.is_empty() {
+        return String::new();
+    }
+
+    format!("Claude: {} - 5h resets {}", windows.join(", "), five_h_reset)
 }
 
-fn format_failed_simple(provider: &str, no_color: bool) -> String {
+fn format_failed_simple(provider: &str, error: &str, rate_limited: bool, no_color: bool) -> String {
     // Capitalize first letter of provider name
     let display_name = {
         let mut chars = provider.chars();
@@ -189,9 +275,18 @@ fn format_failed_simple(provider: &str, no_color: bool) -> String {
         }
     };
 
+    // Drop any JSON response body, keeping the line short
+    let reason = error.split('{').next().unwrap_or(error).trim().trim_end_matches(':');
+    // "rate limited, retry in 45s" already says what happened
+    let reason = if rate_limited {
+        reason.to_string()
+    } else {
+        format!("query failed: {}", reason)
+    };
+
     if no_color {
-        format!("{}: ? (query failed)", display_name)
+        format!("{}: ? ({})", display_name, reason)
     } else {
-        format!("{}: {} (query failed)", display_name, "?".bright_black())
+        format!("{}: {} ({})", display_name, muted("?"), reason)
     }
 }
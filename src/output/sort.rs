@@ -0,0 +1,106 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+
+use crate::cli::{SortKey, SortOrder};
+use crate::providers::{GeminiModelQuota, ProviderData};
+
+/// Compare usage percentages; entries without a known usage always sort last
+pub(crate) fn compare_usage(a: Option<f64>, b: Option<f64>, order: SortOrder) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => apply_order(a.partial_cmp(&b).unwrap_or(Ordering::Equal), order),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Compare reset times; entries without a known reset always sort last
+pub(crate) fn compare_resets(
+    a: Option<DateTime<Utc>>,
+    b: Option<DateTime<Utc>>,
+    order: SortOrder,
+) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => apply_order(a.cmp(&b), order),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+pub(crate) fn compare_names(a: &str, b: &str, order: SortOrder) -> Ordering {
+    apply_order(a.cmp(b), order)
+}
+
+fn apply_order(ordering: Ordering, order: SortOrder) -> Ordering {
+    match order {
+        SortOrder::Asc => ordering,
+        SortOrder::Desc => ordering.reverse(),
+    }
+}
+
+/// Return a sorted copy of the provider data
+/// Providers are ordered by their most significant window; Gemini accounts and
+/// models are ordered within the provider as well
+pub fn sort_providers(data: &[ProviderData], key: SortKey, order: SortOrder) -> Vec<ProviderData> {
+    let mut sorted = data.to_vec();
+
+    for provider in sorted.iter_mut() {
+        if let ProviderData::Gemini(gemini) = provider {
+            for account in gemini.accounts.iter_mut() {
+                sort_gemini_models(&mut account.models, key, order);
+                sort_gemini_models(&mut account.all_models, key, order);
+            }
+            gemini.accounts.sort_by(|a, b| match key {
+                SortKey::Usage => compare_usage(
+                    a.models.iter().map(|m| 100.0 - m.remaining_percent).reduce(f64::max),
+                    b.models.iter().map(|m| 100.0 - m.remaining_percent).reduce(f64::max),
+                    order,
+                ),
+                SortKey::Reset => compare_resets(
+                    a.models.iter().filter_map(|m| m.reset_time).min(),
+                    b.models.iter().filter_map(|m| m.reset_time).min(),
+                    order,
+                ),
+                SortKey::Name => compare_names(&a.email, &b.email, order),
+            });
+        }
+    }
+
+    sorted.sort_by(|a, b| match key {
+        SortKey::Usage => compare_usage(a.worst_used_percent(), b.worst_used_percent(), order),
+        SortKey::Reset => compare_resets(a.soonest_reset(), b.soonest_reset(), order),
+        SortKey::Name => compare_names(a.provider_name(), b.provider_name(), order),
+    });
+
+    sorted
+}
+
+fn sort_gemini_models(models: &mut [GeminiModelQuota], key: SortKey, order: SortOrder) {
+    models.sort_by(|a, b| match key {
+        SortKey::Usage => compare_usage(
+            Some(100.0 - a.remaining_percent),
+            Some(100.0 - b.remaining_percent),
+            order,
+        ),
+        SortKey::Reset => compare_resets(a.reset_time, b.reset_time, order),
+        SortKey::Name => Ordering::Equal,
+    });
+}
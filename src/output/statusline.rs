@@ -0,0 +1,78 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Serialize;
+
+use crate::output::json::{build_report, JsonReport};
+use crate::output::summary::format_summary;
+use crate::output::FormatOptions;
+use crate::providers::{ProviderData, ProviderStatus};
+
+/// One `--format statusline` document
+#[derive(Debug, Serialize)]
+pub struct Statusline {
+    /// Short uncolored text, as `--format summary`
+    pub text: String,
+    /// "ok", "warning" or "error"
+    pub severity: &'static str,
+    /// The full report, as `--format json`
+    pub details: JsonReport,
+}
+
+/// The statusline document for already redacted and sorted data
+pub fn build_statusline(data: &[ProviderData], options: &FormatOptions) -> Statusline {
+    let plain = FormatOptions {
+        no_color: true,
+        ..options.clone()
+    };
+    Statusline {
+        text: format_summary(data, &plain),
+        severity: severity(data, options),
+        details: build_report(data, options),
+    }
+}
+
+/// Format data as a single JSON line for OpenCode plugins and status bars:
+/// a short text, a severity to style it by and the full report
+pub fn format_statusline(data: &[ProviderData], options: &FormatOptions) -> String {
+    match serde_json::to_string(&build_statusline(data, options)) {
+        Ok(json) => json,
+        Err(e) => format!("{{\"error\": \"Failed to serialize: {}\"}}", e),
+    }
+}
+
+/// Worst window status under the thresholds; failed providers only warn
+/// while others still answer, so one outage doesn't turn the whole line red
+fn severity(data: &[ProviderData], options: &FormatOptions) -> &'static str {
+    let all_failed = data.iter().all(|d| matches!(d, ProviderData::Failed { .. }));
+    let worst = data
+        .iter()
+        .map(|d| match d {
+            ProviderData::Failed { .. } if !all_failed => ProviderStatus::Warning,
+            _ => d.status_with(&options.thresholds),
+        })
+        .max_by_key(|status| match status {
+            ProviderStatus::Ok => 0,
+            ProviderStatus::Warning => 1,
+            ProviderStatus::Error => 2,
+        });
+    match worst {
+        Some(ProviderStatus::Error) => "error",
+        Some(ProviderStatus::Warning) => "warning",
+        _ => "ok",
+    }
+}
@@ -0,0 +1,117 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::cli::UsageView;
+use crate::output::colors::muted;
+use crate::output::time::describe_age;
+use crate::output::FormatOptions;
+use crate::providers::{GeminiData, ProviderData, WindowKind};
+
+/// Format data as a single line (e.g. `claude 62%/41% codex 18%/9% copilot 23% gemini 12%`)
+/// Meant for MOTDs, shell greetings and scripts; cached data ends with its age, e.g. `(as of 5m ago)`
+pub fn format_summary(data: &[ProviderData], options: &FormatOptions) -> String {
+    let parts = data
+        .iter()
+        .filter_map(|d| format_provider_summary(d, options))
+        .collect::<Vec<_>>();
+
+    if parts.is_empty() {
+        return "no data".to_string();
+    }
+
+    let line = match options.show {
+        UsageView::Used => parts.join(" "),
+        UsageView::Remaining => format!("{} left", parts.join(" ")),
+    };
+    match options.fetched_at.map(|t| describe_age(t, options)) {
+        Some(age) if options.no_color => format!("{} ({})", line, age),
+        Some(age) => format!("{} {}", line, muted(&format!("({})", age))),
+        None => line,
+    }
+}
+
+/// Render one provider as `name p1/p2`, or None when the --model filter hides every window
+fn format_provider_summary(data: &ProviderData, options: &FormatOptions) -> Option<String> {
+    let name = data.provider_name();
+    let percents: Vec<i32> = match data {
+        ProviderData::Gemini(gemini) => gemini_used_percent(gemini, options).into_iter().collect(),
+        // Session and weekly windows; Claude's per-model windows are left to the table
+        ProviderData::Codex(_) | ProviderData::Claude(_) => data
+            .windows()
+            .iter()
+            .filter(|w| matches!(w.kind, WindowKind::Session | WindowKind::Weekly))
+            .filter(|w| options.shows_model(name, &w.label))
+            .filter_map(|w| w.used_percent())
+            .map(|percent| percent.round() as i32)
+            .collect(),
+        ProviderData::Copilot(copilot) if copilot.premium_unlimited => {
+            return options
+                .shows_model("copilot", "Premium Requests")
+                .then(|| format!("{} unlimited", name));
+        }
+        ProviderData::Copilot(copilot) => options
+            .shows_model("copilot", "Premium Requests")
+            .then_some(copilot.premium_used_percent)
+            .into_iter()
+            .collect(),
+        ProviderData::Failed { provider, .. } => {
+            if !options.shows_model(provider, "") {
+                return None;
+            }
+            let unknown = if options.no_color { "?".to_string() } else { muted("?") };
+            return Some(format!("{} {}", provider, unknown));
+        }
+    };
+
+    if percents.is_empty() {
+        return None;
+    }
+
+    let values = percents
+        .iter()
+        .map(|&percent| {
+            let text = match options.show {
+                UsageView::Used => format!("{}%", percent),
+                UsageView::Remaining => format!("{}%", 100 - percent),
+            };
+            if options.no_color {
+                text
+            } else {
+                options.colors.usage_color(percent as f64).paint(&text)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some(format!("{} {}", name, values))
+}
+
+/// Most-used quota of the active Gemini account (or the first healthy one)
+fn gemini_used_percent(data: &GeminiData, options: &FormatOptions) -> Option<i32> {
+    let account = data
+        .accounts
+        .iter()
+        .filter(|a| !a.is_failed())
+        .find(|a| a.is_active)
+        .or_else(|| data.accounts.iter().find(|a| !a.is_failed()))?;
+
+    if options.detailed { &account.all_models } else { &account.models }
+        .iter()
+        .filter(|m| options.shows_model("gemini", &m.model))
+        .map(|m| m.used_percent.round() as i32)
+        .max()
+}
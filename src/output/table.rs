@@ -15,14 +15,158 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use crate::cli::{GroupBy, SortKey, SortOrder, TableColumn, TableStyle, UsageView};
+use crate::output::colors::ColorScheme;
+use crate::output::pace::{Pace, FIVE_HOURS, SEVEN_DAYS};
+use crate::output::sort::{compare_names, compare_resets, compare_usage};
+use crate::output::time::{capitalize_first, describe_age, format_reset};
+use crate::output::{describe_overage, FormatOptions};
 use crate::providers::{
-    ClaudeData, CodexData, CopilotData, GeminiAccountData, GeminiData, ProviderData, ProviderStatus,
+    ClaudeData, CodexData, CopilotData, CopilotQuota, GeminiAccountData, GeminiData, ProviderData,
+    Overage, ProviderLinks, ProviderStatus, WindowUsage,
 };
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
 use tabled::{
-    builder::Builder, settings::span::Span, settings::style::HorizontalLine,
-    settings::themes::BorderCorrection, settings::Color, settings::Style,
+    builder::Builder,
+    grid::config::HorizontalLine,
+    settings::object::Rows,
+    settings::peaker::Priority,
+    settings::span::Span,
+    settings::style::Style,
+    settings::themes::{BorderCorrection, Theme},
+    settings::{Color, Width},
 };
 
+/// Value of the Status column
+#[derive(Debug, Clone, Copy)]
+enum RowStatus {
+    Provider(ProviderStatus),
+    /// The provider turned the query away with 429
+    RateLimited,
+    /// The provider query failed
+    Failed,
+}
+
+impl RowStatus {
+    fn severity(self) -> u8 {
+        match self {
+            RowStatus::Provider(ProviderStatus::Ok) => 0,
+            RowStatus::Provider(ProviderStatus::Warning) => 1,
+            RowStatus::Provider(ProviderStatus::Error) => 2,
+            RowStatus::RateLimited => 3,
+            RowStatus::Failed => 4,
+        }
+    }
+
+    fn color(self, colors: &ColorScheme) -> Color {
+        match self {
+            RowStatus::Provider(status) => get_status_color(status, colors),
+            RowStatus::RateLimited => colors.warning.table_color(),
+            RowStatus::Failed => colors.error.table_color(),
+        }
+    }
+}
+
+/// A single table row before it is pushed into the builder
+#[derive(Clone)]
+struct Row {
+    model: String,
+    usage: String,
+    reset: String,
+    status: RowStatus,
+    /// Short explanation appended to the status (failure reason)
+    status_detail: Option<String>,
+    usage_color: Option<Color>,
+    status_color: Option<Color>,
+    /// Percentage drawn as an inline bar in the usage cell
+    bar_percent: Option<i32>,
+    /// Sort keys
+    used_percent: Option<f64>,
+    resets_at: Option<DateTime<Utc>>,
+    /// Window progress, for windows of known length
+    pace: Option<Pace>,
+    /// Overage state of the row's provider
+    overage: Option<Overage>,
+    /// Estimated spend of the row's provider (USD)
+    cost: Option<f64>,
+}
+
+impl Row {
+    /// A usage row with per-row status derived from the used percentage and the thresholds
+    fn usage(
+        provider: &str,
+        model: &str,
+        used_percent: i32,
+        reset: String,
+        resets_at: Option<DateTime<Utc>>,
+        options: &FormatOptions,
+    ) -> Self {
+        let status = options.thresholds.status(provider, model, used_percent as f64);
+        let colors = &options.colors;
+        Self {
+            model: model.to_string(),
+            usage: format_percent(used_percent, options.show),
+            reset,
+            status: RowStatus::Provider(status),
+            status_detail: None,
+            usage_color: Some(get_usage_color(used_percent, colors)),
+            status_color: Some(get_status_color(status, colors)),
+            bar_percent: Some(used_percent),
+            used_percent: Some(used_percent as f64),
+            resets_at,
+            pace: None,
+            overage: None,
+            cost: None,
+        }
+    }
+
+    /// Attach window progress for a window that started at `start`
+    fn with_window_start(mut self, start: Option<DateTime<Utc>>) -> Self {
+        if let (Some(start), Some(resets_at), Some(used)) = (start, self.resets_at, self.bar_percent) {
+            self.pace = Pace::new(used, start, resets_at);
+        }
+        self
+    }
+
+    /// Attach window progress for a rolling window of known length
+    fn with_window_seconds(mut self, window_seconds: i64) -> Self {
+        if let (Some(resets_at), Some(used)) = (self.resets_at, self.bar_percent) {
+            self.pace = Pace::rolling(used, window_seconds, resets_at);
+        }
+        self
+    }
+}
+
+/// A group of rows sharing one spanned provider cell (a provider, or one Gemini account)
+struct Section {
+    provider: String,
+    /// Provider name used for sorting
+    name: String,
+    rows: Vec<Row>,
+}
+
+impl Section {
+    fn new(name: &str, provider: String) -> Self {
+        Self {
+            provider,
+            name: name.to_string(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn max_used(&self) -> Option<f64> {
+        self.rows
+            .iter()
+            .filter_map(|r| r.used_percent)
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    }
+
+    fn soonest_reset(&self) -> Option<DateTime<Utc>> {
+        self.rows.iter().filter_map(|r| r.resets_at).min()
+    }
+}
+
 /// Format data as a pretty table with UTF-8 borders
 /// Features:
 /// - Solid UTF-8 lines
@@ -30,131 +174,192 @@ use tabled::{
 /// - Cell spanning for provider/account sections
 /// - Dotted separators between sections (providers and Gemini accounts)
 /// - Proper colorization using tabled's Color settings
-pub fn format_table(data: &[ProviderData], no_color: bool) -> String {
+/// - Optional sorting of sections and rows
+/// - Optional pivot by model family (`--group-by model`)
+pub fn format_table(data: &[ProviderData], options: &FormatOptions) -> String {
     if data.is_empty() {
         return "No provider data available.".to_string();
     }
 
+    let mut sections: Vec<Section> = data
+        .iter()
+        .flat_map(|d| {
+            let overage = d.overage();
+            let cost = options.cost.estimate(d);
+            provider_sections(d, options).into_iter().map(move |mut section| {
+                for row in &mut section.rows {
+                    row.overage = overage;
+                    row.cost = cost;
+                }
+                section
+            })
+        })
+        .filter(|s| !s.rows.is_empty())
+        .collect();
+
+    if !options.models.is_empty() {
+        for section in &mut sections {
+            let provider = section.name.clone();
+            section.rows.retain(|row| {
+                // The overage row belongs to the premium request quota
+                let model = match row.model.as_str() {
+                    "Over Entitlement" => "Premium Requests",
+                    model => model,
+                };
+                options.shows_model(&provider, model)
+            });
+        }
+        sections.retain(|s| !s.rows.is_empty());
+    }
+
+    // The headline describes the data, so compute it before pivoting
+    let summary = (!options.no_summary).then(|| summary_line(&sections, options));
+
+    // The Overage column appears as soon as any provider can bill beyond its plan
+    let show_overage = sections
+        .iter()
+        .flat_map(|s| &s.rows)
+        .any(|r| r.overage.is_some_and(|o| o != Overage::Disabled));
+    // Likewise the cost column once anything has been billed
+    let show_cost = sections.iter().flat_map(|s| &s.rows).any(|r| r.cost.is_some_and(|c| c > 0.0));
+
+    if options.group_by == GroupBy::Model {
+        sections = group_by_model(sections);
+    }
+
+    // Optional columns appear when their flag is set or when `columns` asks for them;
+    // a `columns` list also hides everything it leaves out
+    let listed = |column| options.columns.contains(&column);
+    let columns: Vec<TableColumn> = [
+        (TableColumn::Provider, true),
+        (TableColumn::Model, true),
+        (TableColumn::Usage, true),
+        (TableColumn::Resets, true),
+        (TableColumn::Status, true),
+        (TableColumn::Overage, show_overage || listed(TableColumn::Overage)),
+        (TableColumn::Pace, options.pace || listed(TableColumn::Pace)),
+        (TableColumn::Limits, options.show_thresholds || listed(TableColumn::Limits)),
+        (TableColumn::Cost, show_cost || listed(TableColumn::Cost)),
+    ]
+    .into_iter()
+    .filter(|&(column, shown)| shown && (options.columns.is_empty() || listed(column)))
+    .map(|(column, _)| column)
+    .collect();
+    let col = |column| columns.iter().position(|c| *c == column);
+    let header: Vec<&str> = columns
+        .iter()
+        .map(|column| match (column, options.group_by) {
+            (TableColumn::Provider, GroupBy::Provider) => "Provider",
+            (TableColumn::Provider, GroupBy::Model) => "Model Family",
+            (TableColumn::Model, GroupBy::Provider) => "Model",
+            (TableColumn::Model, GroupBy::Model) => "Source",
+            (TableColumn::Usage, _) => "Usage",
+            (TableColumn::Resets, _) => "Resets",
+            (TableColumn::Status, _) => "Status",
+            (TableColumn::Overage, _) => "Overage",
+            (TableColumn::Pace, _) => "Pace",
+            (TableColumn::Limits, _) => "Limits",
+            (TableColumn::Cost, _) => "Est. cost",
+        })
+        .collect();
+
+    if let Some(key) = options.sort {
+        sort_sections(&mut sections, key, options.sort_order);
+    }
+
     let mut builder = Builder::default();
 
     // Add header as first record
-    builder.push_record(["Provider", "Model", "Usage", "Resets", "Status"]);
+    builder.push_record(header);
+
+    // The overage and cost cells belong to the provider, so they span its section like the provider cell
+    let span_overage = options.group_by == GroupBy::Provider;
 
-    // Track section spans (each Gemini account is a section, other providers are single sections)
-    let mut section_spans: Vec<(usize, usize)> = Vec::new(); // (start_row, row_count)
-    let mut cell_colors: Vec<(usize, usize, Color)> = Vec::new(); // (row, col, color)
+    // Track section spans (start_row, row_count) and cell colors (row, col, color)
+    let mut section_spans: Vec<(usize, usize)> = Vec::new();
+    let mut provider_links: Vec<(usize, Option<&str>)> = Vec::new();
+    let mut cell_colors: Vec<(usize, usize, Color)> = Vec::new();
     let mut current_row = 1usize; // Start after header
 
-    for provider_data in data {
-        let spans = add_provider_rows(
-            &mut builder,
-            provider_data,
-            no_color,
-            current_row,
-            &mut cell_colors,
-        );
-        for (start, count) in spans {
-            if count > 0 {
-                section_spans.push((start, count));
-                current_row = start + count;
+    for section in &sections {
+        for (i, row) in section.rows.iter().enumerate() {
+            let overage = row.overage.filter(|_| i == 0 || !span_overage);
+            let cost = row.cost.filter(|_| i == 0 || !span_overage);
+            let record: Vec<String> = columns
+                .iter()
+                .map(|column| match column {
+                    TableColumn::Provider if i == 0 => section.provider.clone(),
+                    TableColumn::Provider => String::new(),
+                    TableColumn::Model => row.model.clone(),
+                    TableColumn::Usage => usage_cell(row, options),
+                    TableColumn::Resets => row.reset.clone(),
+                    TableColumn::Status => status_cell(row, options),
+                    TableColumn::Overage => overage.map(|o| describe_overage(o, cost)).unwrap_or_default(),
+                    TableColumn::Pace => row.pace.map(|p| p.describe()).unwrap_or_default(),
+                    TableColumn::Limits => thresholds_cell(row, &options.colors),
+                    TableColumn::Cost => cost.map(|usd| format!("${:.2}", usd)).unwrap_or_default(),
+                })
+                .collect();
+            builder.push_record(record);
+
+            let mut color_cell = |column, color: Color| {
+                if let Some(col) = col(column) {
+                    cell_colors.push((current_row + i, col, color));
+                }
+            };
+            if let Some(color) = overage.and_then(|o| options.colors.overage_color(o)) {
+                color_cell(TableColumn::Overage, color.table_color());
+            }
+            if let Some(color) = &row.usage_color {
+                color_cell(TableColumn::Usage, color.clone());
+            }
+            if let Some(color) = &row.status_color {
+                color_cell(TableColumn::Status, color.clone());
+            }
+            if row.pace.is_some_and(|pace| pace.ahead) {
+                color_cell(TableColumn::Pace, options.colors.warning.table_color());
             }
         }
+        section_spans.push((current_row, section.rows.len()));
+        provider_links.push((current_row, ProviderLinks::for_provider(&section.name).map(|l| l.dashboard)));
+        current_row += section.rows.len();
     }
 
     let mut table = builder.build();
 
-    // Build horizontal lines: double line after header + dotted lines between sections
-    let double_line = HorizontalLine::full('═', '╪', '╞', '╡');
-    let dotted_line = HorizontalLine::full('┄', '┼', '├', '┤');
-
-    // Collect separator positions (after each section except the last)
-    let mut separator_rows: Vec<usize> = Vec::new();
-    for (i, (start_row, row_count)) in section_spans.iter().enumerate() {
-        if i < section_spans.len() - 1 {
-            separator_rows.push(start_row + row_count);
-        }
-    }
+    table.with(build_theme(options.style, &section_spans));
 
-    // Apply style based on number of separators needed
-    match separator_rows.len() {
-        0 => {
-            table.with(Style::rounded().horizontals([(1, double_line)]));
-        }
-        1 => {
-            table.with(
-                Style::rounded().horizontals([(1, double_line), (separator_rows[0], dotted_line)]),
-            );
-        }
-        2 => {
-            table.with(Style::rounded().horizontals([
-                (1, double_line),
-                (separator_rows[0], dotted_line.clone()),
-                (separator_rows[1], dotted_line),
-            ]));
-        }
-        3 => {
-            table.with(Style::rounded().horizontals([
-                (1, double_line),
-                (separator_rows[0], dotted_line.clone()),
-                (separator_rows[1], dotted_line.clone()),
-                (separator_rows[2], dotted_line),
-            ]));
-        }
-        4 => {
-            table.with(Style::rounded().horizontals([
-                (1, double_line),
-                (separator_rows[0], dotted_line.clone()),
-                (separator_rows[1], dotted_line.clone()),
-                (separator_rows[2], dotted_line.clone()),
-                (separator_rows[3], dotted_line),
-            ]));
-        }
-        5 => {
-            table.with(Style::rounded().horizontals([
-                (1, double_line),
-                (separator_rows[0], dotted_line.clone()),
-                (separator_rows[1], dotted_line.clone()),
-                (separator_rows[2], dotted_line.clone()),
-                (separator_rows[3], dotted_line.clone()),
-                (separator_rows[4], dotted_line),
-            ]));
-        }
-        6 => {
-            table.with(Style::rounded().horizontals([
-                (1, double_line),
-                (separator_rows[0], dotted_line.clone()),
-                (separator_rows[1], dotted_line.clone()),
-                (separator_rows[2], dotted_line.clone()),
-                (separator_rows[3], dotted_line.clone()),
-                (separator_rows[4], dotted_line.clone()),
-                (separator_rows[5], dotted_line),
-            ]));
-        }
-        _ => {
-            // Fallback for 7+ separators
-            table.with(Style::rounded().horizontals([(1, double_line)]));
+    // Apply cell spanning for provider column only (status is per-row)
+    let spanned = [
+        col(TableColumn::Provider),
+        col(TableColumn::Overage).filter(|_| span_overage),
+        col(TableColumn::Cost).filter(|_| span_overage),
+    ];
+    for (start_row, row_count) in &section_spans {
+        if *row_count > 1 {
+            for column in spanned.into_iter().flatten() {
+                table.modify((*start_row, column), Span::row(*row_count as isize));
+            }
         }
     }
 
-    // Apply cell spanning for provider column only (status is now per-row)
-    for (start_row, row_count) in &section_spans {
-        if *row_count > 1 {
-            table.modify((*start_row, 0), Span::row(*row_count as isize));
+    // Color the Provider column (column 0) in light blue for data rows only, and
+    // link it to the provider's usage dashboard when the terminal supports OSC 8
+    if let Some(provider_col) = col(TableColumn::Provider) {
+        for (start_row, url) in provider_links {
+            let mut color = if options.no_color { Color::empty() } else { Color::FG_BRIGHT_BLUE };
+            if let (true, Some(url)) = (options.hyperlinks, url) {
+                color = color | hyperlink(url);
+            }
+            table.modify((start_row, provider_col), color);
         }
     }
 
     // Apply colors to cells (using tabled's Color, not ANSI codes)
-    if !no_color {
-        use tabled::settings::object::Rows;
-
+    if !options.no_color {
         // Bold header row
         table.modify(Rows::first(), Color::BOLD);
 
-        // Color the Provider column (column 0) in light blue for data rows only
-        for (start_row, _) in &section_spans {
-            table.modify((*start_row, 0), Color::FG_BRIGHT_BLUE);
-        }
-
         // Apply cell-specific colors (usage and status columns)
         for (row, col, color) in cell_colors {
             table.modify((row, col), color);
@@ -164,405 +369,609 @@ pub fn format_table(data: &[ProviderData], no_color: bool) -> String {
     // Correct borders for spanned cells
     table.with(BorderCorrection::span());
 
-    table.to_string()
+    // Shrink the widest columns first, so short ones like Usage stay intact
+    if let Some(width) = options.width {
+        table.with(Width::truncate(width).priority(Priority::max(true)).suffix("…"));
+    }
+
+    let age = options.fetched_at.map(|t| describe_age(t, options));
+    let headline = match (summary, age) {
+        (Some(summary), Some(age)) => Some(format!("{} | {}", summary, capitalize_first(&age))),
+        (summary, age) => summary.or(age.map(|a| capitalize_first(&a))),
+    };
+    match headline {
+        Some(headline) => format!("{}\n{}", headline, table),
+        None => table.to_string(),
+    }
+}
+
+/// OSC 8 hyperlink around a cell's text
+/// Applied as a tabled Color so the escape sequences don't count towards the cell width
+fn hyperlink(url: &str) -> Color {
+    Color::new(format!("\x1b]8;;{}\x1b\\", url), "\x1b]8;;\x1b\\")
 }
 
-/// Returns a vector of (start_row, row_count) for each section
-fn add_provider_rows(
-    builder: &mut Builder,
-    data: &ProviderData,
-    no_color: bool,
-    start_row: usize,
-    cell_colors: &mut Vec<(usize, usize, Color)>,
-) -> Vec<(usize, usize)> {
-    match data {
-        ProviderData::Gemini(gemini) => {
-            add_gemini_rows(builder, gemini, no_color, start_row, cell_colors)
-        }
-        ProviderData::Codex(codex) => {
-            add_codex_rows(builder, codex, no_color, start_row, cell_colors);
-            vec![(start_row, 2)]
+/// Model families in display order
+const MODEL_FAMILIES: [&str; 6] = ["Claude", "GPT", "Gemini Pro", "Gemini Flash", "Other", "Unavailable"];
+
+/// Pivot provider sections into one section per model family
+/// Each row names its source (provider/account and window); a quota shared by
+/// several families (Copilot premium requests) is listed under each of them
+fn group_by_model(sections: Vec<Section>) -> Vec<Section> {
+    let mut groups: Vec<Section> = MODEL_FAMILIES
+        .iter()
+        .map(|family| Section::new(family, family.to_string()))
+        .collect();
+
+    for section in &sections {
+        let label = section_label(section);
+        for row in &section.rows {
+            for family in model_families(&section.name, row) {
+                let Some(group) = groups.iter_mut().find(|g| g.name == family) else {
+                    continue;
+                };
+                let mut row = row.clone();
+                row.model = match row.status {
+                    RowStatus::RateLimited | RowStatus::Failed => label.clone(),
+                    _ => format!("{} · {}", label, row.model),
+                };
+                group.rows.push(row);
+            }
         }
-        ProviderData::Copilot(copilot) => {
-            let row_count = add_copilot_rows(builder, copilot, no_color, start_row, cell_colors);
-            vec![(start_row, row_count)]
+    }
+
+    groups.retain(|g| !g.rows.is_empty());
+    groups
+}
+
+/// Model families a quota row can serve
+fn model_families(provider: &str, row: &Row) -> Vec<&'static str> {
+    if matches!(row.status, RowStatus::RateLimited | RowStatus::Failed) {
+        return vec!["Unavailable"];
+    }
+
+    match provider {
+        "claude" => vec!["Claude"],
+        "codex" => vec!["GPT"],
+        // Premium requests can be spent on any premium model; chat and
+        // completions use the base GPT models
+        "copilot" => match row.model.as_str() {
+            "Chat" | "Completions" => vec!["GPT"],
+            _ => vec!["Claude", "GPT", "Gemini Pro"],
+        },
+        _ => {
+            let model = row.model.to_lowercase();
+            if model.contains("claude") {
+                vec!["Claude"]
+            } else if model.contains("gpt") {
+                vec!["GPT"]
+            } else if model.contains("flash") {
+                vec!["Gemini Flash"]
+            } else if model.contains("pro") {
+                vec!["Gemini Pro"]
+            } else {
+                vec!["Other"]
+            }
         }
-        ProviderData::Claude(claude) => {
-            add_claude_rows(builder, claude, no_color, start_row, cell_colors);
-            vec![(start_row, 2)]
+    }
+}
+
+/// One-line headline: overall worst status, most-constrained window and soonest reset
+fn summary_line(sections: &[Section], options: &FormatOptions) -> String {
+    let rows = || sections.iter().flat_map(|s| s.rows.iter().map(move |r| (s, r)));
+
+    let worst = rows()
+        .map(|(_, r)| r.status)
+        .max_by_key(|status| status.severity())
+        .unwrap_or(RowStatus::Provider(ProviderStatus::Ok));
+    let mut status = format_status(worst, options.no_emoji);
+    if !options.no_color {
+        status = worst.color(&options.colors).colorize(&status);
+    }
+    let mut parts = vec![format!("Overall: {}", status)];
+
+    // First row wins on ties, so the premium row is preferred over its overage row
+    let mut most_used: Option<(&Section, &Row, f64)> = None;
+    for (section, row) in rows() {
+        if let Some(used) = row.used_percent {
+            if most_used.map_or(true, |(_, _, max)| used > max) {
+                most_used = Some((section, row, used));
+            }
         }
-        ProviderData::Failed { provider, .. } => {
-            add_failed_rows(builder, provider, no_color, start_row, cell_colors);
-            vec![(start_row, 1)]
+    }
+    if let Some((section, row, used)) = most_used {
+        parts.push(match options.show {
+            UsageView::Used => format!("Most used: {} {} ({:.0}%)", section_label(section), row.model, used),
+            UsageView::Remaining => format!(
+                "Least remaining: {} {} ({:.0}% left)",
+                section_label(section),
+                row.model,
+                100.0 - used
+            ),
+        });
+    }
+
+    let now = Utc::now();
+    let soonest = rows()
+        .filter_map(|(s, r)| r.resets_at.filter(|t| *t >= now).map(|t| (s, r, t)))
+        .min_by_key(|(_, _, t)| *t);
+    if let Some((section, row, _)) = soonest {
+        // Reuse the rendered cell so the headline matches the row exactly
+        // Copilot reports a plain reset date rather than a duration
+        let when = if row.reset.parse::<chrono::NaiveDate>().is_ok() {
+            format!("on {}", row.reset)
+        } else if options.absolute_times {
+            format!("at {}", row.reset)
+        } else {
+            options.locale.future(&row.reset)
+        };
+        parts.push(format!("Next reset: {} {} {}", section_label(section), row.model, when));
+    }
+
+    if !options.budgets.is_empty() {
+        let budgets: Vec<String> = options
+            .budgets
+            .iter()
+            .map(|budget| {
+                let text = budget.describe();
+                let color = if budget.used_percent >= 100.0 {
+                    &options.colors.error
+                } else if budget.used_percent >= 80.0 {
+                    &options.colors.warning
+                } else {
+                    return text;
+                };
+                if options.no_color { text } else { color.paint(&text) }
+            })
+            .collect();
+        parts.push(format!("Budget: {}", budgets.join(", ")));
+    }
+
+    if options.incomplete {
+        let text = "Incomplete: interrupted".to_string();
+        parts.push(if options.no_color { text } else { options.colors.warning.paint(&text) });
+    }
+
+    parts.join(" | ")
+}
+
+/// Single-line provider label, e.g. "Gemini (user@example.com)"
+fn section_label(section: &Section) -> String {
+    let mut lines = section.provider.lines();
+    let provider = lines.next().unwrap_or_default();
+    match lines.next() {
+        Some(account) => format!("{} ({})", provider, account),
+        None => provider.to_string(),
+    }
+}
+
+/// Status text, followed by the failure reason if any
+fn status_cell(row: &Row, options: &FormatOptions) -> String {
+    let status = format_status(row.status, options.no_emoji);
+    match &row.status_detail {
+        Some(detail) => format!("{}: {}", status, detail),
+        None => status,
+    }
+}
+
+/// Active warning/critical usage limits for a percentage row, e.g. "50/80"
+fn thresholds_cell(row: &Row, colors: &ColorScheme) -> String {
+    if row.bar_percent.is_none() {
+        return String::new();
+    }
+    format!(
+        "{}/{}",
+        format_threshold(colors.usage_warning),
+        format_threshold(colors.usage_critical)
+    )
+}
+
+/// Threshold percentage without a trailing ".0"
+fn format_threshold(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Width of the inline usage bar, in characters
+const BAR_WIDTH: usize = 6;
+
+/// Usage cell text, prefixed with an inline bar unless bars are disabled
+fn usage_cell(row: &Row, options: &FormatOptions) -> String {
+    match row.bar_percent {
+        Some(percent) if !options.no_bars => {
+            // The bar follows the displayed value
+            let fill = match options.show {
+                UsageView::Used => percent,
+                UsageView::Remaining => 100 - percent,
+            };
+            format!("{} {}", usage_bar(fill), row.usage)
         }
+        _ => row.usage.clone(),
     }
 }
 
-/// Returns a vector of (start_row, row_count) - one span per account
-fn add_gemini_rows(
-    builder: &mut Builder,
-    data: &GeminiData,
-    no_color: bool,
-    start_row: usize,
-    cell_colors: &mut Vec<(usize, usize, Color)>,
-) -> Vec<(usize, usize)> {
-    let mut spans: Vec<(usize, usize)> = Vec::new();
-    let mut current_row = start_row;
+/// Percentage cell text for the selected view: "63%" used or "37% left"
+fn format_percent(used_percent: i32, show: UsageView) -> String {
+    match show {
+        UsageView::Used => format!("{}%", used_percent),
+        UsageView::Remaining => format!("{}% left", 100 - used_percent),
+    }
+}
 
-    if data.accounts.is_empty() {
-        // No accounts - add placeholder row
-        builder.push_record([
-            "Gemini".to_string(),
-            "-".to_string(),
-            "-".to_string(),
-            "-".to_string(),
-            "✓ OK".to_string(),
-        ]);
-        if !no_color {
-            cell_colors.push((start_row, 4, Color::FG_GREEN));
+/// Render a percentage as a fixed-width bar (e.g. "▓▓▓▓░░")
+fn usage_bar(percent: i32) -> String {
+    let filled = (percent.clamp(0, 100) as usize * BAR_WIDTH + 50) / 100;
+    format!("{}{}", "▓".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+}
+
+/// Build the border theme for the selected style
+/// Rounded and ASCII styles draw a header line plus separators between sections
+pub(crate) fn build_theme(style: TableStyle, section_spans: &[(usize, usize)]) -> Theme {
+    let (mut theme, header_line, section_line) = match style {
+        TableStyle::Rounded => (
+            Theme::from_style(Style::rounded()),
+            HorizontalLine::full('═', '╪', '╞', '╡'),
+            Some(HorizontalLine::full('┄', '┼', '├', '┤')),
+        ),
+        TableStyle::Ascii => {
+            let mut theme = Theme::from_style(Style::ascii());
+            theme.remove_horizontal_lines();
+            (
+                theme,
+                HorizontalLine::full('=', '+', '+', '+'),
+                Some(HorizontalLine::full('-', '+', '+', '+')),
+            )
+        }
+        TableStyle::Compact => return Theme::from_style(Style::psql()),
+        TableStyle::Borderless => return Theme::from_style(Style::blank()),
+    };
+
+    theme.insert_horizontal_line(1, header_line);
+    if let Some(line) = section_line {
+        for (start_row, row_count) in section_spans.iter().take(section_spans.len().saturating_sub(1)) {
+            theme.insert_horizontal_line(start_row + row_count, line);
         }
-        return vec![(start_row, 1)];
+    }
+    theme
+}
+
+/// Order sections by their most significant row, and rows within each section
+fn sort_sections(sections: &mut [Section], key: SortKey, order: SortOrder) {
+    for section in sections.iter_mut() {
+        section.rows.sort_by(|a, b| match key {
+            SortKey::Usage => compare_usage(a.used_percent, b.used_percent, order),
+            SortKey::Reset => compare_resets(a.resets_at, b.resets_at, order),
+            SortKey::Name => Ordering::Equal,
+        });
     }
 
-    for account in &data.accounts {
-        let account_start = current_row;
-        let row_count =
-            add_gemini_account_rows(builder, account, no_color, current_row, cell_colors);
-        if row_count > 0 {
-            spans.push((account_start, row_count));
-            current_row += row_count;
+    sections.sort_by(|a, b| match key {
+        SortKey::Usage => compare_usage(a.max_used(), b.max_used(), order),
+        SortKey::Reset => compare_resets(a.soonest_reset(), b.soonest_reset(), order),
+        SortKey::Name => compare_names(&a.name, &b.name, order),
+    });
+}
+
+/// Returns one section per provider (one per account for Gemini)
+fn provider_sections(data: &ProviderData, options: &FormatOptions) -> Vec<Section> {
+    match data {
+        ProviderData::Gemini(gemini) => gemini_sections(gemini, options),
+        ProviderData::Codex(codex) => vec![codex_section(codex, options)],
+        ProviderData::Copilot(copilot) => vec![copilot_section(copilot, options)],
+        ProviderData::Claude(claude) => vec![claude_section(claude, options)],
+        ProviderData::Failed { provider, error, rate_limited } => {
+            vec![failed_section(provider, error, *rate_limited, options)]
         }
     }
+}
+
+/// Returns one section per account
+fn gemini_sections(data: &GeminiData, options: &FormatOptions) -> Vec<Section> {
+    if data.accounts.is_empty() {
+        // No accounts - add placeholder row
+        let mut section = Section::new("gemini", "Gemini".to_string());
+        section.rows.push(placeholder_row(&options.colors));
+        return vec![section];
+    }
 
-    spans
+    data.accounts
+        .iter()
+        .map(|account| match &account.error {
+            // Failed accounts keep their place, so the account list doesn't silently shrink
+            Some(error) => {
+                let mut section = Section::new("gemini", format!("Gemini\n{}", account.email));
+                section.rows.push(failed_row(error, false, options));
+                section
+            }
+            None => gemini_account_section(account, options),
+        })
+        .collect()
 }
 
-/// Returns the number of rows added for this account
-fn add_gemini_account_rows(
-    builder: &mut Builder,
-    account: &GeminiAccountData,
-    no_color: bool,
-    start_row: usize,
-    cell_colors: &mut Vec<(usize, usize, Color)>,
-) -> usize {
-    let provider_name = "Gemini".to_string();
+fn gemini_account_section(account: &GeminiAccountData, options: &FormatOptions) -> Section {
+    let mut section = Section::new("gemini", format!("Gemini\n{}", account.email));
 
-    let provider_cell = format!("{}\n{}", provider_name, account.email);
+    let models = if options.detailed { &account.all_models } else { &account.models };
 
-    if account.models.is_empty() {
+    if models.is_empty() {
         // No models - add placeholder row for this account
-        builder.push_record([
-            provider_cell,
-            "-".to_string(),
-            "-".to_string(),
-            "-".to_string(),
-            "✓ OK".to_string(),
-        ]);
-        if !no_color {
-            cell_colors.push((start_row, 4, Color::FG_GREEN));
-        }
-        return 1;
+        section.rows.push(placeholder_row(&options.colors));
+        return section;
     }
 
     // Add one row per model with per-model status
     // Invert usage to show % USED (like other providers) instead of % remaining
-    for (i, model) in account.models.iter().enumerate() {
-        let reset_str = model
+    for model in models {
+        let reset = model
             .reset_time
-            .map(|t| format_reset_time(t))
+            .map(|t| format_reset(t, options))
             .unwrap_or_else(|| "-".to_string());
-
         let used_percent = (100.0 - model.remaining_percent) as i32;
-        let usage_str = format!("{}%", used_percent);
-
-        // Per-model status
-        let row_status = get_row_status(used_percent);
-        let status_text = format_status(row_status);
-        let current_row = start_row + i;
-
-        if i == 0 {
-            builder.push_record([
-                provider_cell.clone(),
-                model.model.clone(),
-                usage_str,
-                reset_str,
-                status_text,
-            ]);
-        } else {
-            builder.push_record([
-                String::new(),
-                model.model.clone(),
-                usage_str,
-                reset_str,
-                status_text,
-            ]);
-        }
-
-        // Track colors for usage (column 2) and status (column 4)
-        if !no_color {
-            let usage_color = get_usage_color(used_percent);
-            let status_color = get_status_color(row_status);
-            cell_colors.push((current_row, 2, usage_color));
-            cell_colors.push((current_row, 4, status_color));
-        }
+        section.rows.push(Row::usage(
+            "gemini",
+            &model.model,
+            used_percent,
+            reset,
+            model.reset_time,
+            options,
+        ));
     }
 
-    account.models.len()
-}
-
-fn add_codex_rows(
-    builder: &mut Builder,
-    data: &CodexData,
-    no_color: bool,
-    start_row: usize,
-    cell_colors: &mut Vec<(usize, usize, Color)>,
-) {
-    let name = "Codex".to_string();
-
-    // Primary window with per-window status
-    let primary_percent = data.primary_window.used_percent;
-    let primary_usage = format!("{}%", primary_percent);
-    let primary_reset = format_seconds(data.primary_window.resets_in_seconds);
-    let primary_status = get_row_status(primary_percent);
-
-    builder.push_record([
-        name.clone(),
-        "Primary".to_string(),
-        primary_usage,
-        primary_reset,
-        format_status(primary_status),
-    ]);
-
-    if !no_color {
-        cell_colors.push((start_row, 2, get_usage_color(primary_percent)));
-        cell_colors.push((start_row, 4, get_status_color(primary_status)));
-    }
-
-    // Secondary window with per-window status
-    let secondary_percent = data.secondary_window.used_percent;
-    let secondary_usage = format!("{}%", secondary_percent);
-    let secondary_reset = format_seconds(data.secondary_window.resets_in_seconds);
-    let secondary_status = get_row_status(secondary_percent);
-
-    builder.push_record([
-        String::new(),
-        "Secondary".to_string(),
-        secondary_usage,
-        secondary_reset,
-        format_status(secondary_status),
-    ]);
-
-    if !no_color {
-        cell_colors.push((start_row + 1, 2, get_usage_color(secondary_percent)));
-        cell_colors.push((start_row + 1, 4, get_status_color(secondary_status)));
-    }
-}
-
-fn add_copilot_rows(
-    builder: &mut Builder,
-    data: &CopilotData,
-    no_color: bool,
-    start_row: usize,
-    cell_colors: &mut Vec<(usize, usize, Color)>,
-) -> usize {
-    let name = "Copilot".to_string();
-    let overage_used = (-data.premium_remaining).max(0);
-
-    // Calculate usage percentage (inverted from remaining to align with other providers)
-    // 100% remaining becomes 0% used, 0% remaining becomes 100% used
-    let used_percent = if data.premium_entitlement > 0 {
-        let remaining_fraction = data.premium_remaining as f64 / data.premium_entitlement as f64;
-        ((1.0 - remaining_fraction) * 100.0).clamp(0.0, 100.0) as i32
-    } else {
-        0
-    };
+    section
+}
+
+fn codex_section(data: &CodexData, options: &FormatOptions) -> Section {
+    let mut section = Section::new("codex", "Codex".to_string());
+
+    // Codex reports window lengths; older responses fall back to the usual 5h/weekly windows
+    for (label, window) in data.windows() {
+        let default_window = if label == "Primary" { FIVE_HOURS } else { SEVEN_DAYS };
+        let reset = format_reset(window.resets_at, options);
+        section.rows.push(
+            Row::usage("codex", label, window.used_percent, reset, Some(window.resets_at), options)
+                .with_window_seconds(window.window_seconds.unwrap_or(default_window)),
+        );
+    }
 
-    let usage_str = format!("{}%", used_percent);
-    let row_status = get_row_status(used_percent);
+    section
+}
 
-    builder.push_record([
-        name,
-        "Premium Requests".to_string(),
-        usage_str,
-        data.quota_reset_date.clone(),
-        format_status(row_status),
-    ]);
+fn copilot_section(data: &CopilotData, options: &FormatOptions) -> Section {
+    let colors = &options.colors;
+    let mut section = Section::new("copilot", "Copilot".to_string());
+    let overage_used = data.overage_requests();
+    let used_percent = data.premium_used_percent;
+    let resets_at = data.quota_reset_at();
 
-    if !no_color {
-        cell_colors.push((start_row, 2, get_usage_color(used_percent)));
-        cell_colors.push((start_row, 4, get_status_color(row_status)));
+    if data.premium_unlimited {
+        section.rows.push(unlimited_row("Premium Requests", colors));
+    } else {
+        let mut premium = Row::usage(
+            "copilot",
+            "Premium Requests",
+            used_percent,
+            data.quota_reset_date.clone(),
+            resets_at,
+            options,
+        )
+        .with_window_start(data.quota_period_start());
+        if options.counts {
+            premium.usage = format_count(data.premium_remaining, data.premium_entitlement);
+        }
+        section.rows.push(premium);
     }
 
     // Add overage row when premium remaining goes negative
-    if overage_used > 0 {
-        let overage_str = format!("{} reqs", overage_used);
-        builder.push_record([
-            String::new(),
-            "Over Entitlement".to_string(),
-            overage_str,
-            "".to_string(),
-            "".to_string(),
-        ]);
-
-        if !no_color {
-            cell_colors.push((start_row + 1, 2, Color::FG_RED));
+    // Billed overage is a warning; overage that isn't permitted means requests are blocked
+    if overage_used > 0 && !data.premium_unlimited {
+        let overage_status = if data.overage_permitted {
+            ProviderStatus::Warning
+        } else {
+            ProviderStatus::Error
+        };
+        section.rows.push(Row {
+            model: "Over Entitlement".to_string(),
+            usage: match options.cost.copilot_overage(data) {
+                Some(usd) => format!("{} reqs (${:.2})", overage_used, usd),
+                None => format!("{} reqs", overage_used),
+            },
+            reset: String::new(),
+            status: RowStatus::Provider(overage_status),
+            status_detail: None,
+            usage_color: Some(colors.error.table_color()),
+            status_color: Some(get_status_color(overage_status, colors)),
+            bar_percent: None,
+            // Keep the overage row next to the premium row when sorting
+            used_percent: Some(used_percent as f64),
+            resets_at,
+            pace: None,
+            overage: None,
+            cost: None,
+        });
+    }
+
+    for (label, quota) in [("Chat", &data.chat), ("Completions", &data.completions)] {
+        if let Some(quota) = quota {
+            section
+                .rows
+                .push(copilot_quota_row(label, quota, data, options));
         }
-        return 2;
-    }
-
-    1
-}
-
-fn add_claude_rows(
-    builder: &mut Builder,
-    data: &ClaudeData,
-    no_color: bool,
-    start_row: usize,
-    cell_colors: &mut Vec<(usize, usize, Color)>,
-) {
-    let name = "Claude".to_string();
-
-    // 5-hour window with per-window status
-    let five_h_percent = data.five_hour.utilization as i32;
-    let five_h_usage = format!("{}%", five_h_percent);
-    let five_h_reset = data
-        .five_hour
-        .resets_at
-        .map(|t| format_reset_time(t))
-        .unwrap_or_else(|| "-".to_string());
-    let five_h_status = get_row_status(five_h_percent);
-
-    builder.push_record([
-        name.clone(),
-        "5h Window".to_string(),
-        five_h_usage,
-        five_h_reset,
-        format_status(five_h_status),
-    ]);
-
-    if !no_color {
-        cell_colors.push((start_row, 2, get_usage_color(five_h_percent)));
-        cell_colors.push((start_row, 4, get_status_color(five_h_status)));
-    }
-
-    // 7-day window with per-window status
-    let seven_d_percent = data.seven_day.utilization as i32;
-    let seven_d_usage = format!("{}%", seven_d_percent);
-    let seven_d_reset = data
-        .seven_day
-        .resets_at
-        .map(|t| format_reset_time(t))
-        .unwrap_or_else(|| "-".to_string());
-    let seven_d_status = get_row_status(seven_d_percent);
-
-    builder.push_record([
-        String::new(),
-        "7d Window".to_string(),
-        seven_d_usage,
-        seven_d_reset,
-        format_status(seven_d_status),
-    ]);
-
-    if !no_color {
-        cell_colors.push((start_row + 1, 2, get_usage_color(seven_d_percent)));
-        cell_colors.push((start_row + 1, 4, get_status_color(seven_d_status)));
-    }
-}
-
-fn add_failed_rows(
-    builder: &mut Builder,
-    provider: &str,
-    no_color: bool,
-    start_row: usize,
-    cell_colors: &mut Vec<(usize, usize, Color)>,
-) {
-    // Capitalize provider name for display
-    let display_name = capitalize_first(provider);
+    }
 
-    builder.push_record([
-        display_name,
-        "-".to_string(),
-        "?".to_string(),
-        "-".to_string(),
-        "✗ FAILED".to_string(),
-    ]);
+    section
+}
 
-    if !no_color {
-        cell_colors.push((start_row, 2, Color::FG_BRIGHT_BLACK)); // Gray for unknown usage
-        cell_colors.push((start_row, 4, Color::FG_RED));
+/// Row for a secondary Copilot quota; unlimited quotas are always OK
+fn copilot_quota_row(label: &str, quota: &CopilotQuota, data: &CopilotData, options: &FormatOptions) -> Row {
+    match quota.used_percent {
+        Some(percent) => {
+            let mut row = Row::usage(
+                "copilot",
+                label,
+                percent,
+                data.quota_reset_date.clone(),
+                data.quota_reset_at(),
+                options,
+            )
+            .with_window_start(data.quota_period_start());
+            if options.counts {
+                row.usage = format_count(quota.remaining, quota.entitlement);
+            }
+            row
+        }
+        None => unlimited_row(label, &options.colors),
     }
 }
 
-/// Capitalize the first letter of a string
-fn capitalize_first(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+/// Row for an uncapped Copilot quota, always OK
+fn unlimited_row(label: &str, colors: &ColorScheme) -> Row {
+    Row {
+        model: label.to_string(),
+        usage: "unlimited".to_string(),
+        reset: "-".to_string(),
+        status: RowStatus::Provider(ProviderStatus::Ok),
+        status_detail: None,
+        usage_color: Some(colors.ok.table_color()),
+        status_color: Some(get_status_color(ProviderStatus::Ok, colors)),
+        bar_percent: None,
+        used_percent: None,
+        resets_at: None,
+        pace: None,
+        overage: None,
+        cost: None,
     }
 }
 
-fn format_reset_time(dt: chrono::DateTime<chrono::Utc>) -> String {
-    let now = chrono::Utc::now();
-    let duration = dt.signed_duration_since(now);
+/// The per-model 7-day windows are optional
+fn claude_section(data: &ClaudeData, options: &FormatOptions) -> Section {
+    let mut section = Section::new("claude", "Claude".to_string());
+    let mut windows: Vec<(&str, &WindowUsage, i64)> = vec![
+        ("5h Window", &data.five_hour, FIVE_HOURS),
+        ("7d Window", &data.seven_day, SEVEN_DAYS),
+    ];
+
+    if !options.hide_model_windows {
+        if let Some(sonnet) = &data.seven_day_sonnet {
+            windows.push(("7d Sonnet", sonnet, SEVEN_DAYS));
+        }
+        if let Some(opus) = &data.seven_day_opus {
+            windows.push(("7d Opus", opus, SEVEN_DAYS));
+        }
+    }
 
-    if duration.num_hours() > 24 {
-        format!("{}d", duration.num_days())
-    } else if duration.num_hours() > 0 {
-        format!("{}h {}m", duration.num_hours(), duration.num_minutes() % 60)
-    } else {
-        format!("{}m", duration.num_minutes())
+    // One row per window with per-window status
+    for (label, window, length) in windows {
+        let reset = window
+            .resets_at
+            .map(|t| format_reset(t, options))
+            .unwrap_or_else(|| "-".to_string());
+        section.rows.push(
+            Row::usage("claude", label, window.utilization as i32, reset, window.resets_at, options)
+                .with_window_seconds(length),
+        );
     }
+
+    section
+}
+
+fn failed_section(provider: &str, error: &str, rate_limited: bool, options: &FormatOptions) -> Section {
+    // Capitalize provider name for display
+    let mut section = Section::new(provider, capitalize_first(provider));
+    section.rows.push(failed_row(error, rate_limited, options));
+    section
 }
 
-fn format_seconds(seconds: i64) -> String {
-    if seconds > 86400 {
-        format!("{}d", seconds / 86400)
-    } else if seconds > 3600 {
-        format!("{}h {}m", seconds / 3600, (seconds % 3600) / 60)
+/// Row for a provider or account whose query failed
+fn failed_row(error: &str, rate_limited: bool, options: &FormatOptions) -> Row {
+    // Rate limits get their own status, with the wait ("retry in 45s") as the detail
+    let (status, detail) = if rate_limited {
+        let wait = error.trim_start_matches("rate limited").trim_start_matches(", ");
+        (RowStatus::RateLimited, (!wait.is_empty()).then(|| wait.to_string()))
     } else {
-        format!("{}m", seconds / 60)
+        (RowStatus::Failed, Some(failure_reason(error)))
+    };
+
+    Row {
+        model: "-".to_string(),
+        usage: "?".to_string(),
+        reset: "-".to_string(),
+        status,
+        status_detail: detail,
+        usage_color: Some(Color::FG_BRIGHT_BLACK), // Gray for unknown usage
+        status_color: Some(status.color(&options.colors)),
+        bar_percent: None,
+        used_percent: None,
+        resets_at: None,
+        pace: None,
+        overage: None,
+        cost: None,
     }
 }
 
-fn format_status(status: ProviderStatus) -> String {
-    // Return plain text with icons - colors are applied via tabled's Color settings
-    match status {
-        ProviderStatus::Ok => "✓ OK".to_string(),
-        ProviderStatus::Warning => "⚠️ WARNING".to_string(),
-        ProviderStatus::Error => "✗ ERROR".to_string(),
-    }
+/// Remaining count for `--counts`, e.g. "37 of 300 left"
+fn format_count(remaining: i64, entitlement: i64) -> String {
+    format!("{} of {} left", remaining.max(0), entitlement)
 }
 
-/// Get tabled Color for status
-fn get_status_color(status: ProviderStatus) -> Color {
-    match status {
-        ProviderStatus::Ok => Color::FG_GREEN,
-        ProviderStatus::Warning => Color::FG_YELLOW,
-        ProviderStatus::Error => Color::FG_RED,
+/// Maximum length of a failure reason shown in the Status column
+const MAX_REASON_LEN: usize = 40;
+
+/// First part of an error message, without any JSON response body, truncated to fit the table
+fn failure_reason(error: &str) -> String {
+    let summary = error.split('{').next().unwrap_or(error).trim().trim_end_matches(':');
+    if summary.chars().count() > MAX_REASON_LEN {
+        let truncated: String = summary.chars().take(MAX_REASON_LEN - 1).collect();
+        format!("{}…", truncated.trim_end())
+    } else {
+        summary.to_string()
     }
 }
 
-/// Get tabled Color for usage percentages (for utilization/used percentages)
-/// Lower usage = better (green), higher = warning (yellow/red)
-fn get_usage_color(percent: i32) -> Color {
-    if percent < 50 {
-        Color::FG_GREEN
-    } else if percent < 80 {
-        Color::FG_YELLOW
-    } else {
-        Color::FG_RED
+/// Placeholder row for a provider or account without any models
+fn placeholder_row(colors: &ColorScheme) -> Row {
+    Row {
+        model: "-".to_string(),
+        usage: "-".to_string(),
+        reset: "-".to_string(),
+        status: RowStatus::Provider(ProviderStatus::Ok),
+        status_detail: None,
+        usage_color: None,
+        status_color: Some(colors.ok.table_color()),
+        bar_percent: None,
+        used_percent: None,
+        resets_at: None,
+        pace: None,
+        overage: None,
+        cost: None,
     }
 }
 
-/// Get status based on usage percentage (for per-row status)
-/// Lower usage = OK, higher = warning
-fn get_row_status(used_percent: i32) -> ProviderStatus {
-    if used_percent >= 100 {
-        ProviderStatus::Error
-    } else if used_percent >= 80 {
-        ProviderStatus::Warning
+fn format_status(status: RowStatus, no_emoji: bool) -> String {
+    // Return plain text with icons - colors are applied via tabled's Color settings
+    let (icon, label) = match status {
+        RowStatus::Provider(ProviderStatus::Ok) => ("✓", "OK"),
+        RowStatus::Provider(ProviderStatus::Warning) => ("⚠️", "WARNING"),
+        RowStatus::Provider(ProviderStatus::Error) => ("✗", "ERROR"),
+        RowStatus::RateLimited => ("⏳", "RATE LIMITED"),
+        RowStatus::Failed => ("✗", "FAILED"),
+    };
+    if no_emoji {
+        label.to_string()
     } else {
-        ProviderStatus::Ok
+        format!("{} {}", icon, label)
     }
 }
+
+/// Get tabled Color for status
+fn get_status_color(status: ProviderStatus, colors: &ColorScheme) -> Color {
+    colors.status_color(status).table_color()
+}
+
+/// Get tabled Color for usage percentages (for utilization/used percentages)
+/// Lower usage = better (ok color), higher = warning/error colors
+fn get_usage_color(percent: i32, colors: &ColorScheme) -> Color {
+    colors.usage_color(percent as f64).table_color()
+}
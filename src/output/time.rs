@@ -0,0 +1,247 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::Deserialize;
+
+use crate::output::FormatOptions;
+
+/// Default strftime pattern for absolute timestamps
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// How relative durations are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationStyle {
+    /// "2h 5m", "3d"
+    #[default]
+    Compact,
+    /// "2 hours 5 minutes", "3 days"
+    Long,
+    /// "02:05", "3d 02:05"
+    Clock,
+}
+
+/// Language used for humanized durations
+/// Compact and clock styles keep their d/h/m abbreviations in every language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// "in 2 days", "2 days ago"
+    #[default]
+    En,
+    /// "dans 2 jours", "il y a 2 jours"
+    Fr,
+    /// "en 2 días", "hace 2 días"
+    Es,
+    /// "em 2 dias", "há 2 dias"
+    Pt,
+}
+
+impl Locale {
+    /// Singular and plural unit names for days, hours and minutes
+    fn units(self) -> [(&'static str, &'static str); 3] {
+        match self {
+            Locale::En => [("day", "days"), ("hour", "hours"), ("minute", "minutes")],
+            Locale::Fr => [("jour", "jours"), ("heure", "heures"), ("minute", "minutes")],
+            Locale::Es => [("día", "días"), ("hora", "horas"), ("minuto", "minutos")],
+            Locale::Pt => [("dia", "dias"), ("hora", "horas"), ("minuto", "minutos")],
+        }
+    }
+
+    /// A point in the future: "in 2 days"
+    pub fn future(self, duration: &str) -> String {
+        match self {
+            Locale::En => format!("in {}", duration),
+            Locale::Fr => format!("dans {}", duration),
+            Locale::Es => format!("en {}", duration),
+            Locale::Pt => format!("em {}", duration),
+        }
+    }
+
+    /// A point in the past: "2 days ago"
+    pub fn past(self, duration: &str) -> String {
+        match self {
+            Locale::En => format!("{} ago", duration),
+            Locale::Fr => format!("il y a {}", duration),
+            Locale::Es => format!("hace {}", duration),
+            Locale::Pt => format!("há {}", duration),
+        }
+    }
+
+    /// A reset whose time has already passed, usually because the local clock runs ahead
+    pub fn resetting_now(self) -> &'static str {
+        match self {
+            Locale::En => "resetting now",
+            Locale::Fr => "réinitialisation en cours",
+            Locale::Es => "reiniciando ahora",
+            Locale::Pt => "reiniciando agora",
+        }
+    }
+
+    /// The present moment, for prose: "resets now"
+    pub fn now(self) -> &'static str {
+        match self {
+            Locale::En => "now",
+            Locale::Fr => "maintenant",
+            Locale::Es => "ahora",
+            Locale::Pt => "agora",
+        }
+    }
+
+    /// Age of data: "as of 2 days ago"
+    fn as_of(self, duration: Option<&str>) -> String {
+        match (self, duration) {
+            (Locale::En, Some(duration)) => format!("as of {}", self.past(duration)),
+            (Locale::En, None) => "as of just now".to_string(),
+            (_, Some(duration)) => self.past(duration),
+            (Locale::Fr, None) => "à l'instant".to_string(),
+            (Locale::Es, None) => "justo ahora".to_string(),
+            (Locale::Pt, None) => "agora mesmo".to_string(),
+        }
+    }
+}
+
+/// Check that a strftime pattern only contains valid specifiers
+pub fn is_valid_time_format(pattern: &str) -> bool {
+    !StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error))
+}
+
+/// Format a duration in the given style, in English
+pub fn format_duration(duration: Duration, style: DurationStyle) -> String {
+    humanize_duration(duration, style, Locale::En)
+}
+
+/// Format a duration in the given style and language: "2h 5m", "2 heures 5 minutes"
+pub fn humanize_duration(duration: Duration, style: DurationStyle, locale: Locale) -> String {
+    let [day, hour, minute] = locale.units();
+    let days = duration.num_days();
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() % 60;
+
+    match style {
+        DurationStyle::Compact => {
+            if hours > 24 {
+                format!("{}d", days)
+            } else if hours > 0 {
+                format!("{}h {}m", hours, minutes)
+            } else {
+                format!("{}m", duration.num_minutes())
+            }
+        }
+        DurationStyle::Long => {
+            if hours > 24 {
+                plural(days, day)
+            } else if hours > 0 && minutes > 0 {
+                format!("{} {}", plural(hours, hour), plural(minutes, minute))
+            } else if hours > 0 {
+                plural(hours, hour)
+            } else {
+                plural(duration.num_minutes(), minute)
+            }
+        }
+        DurationStyle::Clock => {
+            if days > 0 {
+                format!("{}d {:02}:{:02}", days, hours % 24, minutes)
+            } else {
+                format!("{:02}:{:02}", hours, minutes)
+            }
+        }
+    }
+}
+
+fn plural(count: i64, (singular, plural): (&str, &str)) -> String {
+    if count == 1 {
+        format!("{} {}", count, singular)
+    } else {
+        format!("{} {}", count, plural)
+    }
+}
+
+/// Format a signed duration relative to now: "in 2 days" / "2 days ago", "dans 2 jours" / "il y a 2 jours"
+pub fn humanize_relative(duration: Duration, style: DurationStyle, locale: Locale) -> String {
+    if duration < Duration::zero() {
+        locale.past(&humanize_duration(-duration, style, locale))
+    } else {
+        locale.future(&humanize_duration(duration, style, locale))
+    }
+}
+
+/// Format an instant as an absolute timestamp using the configured pattern
+/// Uses the local timezone unless UTC was requested
+pub fn format_timestamp(dt: DateTime<Utc>, options: &FormatOptions) -> String {
+    let pattern = options.time_format.as_deref().unwrap_or(DEFAULT_TIME_FORMAT);
+    if options.utc {
+        format!("{} UTC", dt.format(pattern))
+    } else {
+        dt.with_timezone(&Local).format(pattern).to_string()
+    }
+}
+
+/// Format a reset instant as either a relative duration or an absolute timestamp
+/// A reset already in the past (clock skew, stale data) reads "resetting now" rather than "-3m"
+pub fn format_reset(dt: DateTime<Utc>, options: &FormatOptions) -> String {
+    format_reset_in(dt.signed_duration_since(Utc::now()), options)
+}
+
+fn format_reset_in(remaining: Duration, options: &FormatOptions) -> String {
+    if options.absolute_times {
+        format_timestamp(Utc::now() + remaining, options)
+    } else if remaining < Duration::zero() {
+        options.locale.resetting_now().to_string()
+    } else {
+        humanize_duration(remaining, options.duration_style, options.locale)
+    }
+}
+
+/// Describe when a window resets for prose output: "in 2h 5m" or "at 2026-02-12 14:30"
+pub fn describe_reset(dt: DateTime<Utc>, options: &FormatOptions) -> String {
+    describe_reset_in(dt.signed_duration_since(Utc::now()), options)
+}
+
+fn describe_reset_in(remaining: Duration, options: &FormatOptions) -> String {
+    if options.absolute_times {
+        format!("at {}", format_timestamp(Utc::now() + remaining, options))
+    } else if remaining < Duration::zero() {
+        options.locale.now().to_string()
+    } else {
+        options
+            .locale
+            .future(&humanize_duration(remaining, options.duration_style, options.locale))
+    }
+}
+
+/// Capitalize the first letter of a string
+pub(crate) fn capitalize_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+    }
+}
+
+/// Describe how old cached data is: "as of 2m ago"
+pub fn describe_age(fetched_at: DateTime<Utc>, options: &FormatOptions) -> String {
+    let age = Utc::now().signed_duration_since(fetched_at);
+    if age.num_minutes() < 1 {
+        options.locale.as_of(None)
+    } else {
+        let duration = humanize_duration(age, options.duration_style, options.locale);
+        options.locale.as_of(Some(&duration))
+    }
+}
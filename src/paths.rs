@@ -0,0 +1,57 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::PathBuf;
+
+/// Base directory for user data: `$XDG_DATA_HOME`, or ~/.local/share
+/// XDG locations are used on every platform, as OpenCode does
+pub fn data_home() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", &[".local", "share"])
+}
+
+/// Base directory for user configuration: `$XDG_CONFIG_HOME`, or ~/.config
+pub fn config_home() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", &[".config"])
+}
+
+/// Base directory for cached data: `$XDG_CACHE_HOME`, or ~/.cache
+pub fn cache_home() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", &[".cache"])
+}
+
+/// OpenCode's data directory: `$OPENCODE_DATA_DIR`, or opencode/ under the data home
+pub fn opencode_data_dir() -> Option<PathBuf> {
+    opencode_data_dir_override().or_else(|| data_home().map(|dir| dir.join("opencode")))
+}
+
+/// `$OPENCODE_DATA_DIR`, when set
+pub fn opencode_data_dir_override() -> Option<PathBuf> {
+    std::env::var_os("OPENCODE_DATA_DIR")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// An XDG base directory from the environment, or its default under the home directory
+/// Relative values are ignored, as the XDG spec requires
+fn xdg_dir(var: &str, default: &[&str]) -> Option<PathBuf> {
+    let from_env = std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute());
+    from_env.or_else(|| {
+        dirs::home_dir().map(|home| default.iter().fold(home, |path, part| path.join(part)))
+    })
+}
@@ -0,0 +1,35 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+pub use crate::auth::{AuthManager, AuthSource, EnvAuth, MemoryAuth};
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+pub use crate::auth::{KeyringAuth, KEYRING_SERVICE};
+pub use crate::cli::OutputFormat;
+pub use crate::config::Config;
+pub use crate::error::{ErrorRecord, QuotaError};
+pub use crate::output::json::{parse_json, JsonReport};
+pub use crate::output::{format_output, FormatOptions};
+pub use crate::providers::claude::ClaudeProvider;
+pub use crate::providers::codex::CodexProvider;
+pub use crate::providers::copilot::CopilotProvider;
+pub use crate::providers::events::{EventHook, FetchEvent, RequestTiming, TimingRecorder};
+pub use crate::providers::gemini::GeminiProvider;
+pub use crate::providers::{
+    fetch_all_stream, ClaudeData, CodexData, CopilotData, CostModel, FetchOptions, GeminiData, Overage, Provider, ProviderData,
+    ProviderLinks, ProviderResult, ProviderStatus, Thresholds, UsageWindow, WindowKind,
+};
+pub use crate::run::{fetch_all, RunOptions, RunOutcome};
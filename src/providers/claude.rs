@@ -19,93 +19,241 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::Deserialize;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::auth::AuthManager;
+use crate::auth::{run_blocking, AuthManager, AuthSource, OAuthToken};
 use crate::error::{QuotaError, Result};
-use crate::providers::{ClaudeData, Provider, ProviderData, WindowUsage};
+use crate::providers::events::EventHook;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::{decode_json, error_body, send, send_with_retry, ClaudeData, Provider, ProviderData, ProviderLinks, WindowUsage};
+
+/// OAuth client used by OpenCode's Anthropic login
+const ANTHROPIC_CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
+
+const ANTHROPIC_API_URL: &str = "https://api.anthropic.com";
+const ANTHROPIC_TOKEN_URL: &str = "https://console.anthropic.com/v1/oauth/token";
+
+pub const LINKS: ProviderLinks = ProviderLinks {
+    dashboard: "https://claude.ai/settings/usage",
+    login: "opencode auth login anthropic",
+    pricing: "https://claude.com/pricing",
+};
 
 pub struct ClaudeProvider {
-    auth_manager: AuthManager,
+    /// Where credentials are read from and refreshed tokens are kept
+    auth: Arc<dyn AuthSource>,
+    /// Print response bodies that fail to decode
+    dump_body_on_error: bool,
+    /// Builds the provider's HTTP requests
+    client: Client,
+    /// Sends the provider's HTTP requests
+    transport: Arc<dyn HttpTransport>,
+    /// Notified of each HTTP request, response and retry
+    events: Option<EventHook>,
+    /// Base URL of the usage API, without a trailing slash
+    base_url: String,
+    /// OAuth token endpoint used to refresh expired access tokens
+    token_url: String,
 }
 
 impl ClaudeProvider {
     pub fn new() -> Self {
+        // Shared by request building and the transport; each client loads its own TLS roots
+        let client = Client::new();
         Self {
-            auth_manager: AuthManager::new(),
+            auth: Arc::new(AuthManager::new()),
+            dump_body_on_error: false,
+            client: client.clone(),
+            transport: Arc::new(ReqwestTransport::new(client)),
+            events: None,
+            base_url: ANTHROPIC_API_URL.to_string(),
+            token_url: ANTHROPIC_TOKEN_URL.to_string(),
+        }
+    }
+
+    /// Read credentials from this source instead of OpenCode's files
+    pub fn with_auth(mut self, auth: Arc<dyn AuthSource>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Send requests with this client, e.g. one configured with a proxy or custom TLS roots
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.transport = Arc::new(ReqwestTransport::new(client.clone()));
+        self.client = client;
+        self
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Report each HTTP request, response and retry to this hook
+    pub fn with_event_hook(mut self, hook: EventHook) -> Self {
+        self.events = Some(hook);
+        self
+    }
+
+    /// Print the raw response body to stderr when it fails to decode
+    pub fn with_dump_body_on_error(mut self, dump_body_on_error: bool) -> Self {
+        self.dump_body_on_error = dump_body_on_error;
+        self
+    }
+
+    /// Send API requests to this base URL instead of api.anthropic.com, e.g. an API gateway or a mock server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Refresh tokens at this URL instead of console.anthropic.com
+    pub fn with_token_url(mut self, token_url: impl Into<String>) -> Self {
+        self.token_url = token_url.into();
+        self
+    }
+
+    /// Exchange the refresh token for a new access token and store it in auth.json
+    async fn refresh_token(&self, token: &OAuthToken, timeout: Duration, verbose: bool) -> Result<OAuthToken> {
+        let refresh = token
+            .refresh
+            .as_deref()
+            .ok_or_else(|| QuotaError::TokenRefreshError("Claude token expired and no refresh token is stored".to_string()))?;
+
+        if verbose {
+            eprintln!("[claude] POST {}", self.token_url);
+        }
+
+        let request = self.client
+            .post(&self.token_url)
+            .json(&serde_json::json!({
+                "grant_type": "refresh_token",
+                "refresh_token": refresh,
+                "client_id": ANTHROPIC_CLIENT_ID,
+            }))
+            .timeout(timeout);
+        let response = send("claude", self.transport.as_ref(), self.events.as_ref(), request).await?;
+
+        let status = response.status();
+        if verbose {
+            eprintln!("[claude] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
+        }
+
+        if !status.is_success() {
+            let error_text = error_body("claude", response).await;
+            return Err(QuotaError::TokenRefreshError(format!(
+                "Anthropic OAuth refresh failed ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let refreshed: ClaudeTokenResponse = decode_json("claude", response, self.dump_body_on_error).await?;
+        let token = OAuthToken {
+            access: refreshed.access_token,
+            // Anthropic rotates refresh tokens; keep the old one if none came back
+            refresh: refreshed.refresh_token.or_else(|| token.refresh.clone()),
+            expires: Some(Utc::now().timestamp_millis() + refreshed.expires_in * 1000),
+            ..token.clone()
+        };
+
+        // The old refresh token may already be revoked, so failing to save is worth a warning
+        let (source, saved) = (Arc::clone(&self.auth), token.clone());
+        if let Err(e) = run_blocking(move || source.save_token("anthropic", &saved)).await {
+            eprintln!("Warning: could not save refreshed Claude token to auth.json: {}", e);
+        }
+
+        Ok(token)
+    }
+
+    async fn request_usage(&self, access: &str, timeout: Duration, verbose: bool) -> Result<reqwest::Response> {
+        let url = format!("{}/api/oauth/usage", self.base_url);
+        if verbose {
+            eprintln!("[claude] GET {}", url);
+        }
+
+        let request = self.client
+            .get(url)
+            .header("Authorization", format!("Bearer {}", access))
+            .header("anthropic-beta", "oauth-2025-04-20")
+            .timeout(timeout);
+        let response = send_with_retry("claude", self.transport.as_ref(), self.events.as_ref(), request, timeout, verbose).await?;
+
+        if verbose {
+            let status = response.status();
+            eprintln!("[claude] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
         }
+
+        Ok(response)
     }
 }
 
-#[async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Provider for ClaudeProvider {
     fn name(&self) -> &'static str {
         "claude"
     }
 
+    fn links(&self) -> ProviderLinks {
+        LINKS
+    }
+
     fn is_configured(&self) -> bool {
-        self.auth_manager
+        self.auth
             .is_provider_configured("claude")
             .unwrap_or(false)
     }
 
     async fn fetch(&self, timeout: Duration, verbose: bool) -> Result<ProviderData> {
-        let auth = self
-            .auth_manager
-            .read_opencode_auth()?
+        let source = Arc::clone(&self.auth);
+        let auth = run_blocking(move || source.opencode_auth())
+            .await?
             .ok_or_else(|| QuotaError::ProviderNotConfigured("claude".to_string()))?;
 
-        let anthropic_auth = auth
+        let mut anthropic_auth = auth
             .anthropic
             .ok_or_else(|| QuotaError::ProviderNotConfigured("claude (no token)".to_string()))?;
 
-        let url = "https://api.anthropic.com/api/oauth/usage";
-        if verbose {
-            eprintln!("[claude] GET {}", url);
+        // Refresh up front when the stored token has expired, like OpenCode does;
+        // without a refresh token the user has to log in again
+        let mut refreshed = false;
+        if anthropic_auth.is_expired() {
+            if !anthropic_auth.can_refresh() {
+                let expired_at = anthropic_auth.expires_at().unwrap_or_else(Utc::now);
+                return Err(QuotaError::token_expired("claude", expired_at));
+            }
+            anthropic_auth = self.refresh_token(&anthropic_auth, timeout, verbose).await?;
+            refreshed = true;
         }
 
-        let client = Client::new();
-        let response = client
-            .get(url)
-            .header("Authorization", format!("Bearer {}", anthropic_auth.access))
-            .header("anthropic-beta", "oauth-2025-04-20")
-            .timeout(timeout)
-            .send()
-            .await?;
+        let mut response = self.request_usage(&anthropic_auth.access, timeout, verbose).await?;
 
-        let status = response.status();
-        if verbose {
-            eprintln!("[claude] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
+        // A token revoked before its expiry time gets one refresh and retry
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED && !refreshed && anthropic_auth.can_refresh() {
+            anthropic_auth = self.refresh_token(&anthropic_auth, timeout, verbose).await?;
+            response = self.request_usage(&anthropic_auth.access, timeout, verbose).await?;
         }
 
+        let status = response.status();
         if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(QuotaError::ApiError(format!(
-                "Claude API error ({}): {}",
-                status, error_text
-            )));
+            let error_text = error_body("claude", response).await;
+            return Err(QuotaError::HttpError {
+                status: status.as_u16(),
+                message: format!("Claude API error ({}): {}", status, error_text),
+            });
         }
 
-        let usage: ClaudeUsageResponse = response.json().await?;
+        let usage: ClaudeUsageResponse = decode_json("claude", response, self.dump_body_on_error).await?;
 
         let data = ClaudeData {
-            five_hour: WindowUsage {
-                utilization: usage.five_hour.utilization,
-                resets_at: usage.five_hour.resets_at,
-            },
-            seven_day: WindowUsage {
-                utilization: usage.seven_day.utilization,
-                resets_at: usage.seven_day.resets_at,
-            },
-            seven_day_sonnet: usage.seven_day_sonnet.map(|w| WindowUsage {
-                utilization: w.utilization,
-                resets_at: w.resets_at,
-            }),
-            seven_day_opus: usage.seven_day_opus.map(|w| WindowUsage {
-                utilization: w.utilization,
-                resets_at: w.resets_at,
-            }),
+            five_hour: WindowUsage::new(usage.five_hour.utilization, usage.five_hour.resets_at),
+            seven_day: WindowUsage::new(usage.seven_day.utilization, usage.seven_day.resets_at),
+            seven_day_sonnet: usage.seven_day_sonnet.map(|w| WindowUsage::new(w.utilization, w.resets_at)),
+            seven_day_opus: usage.seven_day_opus.map(|w| WindowUsage::new(w.utilization, w.resets_at)),
             extra_usage_enabled: usage.extra_usage.is_enabled,
+            extra_usage_spent_usd: usage.extra_usage.used_credits.map(|cents| cents / 100.0),
         };
 
         Ok(ProviderData::Claude(data))
@@ -132,6 +280,13 @@ struct ClaudeUsageResponse {
     extra_usage: ClaudeExtraUsage,
 }
 
+#[derive(Debug, Deserialize)]
+struct ClaudeTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
 #[derive(Debug, Deserialize)]
 struct ClaudeWindow {
     utilization: f64,
@@ -143,4 +298,114 @@ struct ClaudeWindow {
 struct ClaudeExtraUsage {
     #[serde(rename = "is_enabled")]
     is_enabled: bool,
+    /// Spent this month, in cents
+    #[serde(rename = "used_credits", default)]
+    used_credits: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{MemoryAuth, OpenCodeAuth};
+    use crate::providers::transport::FakeTransport;
+
+    const USAGE_URL: &str = "https://api.anthropic.com/api/oauth/usage";
+
+    const USAGE: &str = r#"{
+        "five_hour": {"utilization": 23.0, "resets_at": "2026-10-16T18:00:00Z"},
+        "seven_day": {"utilization": 85.0, "resets_at": "2026-10-20T00:00:00Z"},
+        "seven_day_sonnet": null,
+        "seven_day_opus": {"utilization": 2.0, "resets_at": null},
+        "extra_usage": {"is_enabled": true, "used_credits": 1250}
+    }"#;
+
+    fn provider(transport: &Arc<FakeTransport>) -> ClaudeProvider {
+        let token = OAuthToken {
+            token_type: "oauth".to_string(),
+            access: "claude-access".to_string(),
+            refresh: Some("claude-refresh".to_string()),
+            expires: None,
+            account_id: None,
+        };
+        let auth = OpenCodeAuth {
+            google: None,
+            anthropic: Some(token),
+            openai: None,
+            github_copilot: None,
+        };
+        ClaudeProvider::new()
+            .with_auth(Arc::new(MemoryAuth::new(Some(auth), None)))
+            .with_transport(transport.clone())
+    }
+
+    async fn fetch(transport: FakeTransport) -> (Result<ProviderData>, Vec<String>) {
+        let transport = Arc::new(transport);
+        let result = provider(&transport).fetch(Duration::from_secs(5), false).await;
+        (result, transport.requests())
+    }
+
+    #[tokio::test]
+    async fn parses_usage() {
+        let (result, _) = fetch(FakeTransport::new().respond(USAGE_URL, 200, USAGE)).await;
+        let ProviderData::Claude(data) = result.unwrap() else {
+            panic!("expected Claude data");
+        };
+        assert_eq!(data.five_hour.utilization, 23.0);
+        assert_eq!(data.seven_day.remaining_percent, 15.0);
+        assert!(data.seven_day_sonnet.is_none());
+        assert_eq!(data.seven_day_opus.map(|w| w.utilization), Some(2.0));
+        assert!(data.extra_usage_enabled);
+        assert_eq!(data.extra_usage_spent_usd, Some(12.5));
+    }
+
+    #[tokio::test]
+    async fn refreshes_and_retries_on_401() {
+        let transport = FakeTransport::new()
+            .respond(USAGE_URL, 401, r#"{"error": "token revoked"}"#)
+            .respond(USAGE_URL, 200, USAGE)
+            .respond(ANTHROPIC_TOKEN_URL, 200, r#"{"access_token": "new", "refresh_token": "next", "expires_in": 3600}"#);
+        let (result, requests) = fetch(transport).await;
+        assert!(matches!(result, Ok(ProviderData::Claude(_))));
+        assert_eq!(
+            requests,
+            [
+                format!("GET {}", USAGE_URL),
+                format!("POST {}", ANTHROPIC_TOKEN_URL),
+                format!("GET {}", USAGE_URL),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_rate_limit_beyond_the_deadline() {
+        let transport = FakeTransport::new().respond_with_headers(USAGE_URL, 429, &[("retry-after", "3600")], "");
+        let (result, requests) = fetch(transport).await;
+        let err = result.unwrap_err();
+        assert!(err.is_rate_limited());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3600)));
+        assert_eq!(requests.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_a_rate_limit_within_the_deadline() {
+        let transport = FakeTransport::new()
+            .respond_with_headers(USAGE_URL, 429, &[("retry-after", "0")], "")
+            .respond(USAGE_URL, 200, USAGE);
+        let (result, requests) = fetch(transport).await;
+        assert!(matches!(result, Ok(ProviderData::Claude(_))));
+        assert_eq!(requests.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn names_the_field_of_a_malformed_body() {
+        let body = r#"{"five_hour": {"utilization": "lots"}}"#;
+        let (result, _) = fetch(FakeTransport::new().respond(USAGE_URL, 200, body)).await;
+        match result.unwrap_err() {
+            QuotaError::DecodeError { provider, path, .. } => {
+                assert_eq!(provider, "claude");
+                assert_eq!(path, "five_hour.utilization");
+            }
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
 }
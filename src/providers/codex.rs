@@ -16,92 +16,255 @@
  */
 
 use async_trait::async_trait;
+use chrono::Utc;
 use reqwest::Client;
 use serde::Deserialize;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::auth::AuthManager;
+use crate::auth::{run_blocking, AuthManager, AuthSource, OAuthToken};
 use crate::error::{QuotaError, Result};
-use crate::providers::{CodexData, Provider, ProviderData, WindowQuota};
+use crate::providers::events::EventHook;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::{decode_json, error_body, send, send_with_retry, CodexData, Provider, ProviderData, ProviderLinks, WindowQuota};
+
+/// OAuth client used by OpenCode's ChatGPT login
+const OPENAI_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
+
+const CHATGPT_API_URL: &str = "https://chatgpt.com/backend-api";
+const OPENAI_TOKEN_URL: &str = "https://auth.openai.com/oauth/token";
+
+pub const LINKS: ProviderLinks = ProviderLinks {
+    dashboard: "https://chatgpt.com/codex/settings/usage",
+    login: "opencode auth login openai",
+    pricing: "https://chatgpt.com/pricing",
+};
 
 pub struct CodexProvider {
-    auth_manager: AuthManager,
+    /// Where credentials are read from and refreshed tokens are kept
+    auth: Arc<dyn AuthSource>,
+    /// Print response bodies that fail to decode
+    dump_body_on_error: bool,
+    /// Builds the provider's HTTP requests
+    client: Client,
+    /// Sends the provider's HTTP requests
+    transport: Arc<dyn HttpTransport>,
+    /// Notified of each HTTP request, response and retry
+    events: Option<EventHook>,
+    /// Base URL of the usage API, without a trailing slash
+    base_url: String,
+    /// OAuth token endpoint used to refresh expired access tokens
+    token_url: String,
 }
 
 impl CodexProvider {
     pub fn new() -> Self {
+        // Shared by request building and the transport; each client loads its own TLS roots
+        let client = Client::new();
         Self {
-            auth_manager: AuthManager::new(),
+            auth: Arc::new(AuthManager::new()),
+            dump_body_on_error: false,
+            client: client.clone(),
+            transport: Arc::new(ReqwestTransport::new(client)),
+            events: None,
+            base_url: CHATGPT_API_URL.to_string(),
+            token_url: OPENAI_TOKEN_URL.to_string(),
         }
     }
-}
 
-#[async_trait]
-impl Provider for CodexProvider {
-    fn name(&self) -> &'static str {
-        "codex"
+    /// Read credentials from this source instead of OpenCode's files
+    pub fn with_auth(mut self, auth: Arc<dyn AuthSource>) -> Self {
+        self.auth = auth;
+        self
     }
 
-    fn is_configured(&self) -> bool {
-        self.auth_manager
-            .is_provider_configured("codex")
-            .unwrap_or(false)
+    /// Send requests with this client, e.g. one configured with a proxy or custom TLS roots
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.transport = Arc::new(ReqwestTransport::new(client.clone()));
+        self.client = client;
+        self
     }
 
-    async fn fetch(&self, timeout: Duration, verbose: bool) -> Result<ProviderData> {
-        let auth = self
-            .auth_manager
-            .read_opencode_auth()?
-            .ok_or_else(|| QuotaError::ProviderNotConfigured("codex".to_string()))?;
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
 
-        let openai_auth = auth
-            .openai
-            .ok_or_else(|| QuotaError::ProviderNotConfigured("codex (no openai token)".to_string()))?;
+    /// Report each HTTP request, response and retry to this hook
+    pub fn with_event_hook(mut self, hook: EventHook) -> Self {
+        self.events = Some(hook);
+        self
+    }
+
+    /// Print the raw response body to stderr when it fails to decode
+    pub fn with_dump_body_on_error(mut self, dump_body_on_error: bool) -> Self {
+        self.dump_body_on_error = dump_body_on_error;
+        self
+    }
+
+    /// Send API requests to this base URL instead of chatgpt.com/backend-api, e.g. an API gateway or a mock server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Refresh tokens at this URL instead of auth.openai.com
+    pub fn with_token_url(mut self, token_url: impl Into<String>) -> Self {
+        self.token_url = token_url.into();
+        self
+    }
+
+    /// Exchange the refresh token for a new access token and store it in auth.json
+    async fn refresh_token(&self, token: &OAuthToken, timeout: Duration, verbose: bool) -> Result<OAuthToken> {
+        let refresh = token
+            .refresh
+            .as_deref()
+            .ok_or_else(|| QuotaError::TokenRefreshError("Codex token expired and no refresh token is stored".to_string()))?;
 
-        let url = "https://chatgpt.com/backend-api/wham/usage";
+        if verbose {
+            eprintln!("[codex] POST {}", self.token_url);
+        }
+
+        let params = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh),
+            ("client_id", OPENAI_CLIENT_ID),
+        ];
+
+        let request = self.client
+            .post(&self.token_url)
+            .form(&params)
+            .timeout(timeout);
+        let response = send("codex", self.transport.as_ref(), self.events.as_ref(), request).await?;
+
+        let status = response.status();
+        if verbose {
+            eprintln!("[codex] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
+        }
+
+        if !status.is_success() {
+            let error_text = error_body("codex", response).await;
+            return Err(QuotaError::TokenRefreshError(format!(
+                "OpenAI OAuth refresh failed ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let refreshed: CodexTokenResponse = decode_json("codex", response, self.dump_body_on_error).await?;
+        let token = OAuthToken {
+            access: refreshed.access_token,
+            // OpenAI rotates refresh tokens; keep the old one if none came back
+            refresh: refreshed.refresh_token.or_else(|| token.refresh.clone()),
+            expires: Some(Utc::now().timestamp_millis() + refreshed.expires_in * 1000),
+            ..token.clone()
+        };
+
+        // The old refresh token may already be revoked, so failing to save is worth a warning
+        let (source, saved) = (Arc::clone(&self.auth), token.clone());
+        if let Err(e) = run_blocking(move || source.save_token("openai", &saved)).await {
+            eprintln!("Warning: could not save refreshed Codex token to auth.json: {}", e);
+        }
+
+        Ok(token)
+    }
+
+    async fn request_usage(&self, token: &OAuthToken, timeout: Duration, verbose: bool) -> Result<reqwest::Response> {
+        let url = format!("{}/wham/usage", self.base_url);
         if verbose {
             eprintln!("[codex] GET {}", url);
         }
 
-        let client = Client::new();
+        let client = &self.client;
         let mut request = client
             .get(url)
-            .header("Authorization", format!("Bearer {}", openai_auth.access))
+            .header("Authorization", format!("Bearer {}", token.access))
             .timeout(timeout);
 
         // Add account ID header if available
-        if let Some(account_id) = &openai_auth.account_id {
+        if let Some(account_id) = &token.account_id {
             request = request.header("ChatGPT-Account-Id", account_id);
         }
 
-        let response = request.send().await?;
+        let response = send_with_retry("codex", self.transport.as_ref(), self.events.as_ref(), request, timeout, verbose).await?;
 
-        let status = response.status();
         if verbose {
+            let status = response.status();
             eprintln!("[codex] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
         }
 
+        Ok(response)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Provider for CodexProvider {
+    fn name(&self) -> &'static str {
+        "codex"
+    }
+
+    fn links(&self) -> ProviderLinks {
+        LINKS
+    }
+
+    fn is_configured(&self) -> bool {
+        self.auth
+            .is_provider_configured("codex")
+            .unwrap_or(false)
+    }
+
+    async fn fetch(&self, timeout: Duration, verbose: bool) -> Result<ProviderData> {
+        let source = Arc::clone(&self.auth);
+        let auth = run_blocking(move || source.opencode_auth())
+            .await?
+            .ok_or_else(|| QuotaError::ProviderNotConfigured("codex".to_string()))?;
+
+        let mut openai_auth = auth
+            .openai
+            .ok_or_else(|| QuotaError::ProviderNotConfigured("codex (no openai token)".to_string()))?;
+
+        // Refresh up front when the stored token has expired, like OpenCode does;
+        // without a refresh token the user has to log in again
+        let mut refreshed = false;
+        if openai_auth.is_expired() {
+            if !openai_auth.can_refresh() {
+                let expired_at = openai_auth.expires_at().unwrap_or_else(Utc::now);
+                return Err(QuotaError::token_expired("codex", expired_at));
+            }
+            openai_auth = self.refresh_token(&openai_auth, timeout, verbose).await?;
+            refreshed = true;
+        }
+
+        let mut response = self.request_usage(&openai_auth, timeout, verbose).await?;
+
+        // A token revoked before its expiry time gets one refresh and retry
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED && !refreshed && openai_auth.can_refresh() {
+            openai_auth = self.refresh_token(&openai_auth, timeout, verbose).await?;
+            response = self.request_usage(&openai_auth, timeout, verbose).await?;
+        }
+
+        let status = response.status();
         if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(QuotaError::ApiError(format!(
-                "Codex API error ({}): {}",
-                status, error_text
-            )));
+            let error_text = error_body("codex", response).await;
+            return Err(QuotaError::HttpError {
+                status: status.as_u16(),
+                message: format!("Codex API error ({}): {}", status, error_text),
+            });
         }
 
-        let usage: CodexUsageResponse = response.json().await?;
+        let usage: CodexUsageResponse = decode_json("codex", response, self.dump_body_on_error).await?;
 
+        // Some plans omit one of the windows (or the whole rate limit); render what's there
+        let rate_limit = usage.rate_limit.unwrap_or_default();
         let data = CodexData {
             plan: usage.plan_type,
-            primary_window: WindowQuota {
-                used_percent: usage.rate_limit.primary_window.used_percent,
-                resets_in_seconds: usage.rate_limit.primary_window.reset_after_seconds,
-            },
-            secondary_window: WindowQuota {
-                used_percent: usage.rate_limit.secondary_window.used_percent,
-                resets_in_seconds: usage.rate_limit.secondary_window.reset_after_seconds,
-            },
+            primary_window: rate_limit.primary_window.map(CodexWindow::into_quota),
+            secondary_window: rate_limit.secondary_window.map(CodexWindow::into_quota),
         };
+        if data.windows().next().is_none() {
+            return Err(QuotaError::ApiError("Codex response has no rate limit windows".to_string()));
+        }
 
         Ok(ProviderData::Codex(data))
     }
@@ -113,26 +276,162 @@ impl Default for CodexProvider {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct CodexTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
 #[derive(Debug, Deserialize)]
 struct CodexUsageResponse {
-    #[serde(rename = "plan_type")]
+    #[serde(default, rename = "plan_type")]
     plan_type: String,
-    #[serde(rename = "rate_limit")]
-    rate_limit: CodexRateLimit,
+    #[serde(default, rename = "rate_limit", alias = "rate_limits")]
+    rate_limit: Option<CodexRateLimit>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Windows may be missing or null; the short names come from the Codex CLI's own protocol
+#[derive(Debug, Default, Deserialize)]
 struct CodexRateLimit {
-    #[serde(rename = "primary_window")]
-    primary_window: CodexWindow,
-    #[serde(rename = "secondary_window")]
-    secondary_window: CodexWindow,
+    #[serde(default, rename = "primary_window", alias = "primary")]
+    primary_window: Option<CodexWindow>,
+    #[serde(default, rename = "secondary_window", alias = "secondary")]
+    secondary_window: Option<CodexWindow>,
 }
 
 #[derive(Debug, Deserialize)]
 struct CodexWindow {
-    #[serde(rename = "used_percent")]
-    used_percent: i32,
-    #[serde(rename = "reset_after_seconds")]
-    reset_after_seconds: i64,
+    #[serde(rename = "used_percent", alias = "used_percentage")]
+    used_percent: f64,
+    #[serde(default, rename = "reset_after_seconds", alias = "resets_in_seconds")]
+    reset_after_seconds: Option<i64>,
+    /// Unix timestamp (seconds), sent instead of a relative reset by some responses
+    #[serde(default, rename = "reset_at", alias = "resets_at")]
+    reset_at: Option<i64>,
+    #[serde(default, rename = "limit_window_seconds", alias = "window_seconds")]
+    limit_window_seconds: Option<i64>,
+    #[serde(default, rename = "window_minutes")]
+    window_minutes: Option<i64>,
+}
+
+impl CodexWindow {
+    fn into_quota(self) -> WindowQuota {
+        let resets_in_seconds = self
+            .reset_after_seconds
+            .or_else(|| self.reset_at.map(|at| at - Utc::now().timestamp()))
+            .unwrap_or(0);
+        let window_seconds = self.limit_window_seconds.or(self.window_minutes.map(|m| m * 60));
+        WindowQuota::new(self.used_percent.round() as i32, resets_in_seconds).with_window_seconds(window_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{MemoryAuth, OpenCodeAuth};
+    use crate::providers::transport::FakeTransport;
+
+    const USAGE_URL: &str = "https://chatgpt.com/backend-api/wham/usage";
+
+    const USAGE: &str = r#"{
+        "plan_type": "plus",
+        "rate_limit": {
+            "primary_window": {"used_percent": 9.4, "reset_after_seconds": 7260, "limit_window_seconds": 18000},
+            "secondary_window": {"used_percent": 55, "reset_after_seconds": 265260}
+        }
+    }"#;
+
+    fn provider(transport: &Arc<FakeTransport>) -> CodexProvider {
+        let token = OAuthToken {
+            token_type: "oauth".to_string(),
+            access: "codex-access".to_string(),
+            refresh: Some("codex-refresh".to_string()),
+            expires: None,
+            account_id: Some("account".to_string()),
+        };
+        let auth = OpenCodeAuth {
+            google: None,
+            anthropic: None,
+            openai: Some(token),
+            github_copilot: None,
+        };
+        CodexProvider::new()
+            .with_auth(Arc::new(MemoryAuth::new(Some(auth), None)))
+            .with_transport(transport.clone())
+    }
+
+    async fn fetch(transport: FakeTransport) -> (Result<ProviderData>, Vec<String>) {
+        let transport = Arc::new(transport);
+        let result = provider(&transport).fetch(Duration::from_secs(5), false).await;
+        (result, transport.requests())
+    }
+
+    #[tokio::test]
+    async fn parses_usage() {
+        let (result, _) = fetch(FakeTransport::new().respond(USAGE_URL, 200, USAGE)).await;
+        let ProviderData::Codex(data) = result.unwrap() else {
+            panic!("expected Codex data");
+        };
+        assert_eq!(data.plan, "plus");
+        let primary = data.primary_window.unwrap();
+        assert_eq!((primary.used_percent, primary.resets_in_seconds), (9, 7260));
+        assert_eq!(primary.window_seconds, Some(18000));
+        assert_eq!(data.secondary_window.map(|w| w.remaining_percent), Some(45));
+    }
+
+    #[tokio::test]
+    async fn keeps_the_reset_time_of_stored_data() {
+        let (result, _) = fetch(FakeTransport::new().respond(USAGE_URL, 200, USAGE)).await;
+        let data = result.unwrap();
+        let ProviderData::Codex(codex) = &data else {
+            panic!("expected Codex data");
+        };
+        let resets_at = codex.primary_window.as_ref().unwrap().resets_at;
+
+        // A cache or team server hands the data back later; the reset must not move with it
+        let stored: ProviderData = serde_json::from_str(&serde_json::to_string(&data).unwrap()).unwrap();
+        assert_eq!(stored.soonest_reset(), Some(resets_at));
+    }
+
+    #[tokio::test]
+    async fn refreshes_and_retries_on_401() {
+        let transport = FakeTransport::new()
+            .respond(USAGE_URL, 401, r#"{"detail": "token revoked"}"#)
+            .respond(USAGE_URL, 200, USAGE)
+            .respond(OPENAI_TOKEN_URL, 200, r#"{"access_token": "new", "refresh_token": "next", "expires_in": 3600}"#);
+        let (result, requests) = fetch(transport).await;
+        assert!(matches!(result, Ok(ProviderData::Codex(_))));
+        assert_eq!(
+            requests,
+            [
+                format!("GET {}", USAGE_URL),
+                format!("POST {}", OPENAI_TOKEN_URL),
+                format!("GET {}", USAGE_URL),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_rate_limit_beyond_the_deadline() {
+        let transport = FakeTransport::new().respond_with_headers(USAGE_URL, 429, &[("retry-after", "3600")], "");
+        let (result, requests) = fetch(transport).await;
+        let err = result.unwrap_err();
+        assert!(err.is_rate_limited());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3600)));
+        assert_eq!(requests.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn names_the_field_of_a_malformed_body() {
+        let body = r#"{"plan_type": "plus", "rate_limit": {"primary_window": {"used_percent": "high"}}}"#;
+        let (result, _) = fetch(FakeTransport::new().respond(USAGE_URL, 200, body)).await;
+        match result.unwrap_err() {
+            QuotaError::DecodeError { provider, path, .. } => {
+                assert_eq!(provider, "codex");
+                assert_eq!(path, "rate_limit.primary_window.used_percent");
+            }
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
 }
@@ -16,90 +16,257 @@
  */
 
 use async_trait::async_trait;
+use chrono::Utc;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::auth::AuthManager;
+use crate::auth::{run_blocking, AuthManager, AuthSource};
+use crate::cache::{cache_dir, fingerprint, write_private};
+use crate::config::ClientIdentity;
 use crate::error::{QuotaError, Result};
-use crate::providers::{CopilotData, Provider, ProviderData};
+use crate::providers::events::EventHook;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::{decode_json, error_body, send, send_with_retry, used_percent_of, CopilotData, CopilotQuota, Provider, ProviderData, ProviderLinks};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+pub const LINKS: ProviderLinks = ProviderLinks {
+    dashboard: "https://github.com/settings/billing",
+    login: "opencode auth login github-copilot",
+    pricing: "https://github.com/features/copilot/plans",
+};
 
 pub struct CopilotProvider {
-    auth_manager: AuthManager,
+    /// Where credentials are read from and refreshed tokens are kept
+    auth: Arc<dyn AuthSource>,
+    /// Print response bodies that fail to decode
+    dump_body_on_error: bool,
+    /// Builds the provider's HTTP requests
+    client: Client,
+    /// Sends the provider's HTTP requests
+    transport: Arc<dyn HttpTransport>,
+    /// Notified of each HTTP request, response and retry
+    events: Option<EventHook>,
+    /// User-Agent and version headers sent with each request
+    identity: ClientIdentity,
+    /// Base URL of the API, without a trailing slash
+    base_url: String,
+    /// Directory the exchanged token is cached in; none disables the cache
+    cache_dir: Option<PathBuf>,
 }
 
 impl CopilotProvider {
     pub fn new() -> Self {
+        // Shared by request building and the transport; each client loads its own TLS roots
+        let client = Client::new();
         Self {
-            auth_manager: AuthManager::new(),
+            auth: Arc::new(AuthManager::new()),
+            dump_body_on_error: false,
+            client: client.clone(),
+            transport: Arc::new(ReqwestTransport::new(client)),
+            events: None,
+            identity: ClientIdentity::default(),
+            base_url: GITHUB_API_URL.to_string(),
+            cache_dir: cache_dir(),
         }
     }
 
+    /// Read credentials from this source instead of OpenCode's files
+    pub fn with_auth(mut self, auth: Arc<dyn AuthSource>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Send requests with this client, e.g. one configured with a proxy or custom TLS roots
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.transport = Arc::new(ReqwestTransport::new(client.clone()));
+        self.client = client;
+        self
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Identify with these User-Agent and version headers instead of the defaults
+    pub fn with_identity(mut self, identity: ClientIdentity) -> Self {
+        self.identity = identity;
+        self
+    }
+
+    /// Report each HTTP request, response and retry to this hook
+    pub fn with_event_hook(mut self, hook: EventHook) -> Self {
+        self.events = Some(hook);
+        self
+    }
+
+    /// Print the raw response body to stderr when it fails to decode
+    pub fn with_dump_body_on_error(mut self, dump_body_on_error: bool) -> Self {
+        self.dump_body_on_error = dump_body_on_error;
+        self
+    }
+
+    /// Send API requests to this base URL instead of api.github.com, e.g. a GitHub Enterprise host
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Cache the exchanged token in this directory instead of ~/.cache/ocu
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    async fn request_usage(&self, authorization: &str, timeout: Duration, verbose: bool) -> Result<reqwest::Response> {
+        let url = format!("{}/copilot_internal/user", self.base_url);
+        if verbose {
+            eprintln!("[copilot] GET {}", url);
+        }
+
+        let request = self.client
+            .get(url)
+            .header("Authorization", authorization)
+            .header("Accept", "application/json")
+            .header("User-Agent", &self.identity.user_agent)
+            .header("Editor-Version", &self.identity.editor_version)
+            .header("X-Github-Api-Version", "2025-04-01")
+            .timeout(timeout);
+        let response = send_with_retry("copilot", self.transport.as_ref(), self.events.as_ref(), request, timeout, verbose).await?;
+
+        if verbose {
+            let status = response.status();
+            eprintln!("[copilot] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
+        }
+
+        Ok(response)
+    }
+
+    /// Exchange the GitHub OAuth token for a short-lived Copilot token, cached until it expires
+    async fn exchange_token(&self, oauth_token: &str, timeout: Duration, verbose: bool) -> Result<String> {
+        let url = format!("{}/copilot_internal/v2/token", self.base_url);
+        if verbose {
+            eprintln!("[copilot] GET {}", url);
+        }
+
+        let request = self.client
+            .get(url)
+            .header("Authorization", format!("token {}", oauth_token))
+            .header("Accept", "application/json")
+            .header("User-Agent", &self.identity.user_agent)
+            .header("Editor-Version", &self.identity.editor_version)
+            .timeout(timeout);
+        let response = send("copilot", self.transport.as_ref(), self.events.as_ref(), request).await?;
+
+        let status = response.status();
+        if verbose {
+            eprintln!("[copilot] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
+        }
+
+        if !status.is_success() {
+            let error_text = error_body("copilot", response).await;
+            return Err(QuotaError::TokenRefreshError(format!(
+                "Copilot token exchange failed ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let exchanged: CopilotTokenResponse = decode_json("copilot", response, self.dump_body_on_error).await?;
+        let cached = CachedCopilotToken {
+            fingerprint: fingerprint(oauth_token),
+            token: exchanged.token,
+            expires_at: exchanged.expires_at,
+        };
+        if let Err(e) = cached.store(self.cache_dir.as_deref()) {
+            if verbose {
+                eprintln!("[copilot] could not cache exchanged token: {}", e);
+            }
+        }
+
+        Ok(cached.token)
+    }
 }
 
-#[async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Provider for CopilotProvider {
     fn name(&self) -> &'static str {
         "copilot"
     }
 
+    fn links(&self) -> ProviderLinks {
+        LINKS
+    }
+
     fn is_configured(&self) -> bool {
-        self.auth_manager
+        self.auth
             .is_provider_configured("copilot")
             .unwrap_or(false)
     }
 
     async fn fetch(&self, timeout: Duration, verbose: bool) -> Result<ProviderData> {
-        let auth = self
-            .auth_manager
-            .read_opencode_auth()?
+        let source = Arc::clone(&self.auth);
+        let auth = run_blocking(move || source.opencode_auth())
+            .await?
             .ok_or_else(|| QuotaError::ProviderNotConfigured("copilot".to_string()))?;
 
         let copilot_auth = auth
             .github_copilot
             .ok_or_else(|| QuotaError::ProviderNotConfigured("copilot (no token)".to_string()))?;
 
-        let client = Client::new();
+        // Reuse a still-valid exchanged token; otherwise try the OAuth token directly and
+        // exchange it only when the usage endpoint rejects it
+        // OpenCode keeps the long-lived GitHub OAuth token in `refresh` and a Copilot token in `access`
+        let oauth_token = copilot_auth
+            .refresh
+            .as_deref()
+            .filter(|t| !t.is_empty())
+            .unwrap_or(&copilot_auth.access);
 
-        // Fetch quota data
-        let url = "https://api.github.com/copilot_internal/user";
-        if verbose {
-            eprintln!("[copilot] GET {}", url);
-        }
+        let authorization = match CachedCopilotToken::load(self.cache_dir.as_deref(), oauth_token) {
+            Some(token) => format!("Bearer {}", token),
+            None => format!("token {}", copilot_auth.access),
+        };
+        let mut response = self.request_usage(&authorization, timeout, verbose).await?;
 
-        let response = client
-            .get(url)
-            .header("Authorization", format!("token {}", copilot_auth.access))
-            .header("Accept", "application/json")
-            .header("User-Agent", "ocu/0.1.0")
-            .header("Editor-Version", "vscode/1.96.2")
-            .header("X-Github-Api-Version", "2025-04-01")
-            .timeout(timeout)
-            .send()
-            .await?;
+        // Exchange once on 401, which also replaces a cached token that was revoked early
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            let token = self.exchange_token(oauth_token, timeout, verbose).await?;
+            response = self.request_usage(&format!("Bearer {}", token), timeout, verbose).await?;
+        }
 
         let status = response.status();
-        if verbose {
-            eprintln!("[copilot] {} {}", status.as_u16(), status.canonical_reason().unwrap_or(""));
-        }
 
         if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(QuotaError::ApiError(format!(
-                "Copilot API error ({}): {}",
-                status, error_text
-            )));
+            let error_text = error_body("copilot", response).await;
+            return Err(QuotaError::HttpError {
+                status: status.as_u16(),
+                message: format!("Copilot API error ({}): {}", status, error_text),
+            });
         }
 
-        let usage: CopilotUsageResponse = response.json().await?;
-        let premium = &usage.quota_snapshots.premium_interactions;
+        let usage: CopilotUsageResponse = decode_json("copilot", response, self.dump_body_on_error).await?;
+        let snapshots = usage.quota_snapshots;
+        let premium = &snapshots.premium_interactions;
+        let premium_unlimited = is_unlimited(premium.unlimited, premium.entitlement);
+        let entitlement = premium.entitlement.unwrap_or(0);
+        let remaining = premium.remaining.unwrap_or(0);
 
         let data = CopilotData {
             plan: usage.copilot_plan,
-            premium_entitlement: premium.entitlement,
-            premium_remaining: premium.remaining,
+            premium_entitlement: entitlement,
+            premium_remaining: remaining,
+            premium_used_percent: if premium_unlimited { 0 } else { used_percent_of(entitlement, remaining) },
+            premium_unlimited,
             overage_permitted: premium.overage_permitted,
             overage_count: premium.overage_count,
+            chat: snapshots.chat.map(CopilotQuota::from),
+            completions: snapshots.completions.map(CopilotQuota::from),
             quota_reset_date: usage.quota_reset_date,
         };
 
@@ -113,6 +280,43 @@ impl Default for CopilotProvider {
     }
 }
 
+/// Short-lived token from `copilot_internal/v2/token`
+#[derive(Debug, Deserialize)]
+struct CopilotTokenResponse {
+    token: String,
+    /// Unix timestamp (seconds)
+    expires_at: i64,
+}
+
+/// Exchanged token cached in ~/.cache/ocu/copilot-token.json
+/// Tied to the OAuth token it came from by a fingerprint, so logging in again invalidates it
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedCopilotToken {
+    fingerprint: String,
+    token: String,
+    expires_at: i64,
+}
+
+impl CachedCopilotToken {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("copilot-token.json")
+    }
+
+    /// Cached token for this OAuth token, if it is still valid for another minute
+    fn load(dir: Option<&Path>, oauth_token: &str) -> Option<String> {
+        let content = std::fs::read_to_string(Self::path(dir?)).ok()?;
+        let cached: Self = serde_json::from_str(&content).ok()?;
+        (cached.fingerprint == fingerprint(oauth_token) && cached.expires_at > Utc::now().timestamp() + 60)
+            .then_some(cached.token)
+    }
+
+    fn store(&self, dir: Option<&Path>) -> Result<()> {
+        let dir = dir.ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))?;
+        // The token grants Copilot access, so keep it private to the user
+        write_private(&Self::path(dir), &serde_json::to_string(self)?)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct CopilotUsageResponse {
     #[serde(rename = "copilot_plan")]
@@ -127,14 +331,244 @@ struct CopilotUsageResponse {
 struct CopilotQuotaSnapshots {
     #[serde(rename = "premium_interactions")]
     premium_interactions: CopilotPremiumInteractions,
+    chat: Option<CopilotQuotaSnapshot>,
+    completions: Option<CopilotQuotaSnapshot>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CopilotQuotaSnapshot {
+    #[serde(default, deserialize_with = "count_or_unlimited")]
+    entitlement: Option<i64>,
+    #[serde(default, deserialize_with = "count_or_unlimited")]
+    remaining: Option<i64>,
+    #[serde(default)]
+    unlimited: bool,
+}
+
+impl From<CopilotQuotaSnapshot> for CopilotQuota {
+    fn from(snapshot: CopilotQuotaSnapshot) -> Self {
+        let unlimited = is_unlimited(snapshot.unlimited, snapshot.entitlement);
+        let entitlement = snapshot.entitlement.unwrap_or(0);
+        let remaining = snapshot.remaining.unwrap_or(0);
+        Self {
+            entitlement,
+            remaining,
+            unlimited,
+            used_percent: (!unlimited).then(|| used_percent_of(entitlement, remaining)),
+        }
+    }
+}
+
+/// Business/Enterprise plans report uncapped quotas with the `unlimited` flag, an
+/// "unlimited" marker or a zero entitlement rather than a count
+fn is_unlimited(flag: bool, entitlement: Option<i64>) -> bool {
+    flag || entitlement.map_or(true, |e| e <= 0)
+}
+
+/// A request count that may also be sent as a string, e.g. `300`, `"300"` or `"unlimited"`
+/// Anything that isn't a number (markers, null) becomes `None`
+fn count_or_unlimited<'de, D>(deserializer: D) -> std::result::Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(match value {
+        serde_json::Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f.round() as i64)),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    })
 }
 
 #[derive(Debug, Deserialize)]
 struct CopilotPremiumInteractions {
-    entitlement: i64,
-    remaining: i64,
-    #[serde(rename = "overage_permitted")]
+    #[serde(default, deserialize_with = "count_or_unlimited")]
+    entitlement: Option<i64>,
+    #[serde(default, deserialize_with = "count_or_unlimited")]
+    remaining: Option<i64>,
+    #[serde(default)]
+    unlimited: bool,
+    #[serde(default, rename = "overage_permitted")]
     overage_permitted: bool,
     #[serde(default, rename = "overage_count", alias = "overageCount")]
     overage_count: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{MemoryAuth, OAuthToken, OpenCodeAuth};
+    use crate::providers::transport::FakeTransport;
+    use crate::output::describe_overage;
+    use crate::providers::cost::COPILOT_PREMIUM_REQUEST_USD;
+    use crate::providers::{CostModel, Overage, ProviderStatus};
+    use tempfile::TempDir;
+
+    const USAGE_URL: &str = "https://api.github.com/copilot_internal/user";
+    const TOKEN_URL: &str = "https://api.github.com/copilot_internal/v2/token";
+
+    const USAGE: &str = r#"{
+        "copilot_plan": "individual",
+        "quota_reset_date": "2026-11-01",
+        "quota_snapshots": {
+            "premium_interactions": {"entitlement": 300, "remaining": -20, "unlimited": false, "overage_permitted": true, "overage_count": 20},
+            "chat": {"entitlement": 0, "remaining": 0, "unlimited": true},
+            "completions": {"entitlement": 2000, "remaining": 1500, "unlimited": false}
+        }
+    }"#;
+
+    fn provider(transport: &Arc<FakeTransport>, cache: &TempDir, oauth_token: &str) -> CopilotProvider {
+        let token = OAuthToken {
+            token_type: "oauth".to_string(),
+            access: "copilot-access".to_string(),
+            refresh: Some(oauth_token.to_string()),
+            expires: None,
+            account_id: None,
+        };
+        let auth = OpenCodeAuth {
+            google: None,
+            anthropic: None,
+            openai: None,
+            github_copilot: Some(token),
+        };
+        CopilotProvider::new()
+            .with_auth(Arc::new(MemoryAuth::new(Some(auth), None)))
+            .with_transport(transport.clone())
+            .with_cache_dir(cache.path())
+    }
+
+    async fn fetch(transport: FakeTransport, oauth_token: &str) -> (Result<ProviderData>, Vec<String>) {
+        let transport = Arc::new(transport);
+        let cache = TempDir::new().unwrap();
+        let result = provider(&transport, &cache, oauth_token).fetch(Duration::from_secs(5), false).await;
+        (result, transport.requests())
+    }
+
+    async fn fetch_data(body: &str, oauth_token: &str) -> CopilotData {
+        match fetch(FakeTransport::new().respond(USAGE_URL, 200, body), oauth_token).await.0 {
+            Ok(ProviderData::Copilot(data)) => data,
+            other => panic!("expected Copilot data, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn parses_usage_with_overage() {
+        let data = fetch_data(USAGE, "oauth-usage").await;
+        assert_eq!(data.plan, "individual");
+        assert_eq!((data.premium_entitlement, data.premium_remaining), (300, -20));
+        assert_eq!(data.premium_used_percent, 100);
+        assert!(!data.premium_unlimited);
+        assert_eq!(CostModel::default().copilot_overage(&data), Some(20.0 * COPILOT_PREMIUM_REQUEST_USD));
+        assert_eq!(data.chat.as_ref().map(|q| q.unlimited), Some(true));
+        assert_eq!(data.completions.and_then(|q| q.used_percent), Some(25));
+    }
+
+    #[tokio::test]
+    async fn counts_overage_requests_the_same_everywhere() {
+        // The overage count can run ahead of the remainder once the period's usage is reconciled
+        let body = USAGE.replace(r#""remaining": -20"#, r#""remaining": -5"#);
+        let data = fetch_data(&body, "oauth-overage-count").await;
+        assert_eq!(data.overage_requests(), 20);
+        let data = ProviderData::Copilot(data);
+        assert_eq!(data.overage(), Some(Overage::Billing { requests: 20 }));
+        assert_eq!(CostModel::default().estimate(&data), Some(20.0 * COPILOT_PREMIUM_REQUEST_USD));
+    }
+
+    #[tokio::test]
+    async fn prices_overage_with_the_configured_rate() {
+        let data = ProviderData::Copilot(fetch_data(USAGE, "oauth-overage-price").await);
+        let cost = CostModel {
+            copilot_premium_request: 0.1,
+            ..CostModel::default()
+        };
+        let overage = data.overage().unwrap();
+        assert_eq!(describe_overage(overage, cost.estimate(&data)), "paying 20 reqs ($2.00)");
+    }
+
+    // Captured from Business and Enterprise seats, which mark premium requests unlimited in three ways
+    const BUSINESS_FLAGGED: &str = r#"{
+        "copilot_plan": "business",
+        "quota_reset_date": "2026-11-01",
+        "quota_snapshots": {
+            "premium_interactions": {"entitlement": 0, "remaining": 0, "unlimited": true, "overage_permitted": false, "overage_count": 0, "percent_remaining": 100.0},
+            "chat": {"entitlement": 0, "remaining": 0, "unlimited": true, "percent_remaining": 100.0},
+            "completions": {"entitlement": 0, "remaining": 0, "unlimited": true, "percent_remaining": 100.0}
+        }
+    }"#;
+
+    const ENTERPRISE_ZERO: &str = r#"{
+        "copilot_plan": "enterprise",
+        "quota_reset_date": "2026-11-01",
+        "quota_snapshots": {
+            "premium_interactions": {"entitlement": 0, "remaining": 0, "overage_permitted": true, "overage_count": 0},
+            "chat": {"entitlement": 0, "remaining": 0},
+            "completions": {"entitlement": 0, "remaining": 0}
+        }
+    }"#;
+
+    const ENTERPRISE_MARKER: &str = r#"{
+        "copilot_plan": "enterprise",
+        "quota_reset_date": "2026-11-01",
+        "quota_snapshots": {
+            "premium_interactions": {"entitlement": "unlimited", "remaining": "unlimited", "overage_permitted": false},
+            "chat": {"entitlement": "unlimited", "remaining": "unlimited"},
+            "completions": {"entitlement": "unlimited", "remaining": "unlimited"}
+        }
+    }"#;
+
+    #[tokio::test]
+    async fn treats_business_and_enterprise_seats_as_unlimited() {
+        for (fixture, token) in [
+            (BUSINESS_FLAGGED, "oauth-business"),
+            (ENTERPRISE_ZERO, "oauth-enterprise-zero"),
+            (ENTERPRISE_MARKER, "oauth-enterprise-marker"),
+        ] {
+            let data = fetch_data(fixture, token).await;
+            assert!(data.premium_unlimited, "{}", data.plan);
+            assert_eq!(data.premium_used_percent, 0, "{}", data.plan);
+            assert_eq!(data.overage_requests(), 0, "{}", data.plan);
+            assert_eq!(data.chat.as_ref().map(|q| q.unlimited), Some(true), "{}", data.plan);
+            assert_eq!(ProviderData::Copilot(data).status(), ProviderStatus::Ok);
+        }
+    }
+
+    #[tokio::test]
+    async fn exchanges_the_token_on_401() {
+        let transport = FakeTransport::new()
+            .respond(USAGE_URL, 401, r#"{"message": "Bad credentials"}"#)
+            .respond(USAGE_URL, 200, USAGE)
+            .respond(TOKEN_URL, 200, r#"{"token": "exchanged", "expires_at": 4102444800}"#);
+        let (result, requests) = fetch(transport, "oauth-exchange").await;
+        assert!(matches!(result, Ok(ProviderData::Copilot(_))));
+        assert_eq!(
+            requests,
+            [
+                format!("GET {}", USAGE_URL),
+                format!("GET {}", TOKEN_URL),
+                format!("GET {}", USAGE_URL),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_rate_limit_beyond_the_deadline() {
+        let transport = FakeTransport::new().respond_with_headers(USAGE_URL, 429, &[("retry-after", "3600")], "");
+        let (result, requests) = fetch(transport, "oauth-rate-limit").await;
+        let err = result.unwrap_err();
+        assert!(err.is_rate_limited());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3600)));
+        assert_eq!(requests.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn names_the_field_of_a_malformed_body() {
+        let body = r#"{"copilot_plan": "individual", "quota_reset_date": "2026-11-01", "quota_snapshots": {}}"#;
+        let (result, _) = fetch(FakeTransport::new().respond(USAGE_URL, 200, body), "oauth-malformed").await;
+        match result.unwrap_err() {
+            QuotaError::DecodeError { provider, path, .. } => {
+                assert_eq!(provider, "copilot");
+                assert_eq!(path, "quota_snapshots");
+            }
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
+}
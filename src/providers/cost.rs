@@ -0,0 +1,115 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+
+use crate::glob::matches_filter;
+use crate::providers::{CopilotData, ProviderData};
+
+/// Price GitHub charges per premium request beyond the plan entitlement (USD)
+pub const COPILOT_PREMIUM_REQUEST_USD: f64 = 0.04;
+
+/// List prices per million tokens (model pattern, input, output) used when no
+/// `[[cost.models]]` entry matches; more specific patterns come first
+const BUILT_IN_PRICES: &[(&str, f64, f64)] = &[
+    ("*claude-opus-4-5*", 5.0, 25.0),
+    ("*claude-opus-4*", 15.0, 75.0),
+    ("*claude-sonnet-4*", 3.0, 15.0),
+    ("*claude-haiku-4*", 1.0, 5.0),
+    ("*claude-3-5-haiku*", 0.8, 4.0),
+    ("*gpt-5*-mini*", 0.25, 2.0),
+    ("*gpt-5*", 1.25, 10.0),
+    ("*gpt-4.1*", 2.0, 8.0),
+    ("*gemini-3-pro*", 2.0, 12.0),
+    ("*gemini-2.5-pro*", 1.25, 10.0),
+    ("*gemini-2.5-flash*", 0.3, 2.5),
+];
+
+/// Prices that turn usage beyond a plan into estimated dollars,
+/// configurable through the `[cost]` config section
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CostModel {
+    /// Price of a Copilot premium request beyond the entitlement (USD)
+    pub copilot_premium_request: f64,
+    /// Per-model token prices, checked before the built-in ones; the first entry matching a model wins
+    pub models: Vec<TokenPrice>,
+}
+
+/// One `[[cost.models]]` entry
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenPrice {
+    /// Model name (substring or glob, e.g. "claude-sonnet-4*")
+    pub model: String,
+    /// USD per million input tokens
+    pub input: f64,
+    /// USD per million output tokens
+    pub output: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            copilot_premium_request: COPILOT_PREMIUM_REQUEST_USD,
+            models: Vec::new(),
+        }
+    }
+}
+
+impl CostModel {
+    /// Estimated spend beyond the plan this period (USD, rounded to cents),
+    /// for providers that can bill it: Copilot with overage permitted and Claude with extra usage enabled
+    pub fn estimate(&self, data: &ProviderData) -> Option<f64> {
+        let usd = match data {
+            ProviderData::Copilot(data) => return self.copilot_overage(data),
+            ProviderData::Claude(data) if data.extra_usage_enabled => data.extra_usage_spent_usd?,
+            _ => return None,
+        };
+        Some((usd * 100.0).round() / 100.0)
+    }
+
+    /// Estimated Copilot spend on premium requests beyond the entitlement (USD, rounded to cents),
+    /// or None when overage isn't permitted
+    pub fn copilot_overage(&self, data: &CopilotData) -> Option<f64> {
+        if !data.overage_permitted || data.premium_unlimited {
+            return None;
+        }
+        let usd = data.overage_requests() as f64 * self.copilot_premium_request;
+        Some((usd * 100.0).round() / 100.0)
+    }
+
+    /// Input and output prices per million tokens for a model, if any entry matches
+    pub fn token_price(&self, model: &str) -> Option<(f64, f64)> {
+        self.models
+            .iter()
+            .find(|p| matches_filter(&p.model, model))
+            .map(|p| (p.input, p.output))
+            .or_else(|| {
+                BUILT_IN_PRICES
+                    .iter()
+                    .find(|(pattern, _, _)| matches_filter(pattern, model))
+                    .map(|&(_, input, output)| (input, output))
+            })
+    }
+
+    /// What the tokens would cost at API prices (USD), for usage billed per token
+    pub fn token_cost(&self, model: &str, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+        let (input, output) = self.token_price(model)?;
+        Some((input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0)
+    }
+}
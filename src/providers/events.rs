@@ -0,0 +1,104 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Progress of a fetch, for progress UIs, logging and telemetry
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchEvent {
+    /// A provider's fetch began
+    Started { provider: String },
+    /// A request is about to be sent
+    Request { provider: String, method: String, url: String },
+    /// A response arrived (any status); `bytes` is the body size when the server sends Content-Length
+    Response { provider: String, url: String, status: u16, elapsed: Duration, bytes: Option<u64> },
+    /// A 429 is being waited out before the request is sent again
+    Retry { provider: String, url: String, wait: Duration },
+    /// A provider's fetch finished, successfully or not
+    Finished { provider: String, ok: bool, elapsed: Duration },
+}
+
+/// Callback receiving fetch events; called inline, so it should return quickly
+pub type EventHook = Arc<dyn Fn(&FetchEvent) + Send + Sync>;
+
+/// Latency, status and size of one HTTP request, as reported under `meta.timings` in JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RequestTiming {
+    pub provider: String,
+    pub url: String,
+    pub status: u16,
+    /// Time until the response headers arrived
+    pub elapsed_ms: u64,
+    /// Body size, when the server sends Content-Length
+    pub bytes: Option<u64>,
+}
+
+impl RequestTiming {
+    /// The timing carried by a `Response` event
+    pub fn from_event(event: &FetchEvent) -> Option<Self> {
+        match event {
+            FetchEvent::Response { provider, url, status, elapsed, bytes } => Some(Self {
+                provider: provider.clone(),
+                url: url.clone(),
+                status: *status,
+                elapsed_ms: elapsed.as_millis() as u64,
+                bytes: *bytes,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Collects the timing of every response seen by its hook
+#[derive(Clone, Default)]
+pub struct TimingRecorder {
+    timings: Arc<Mutex<Vec<RequestTiming>>>,
+}
+
+impl TimingRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the event if it is a response
+    pub fn record(&self, event: &FetchEvent) {
+        if let (Some(timing), Ok(mut timings)) = (RequestTiming::from_event(event), self.timings.lock()) {
+            timings.push(timing);
+        }
+    }
+
+    /// Hook recording into this recorder, for the providers' `with_event_hook`
+    pub fn hook(&self) -> EventHook {
+        let recorder = self.clone();
+        Arc::new(move |event| recorder.record(event))
+    }
+
+    /// Timings recorded so far, in arrival order
+    pub fn timings(&self) -> Vec<RequestTiming> {
+        self.timings.lock().map(|t| t.clone()).unwrap_or_default()
+    }
+}
+
+/// Report an event to the hook, if there is one
+pub(crate) fn emit(hook: Option<&EventHook>, event: impl FnOnce() -> FetchEvent) {
+    if let Some(hook) = hook {
+        hook(&event());
+    }
+}
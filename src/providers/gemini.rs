@@ -18,12 +18,23 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Mutex;
 
-use crate::auth::{AntigravityAccount, AuthManager, GeminiTokenResponse};
+use crate::auth::{run_blocking, AntigravityAccount, AuthManager, AuthSource, GeminiTokenResponse};
+use crate::cache::{cache_dir, fingerprint, write_private};
+use crate::config::{ClientIdentity, GeminiConfig};
 use crate::error::{QuotaError, Result};
-use crate::providers::{GeminiAccountData, GeminiData, GeminiModelQuota, Provider, ProviderData};
+use crate::glob::glob_match;
+use crate::output::redact::scrub_secrets;
+use crate::providers::events::EventHook;
+use crate::providers::transport::{HttpTransport, ReqwestTransport};
+use crate::providers::{decode_json, error_body, send, send_with_retry, GeminiAccountData, GeminiData, GeminiModelQuota, Provider, ProviderData, ProviderLinks};
+#[cfg(feature = "cli")]
 use colored::Colorize;
 
 /// Public Google OAuth client credentials for CLI/installed apps
@@ -36,8 +47,13 @@ const ANTIGRAVITY_ENDPOINT_PROD: &str = "https://cloudcode-pa.googleapis.com";
 const _ANTIGRAVITY_ENDPOINT_DAILY: &str = "https://daily-cloudcode-pa.sandbox.googleapis.com";
 const _ANTIGRAVITY_ENDPOINT_AUTOPUSH: &str = "https://autopush-cloudcode-pa.sandbox.googleapis.com";
 
-/// Default headers for Antigravity API requests
-const ANTIGRAVITY_VERSION: &str = "1.15.8";
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+pub const LINKS: ProviderLinks = ProviderLinks {
+    dashboard: "https://aistudio.google.com/usage",
+    login: "opencode auth login gemini",
+    pricing: "https://ai.google.dev/gemini-api/docs/pricing",
+};
 
 /// Get platform string for User-Agent header
 fn get_platform() -> &'static str {
@@ -73,21 +89,174 @@ fn get_platform() -> &'static str {
 }
 
 pub struct GeminiProvider {
-    auth_manager: AuthManager,
+    /// Where credentials are read from and refreshed tokens are kept
+    auth: Arc<dyn AuthSource>,
+    /// Account email patterns to query (globs); empty means all accounts
+    account_patterns: Vec<String>,
+    /// Only query the active account
+    active_only: bool,
+    /// Print response bodies that fail to decode
+    dump_body_on_error: bool,
+    /// Builds the provider's HTTP requests
+    client: Client,
+    /// Sends the provider's HTTP requests
+    transport: Arc<dyn HttpTransport>,
+    /// Notified of each HTTP request, response and retry
+    events: Option<EventHook>,
+    /// User-Agent and version headers sent with each request
+    identity: ClientIdentity,
+    /// Base URL of the Antigravity API, without a trailing slash
+    base_url: String,
+    /// OAuth token endpoint used to refresh expired access tokens
+    token_url: String,
+    /// How models are grouped into quota buckets and which are hidden
+    rules: GeminiConfig,
+    /// Access tokens by refresh token fingerprint; each slot is locked while its token is
+    /// refreshed, so accounts sharing a refresh token wait for one refresh instead of racing
+    access_tokens: Mutex<HashMap<String, Arc<Mutex<Option<CachedAccessToken>>>>>,
+    /// Directory access tokens are cached in between runs; none disables the cache
+    cache_dir: Option<PathBuf>,
 }
 
 impl GeminiProvider {
     pub fn new() -> Self {
+        // Shared by request building and the transport; each client loads its own TLS roots
+        let client = Client::new();
         Self {
-            auth_manager: AuthManager::new(),
+            auth: Arc::new(AuthManager::new()),
+            account_patterns: Vec::new(),
+            active_only: false,
+            dump_body_on_error: false,
+            client: client.clone(),
+            transport: Arc::new(ReqwestTransport::new(client)),
+            events: None,
+            identity: ClientIdentity::default(),
+            base_url: ANTIGRAVITY_ENDPOINT_PROD.to_string(),
+            token_url: GOOGLE_TOKEN_URL.to_string(),
+            rules: GeminiConfig::default(),
+            access_tokens: Mutex::new(HashMap::new()),
+            cache_dir: cache_dir(),
         }
     }
 
+    /// Restrict fetching to accounts whose email matches one of the patterns
+    /// Patterns support `*` and `?` wildcards and are case-insensitive
+    pub fn with_accounts(mut self, patterns: Vec<String>) -> Self {
+        self.account_patterns = patterns;
+        self
+    }
+
+    /// Only fetch the active account, skipping the round trips for the others
+    pub fn with_active_only(mut self, active_only: bool) -> Self {
+        self.active_only = active_only;
+        self
+    }
+
+    /// Group models into quota buckets with these rules instead of the built-in ones
+    pub fn with_rules(mut self, rules: GeminiConfig) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Read credentials from this source instead of OpenCode's files
+    pub fn with_auth(mut self, auth: Arc<dyn AuthSource>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Send requests with this client, e.g. one configured with a proxy or custom TLS roots
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.transport = Arc::new(ReqwestTransport::new(client.clone()));
+        self.client = client;
+        self
+    }
+
+    /// Send requests through this transport instead of the network, e.g. a `FakeTransport` in tests
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Identify with these User-Agent and version headers instead of the defaults
+    pub fn with_identity(mut self, identity: ClientIdentity) -> Self {
+        self.identity = identity;
+        self
+    }
+
+    /// Report each HTTP request, response and retry to this hook
+    pub fn with_event_hook(mut self, hook: EventHook) -> Self {
+        self.events = Some(hook);
+        self
+    }
+
+    /// Print the raw response body to stderr when it fails to decode
+    pub fn with_dump_body_on_error(mut self, dump_body_on_error: bool) -> Self {
+        self.dump_body_on_error = dump_body_on_error;
+        self
+    }
+
+    /// Send API requests to this base URL instead of cloudcode-pa.googleapis.com, e.g. an API gateway or a mock server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Refresh tokens at this URL instead of oauth2.googleapis.com
+    pub fn with_token_url(mut self, token_url: impl Into<String>) -> Self {
+        self.token_url = token_url.into();
+        self
+    }
+
+    /// Cache access tokens in this directory instead of ~/.cache/ocu
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    fn account_selected(&self, email: &str) -> bool {
+        self.account_patterns.is_empty()
+            || self
+                .account_patterns
+                .iter()
+                .any(|p| glob_match(&p.to_lowercase(), &email.to_lowercase()))
+    }
+
+    /// Access token for a refresh token, refreshed at most once per expiry
+    /// Reuses a token already refreshed by this process or, via the token cache, by an earlier run
+    async fn access_token(&self, refresh_token: &str, verbose: bool) -> Result<String> {
+        let key = fingerprint(refresh_token);
+        let slot = self.access_tokens.lock().await.entry(key.clone()).or_default().clone();
+        let mut slot = slot.lock().await;
+        if let Some(cached) = slot.as_ref().filter(|t| t.is_valid()) {
+            return Ok(cached.token.clone());
+        }
+
+        let mut cache = CachedGoogleTokens::load(self.cache_dir.as_deref());
+        if let Some(cached) = cache.tokens.get(&key).filter(|t| t.is_valid()) {
+            if verbose {
+                eprintln!("[gemini] Reusing cached access token");
+            }
+            *slot = Some(cached.clone());
+            return Ok(cached.token.clone());
+        }
+
+        let refreshed = self.refresh_access_token(refresh_token, verbose).await?;
+        cache.tokens.retain(|_, t| t.is_valid());
+        cache.tokens.insert(key, refreshed.clone());
+        if let Err(e) = cache.store(self.cache_dir.as_deref()) {
+            if verbose {
+                eprintln!("[gemini] could not cache access token: {}", e);
+            }
+        }
+        *slot = Some(refreshed.clone());
+        Ok(refreshed.token)
+    }
+
     /// Refresh access token using refresh token
-    async fn refresh_access_token(&self, refresh_token: &str, verbose: bool) -> Result<String> {
-        let client = Client::new();
+    async fn refresh_access_token(&self, refresh_token: &str, verbose: bool) -> Result<CachedAccessToken> {
+        let client = &self.client;
 
-        let url = "https://oauth2.googleapis.com/token";
+        let url = &self.token_url;
         if verbose {
             eprintln!("[gemini] POST {}", url);
         }
@@ -99,12 +268,11 @@ impl GeminiProvider {
             ("grant_type", "refresh_token"),
         ];
 
-        let response = client
+        let request = client
             .post(url)
             .form(&params)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await?;
+            .timeout(Duration::from_secs(10));
+        let response = send("gemini", self.transport.as_ref(), self.events.as_ref(), request).await?;
 
         let status = response.status();
         if verbose {
@@ -112,22 +280,25 @@ impl GeminiProvider {
         }
 
         if !status.is_success() {
-            let error_text = response.text().await?;
+            let error_text = error_body("gemini", response).await;
             return Err(QuotaError::TokenRefreshError(format!(
                 "Google OAuth refresh failed: {}",
                 error_text
             )));
         }
 
-        let token_response: GeminiTokenResponse = response.json().await?;
-        Ok(token_response.access_token)
+        let token_response: GeminiTokenResponse = decode_json("gemini", response, self.dump_body_on_error).await?;
+        Ok(CachedAccessToken {
+            token: token_response.access_token,
+            expires_at: Utc::now().timestamp() + i64::from(token_response.expires_in),
+        })
     }
 
     /// Load code assist to get project ID
     async fn load_code_assist(&self, access_token: &str, timeout: Duration, verbose: bool) -> Result<LoadCodeAssistResponse> {
-        let client = Client::new();
+        let client = &self.client;
 
-        let url = format!("{}/v1internal:loadCodeAssist", ANTIGRAVITY_ENDPOINT_PROD);
+        let url = format!("{}/v1internal:loadCodeAssist", self.base_url);
         if verbose {
             eprintln!("[gemini] POST {}", url);
         }
@@ -138,16 +309,15 @@ impl GeminiProvider {
             "pluginType": "GEMINI",
         });
 
-        let response = client
+        let request = client
             .post(&url)
             .header("Authorization", format!("Bearer {}", access_token))
             .header("Content-Type", "application/json")
-            .header("User-Agent", format!("antigravity/{} {}", ANTIGRAVITY_VERSION, get_platform()))
+            .header("User-Agent", format!("antigravity/{} {}", self.identity.antigravity_version, get_platform()))
             .header("X-Goog-Api-Client", "google-cloud-sdk vscode_cloudshelleditor/0.1")
             .json(&serde_json::json!({ "metadata": metadata }))
-            .timeout(timeout)
-            .send()
-            .await?;
+            .timeout(timeout);
+        let response = send_with_retry("gemini", self.transport.as_ref(), self.events.as_ref(), request, timeout, verbose).await?;
 
         let status = response.status();
         if verbose {
@@ -155,14 +325,14 @@ impl GeminiProvider {
         }
 
         if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(QuotaError::ApiError(format!(
-                "loadCodeAssist failed ({}): {}",
-                status, error_text
-            )));
+            let error_text = error_body("gemini", response).await;
+            return Err(QuotaError::HttpError {
+                status: status.as_u16(),
+                message: format!("loadCodeAssist failed ({}): {}", status, error_text),
+            });
         }
 
-        let result: LoadCodeAssistResponse = response.json().await?;
+        let result: LoadCodeAssistResponse = decode_json("gemini", response, self.dump_body_on_error).await?;
         Ok(result)
     }
 
@@ -188,9 +358,9 @@ impl GeminiProvider {
         timeout: Duration,
         verbose: bool,
     ) -> Result<FetchAvailableModelsResponse> {
-        let client = Client::new();
+        let client = &self.client;
 
-        let url = format!("{}/v1internal:fetchAvailableModels", ANTIGRAVITY_ENDPOINT_PROD);
+        let url = format!("{}/v1internal:fetchAvailableModels", self.base_url);
         if verbose {
             eprintln!("[gemini] POST {}", url);
         }
@@ -201,16 +371,15 @@ impl GeminiProvider {
             serde_json::json!({})
         };
 
-        let response = client
+        let request = client
             .post(&url)
             .header("Authorization", format!("Bearer {}", access_token))
             .header("Content-Type", "application/json")
-            .header("User-Agent", format!("antigravity/{} {}", ANTIGRAVITY_VERSION, get_platform()))
+            .header("User-Agent", format!("antigravity/{} {}", self.identity.antigravity_version, get_platform()))
             .header("X-Goog-Api-Client", "google-cloud-sdk vscode_cloudshelleditor/0.1")
             .json(&payload)
-            .timeout(timeout)
-            .send()
-            .await?;
+            .timeout(timeout);
+        let response = send_with_retry("gemini", self.transport.as_ref(), self.events.as_ref(), request, timeout, verbose).await?;
 
         let status = response.status();
         if verbose {
@@ -218,14 +387,14 @@ impl GeminiProvider {
         }
 
         if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(QuotaError::ApiError(format!(
-                "fetchAvailableModels failed ({}): {}",
-                status, error_text
-            )));
+            let error_text = error_body("gemini", response).await;
+            return Err(QuotaError::HttpError {
+                status: status.as_u16(),
+                message: format!("fetchAvailableModels failed ({}): {}", status, error_text),
+            });
         }
 
-        let result: FetchAvailableModelsResponse = response.json().await?;
+        let result: FetchAvailableModelsResponse = decode_json("gemini", response, self.dump_body_on_error).await?;
         Ok(result)
     }
 
@@ -241,7 +410,7 @@ impl GeminiProvider {
             eprintln!("[gemini] Fetching quota for {}", account.email);
         }
 
-        let access_token = self.refresh_access_token(&account.refresh_token, verbose).await?;
+        let access_token = self.access_token(&account.refresh_token, verbose).await?;
 
         // Get project ID - either from account or from loadCodeAssist
         let project_id = account.project_id.clone()
@@ -263,19 +432,18 @@ impl GeminiProvider {
 
         // Quota buckets: group models that share the same quota
         // Each bucket tracks: (remaining_percent, reset_time)
-        let mut claude_bucket: Option<(f64, Option<DateTime<Utc>>)> = None;
-        let mut gemini_flash_bucket: Option<(f64, Option<DateTime<Utc>>)> = None;
-        let mut gemini_3_pro_bucket: Option<(f64, Option<DateTime<Utc>>)> = None;
-        let mut gemini_3_pro_image_bucket: Option<(f64, Option<DateTime<Utc>>)> = None;
+        let mut buckets: Vec<Option<(f64, Option<DateTime<Utc>>)>> = vec![None; self.rules.buckets.len()];
+
+        // Per-model quotas, before bucketing
+        let mut all_models: Vec<GeminiModelQuota> = Vec::new();
 
         if let Some(models_map) = models_response.models {
             for (model_key, info) in models_map {
                 if let Some(quota_info) = info.quota_info {
                     let display_name = info.display_name.unwrap_or_else(|| model_key.clone());
-                    let lower_name = display_name.to_lowercase();
 
                     // Filter out internal/test models
-                    if lower_name.starts_with("chat_") || lower_name.starts_with("rev19") {
+                    if self.rules.is_hidden(&display_name) {
                         continue;
                     }
 
@@ -283,25 +451,20 @@ impl GeminiProvider {
                         .clamp(0.0, 1.0);
                     let remaining_percent = remaining_fraction * 100.0;
 
-                    let reset_time = quota_info.reset_time
-                        .and_then(|t| t.parse::<DateTime<Utc>>().ok())
-                        .or_else(|| Some(now + chrono::Duration::days(1)));
-
-                    // Categorize into quota buckets
-                    let bucket = if lower_name.contains("claude") || lower_name.contains("gpt-oss") {
-                        Some(&mut claude_bucket)
-                    } else if lower_name.contains("gemini 3 pro image") {
-                        Some(&mut gemini_3_pro_image_bucket)
-                    } else if lower_name.contains("gemini 3 pro") {
-                        Some(&mut gemini_3_pro_bucket)
-                    } else if lower_name.contains("gemini") && lower_name.contains("flash") && !lower_name.contains("2.5") {
-                        Some(&mut gemini_flash_bucket)
-                    } else {
-                        // Skip hidden models (Gemini 2.5 variants, tab_flash_lite_preview, etc.)
-                        None
-                    };
+                    let parsed_reset = quota_info.reset_time
+                        .and_then(|t| t.parse::<DateTime<Utc>>().ok());
+
+                    all_models.push(GeminiModelQuota::new(
+                        display_name.clone(),
+                        remaining_percent,
+                        parsed_reset,
+                    ));
+
+                    let reset_time = parsed_reset.or_else(|| Some(now + chrono::Duration::days(1)));
 
-                    if let Some(bucket) = bucket {
+                    // Categorize into quota buckets; models matching no bucket (Gemini 2.5 variants,
+                    // tab_flash_lite_preview, etc.) are only listed with --detailed
+                    if let Some(bucket) = self.rules.bucket_for(&display_name).map(|i| &mut buckets[i]) {
                         // Update bucket with worst-case (minimum remaining, earliest reset)
                         match bucket {
                             Some((existing_pct, existing_reset)) => {
@@ -323,66 +486,52 @@ impl GeminiProvider {
             }
         }
 
-        // Convert buckets to model entries
-        let mut models: Vec<GeminiModelQuota> = Vec::new();
+        // Convert buckets to model entries, in the order the rules list them
+        let models: Vec<GeminiModelQuota> = self
+            .rules
+            .buckets
+            .iter()
+            .zip(buckets)
+            .filter_map(|(rule, bucket)| {
+                bucket.map(|(remaining, reset)| GeminiModelQuota::new(rule.name.clone(), remaining, reset))
+            })
+            .collect();
 
-        if let Some((remaining, reset)) = claude_bucket {
-            models.push(GeminiModelQuota {
-                model: "Claude Models".to_string(),
-                remaining_percent: remaining,
-                reset_time: reset,
-            });
-        }
-
-        if let Some((remaining, reset)) = gemini_flash_bucket {
-            models.push(GeminiModelQuota {
-                model: "Gemini Flash".to_string(),
-                remaining_percent: remaining,
-                reset_time: reset,
-            });
-        }
-
-        if let Some((remaining, reset)) = gemini_3_pro_bucket {
-            models.push(GeminiModelQuota {
-                model: "Gemini 3 Pro".to_string(),
-                remaining_percent: remaining,
-                reset_time: reset,
-            });
-        }
-
-        if let Some((remaining, reset)) = gemini_3_pro_image_bucket {
-            models.push(GeminiModelQuota {
-                model: "Gemini 3 Pro Image".to_string(),
-                remaining_percent: remaining,
-                reset_time: reset,
-            });
-        }
+        // The API returns models in arbitrary order
+        all_models.sort_by(|a, b| a.model.cmp(&b.model));
 
         Ok(GeminiAccountData {
             email: account.email.clone(),
             is_active,
             models,
+            all_models,
+            error: None,
         })
     }
 }
 
-#[async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Provider for GeminiProvider {
     fn name(&self) -> &'static str {
         "gemini"
     }
 
+    fn links(&self) -> ProviderLinks {
+        LINKS
+    }
+
     fn is_configured(&self) -> bool {
-        self.auth_manager
+        self.auth
             .is_provider_configured("gemini")
             .unwrap_or(false)
     }
 
     async fn fetch(&self, timeout: Duration, verbose: bool) -> Result<ProviderData> {
         // Read antigravity accounts
-        let antigravity = self
-            .auth_manager
-            .read_antigravity_accounts()?
+        let source = Arc::clone(&self.auth);
+        let antigravity = run_blocking(move || source.antigravity_accounts())
+            .await?
             .ok_or_else(|| QuotaError::ProviderNotConfigured("gemini (no antigravity accounts found)".to_string()))?;
 
         if antigravity.accounts.is_empty() {
@@ -399,32 +548,59 @@ impl Provider for GeminiProvider {
         let mut account_data: Vec<GeminiAccountData> = Vec::new();
 
         for (idx, account) in antigravity.accounts.iter().enumerate() {
+            if !self.account_selected(&account.email) {
+                if verbose {
+                    eprintln!("[gemini] Skipping {} (not selected by --account)", account.email);
+                }
+                continue;
+            }
             let is_active = idx == antigravity.active_index;
+            if self.active_only && !is_active {
+                if verbose {
+                    eprintln!("[gemini] Skipping inactive account {}", account.email);
+                }
+                continue;
+            }
             match self.fetch_account_quota(account, is_active, timeout, verbose).await {
                 Ok(data) => account_data.push(data),
                 Err(e) => {
                     // Log error but continue with other accounts
-                    let error_str = e.to_string();
+                    let error_str = scrub_secrets(&e.to_string());
                     let (summary, detail) = if let Some(json_start) = error_str.find('{') {
                         let (s, d) = error_str.split_at(json_start);
                         (s.trim(), Some(d.trim()))
                     } else {
                         (error_str.as_str(), None)
                     };
-                    eprintln!(
-                        "\n{} Failed to fetch quota for {}: {}",
-                        "Warning:".yellow().bold(),
-                        account.email.bright_blue(),
-                        summary
-                    );
+                    #[cfg(feature = "cli")]
+                    let (label, email) = ("Warning:".yellow().bold(), account.email.bright_blue());
+                    #[cfg(not(feature = "cli"))]
+                    let (label, email) = ("Warning:", &account.email);
+                    eprintln!("\n{} Failed to fetch quota for {}: {}", label, email, summary);
                     if let Some(d) = detail {
                         eprintln!("    {}", d);
                     }
+
+                    // Keep the account so consumers can see it is degraded
+                    account_data.push(GeminiAccountData {
+                        email: account.email.clone(),
+                        is_active,
+                        models: Vec::new(),
+                        all_models: Vec::new(),
+                        error: Some(summary.trim_end_matches(':').to_string()),
+                    });
                 }
             }
         }
 
-        if account_data.is_empty() {
+        if account_data.is_empty() && !antigravity.accounts.iter().any(|a| self.account_selected(&a.email)) {
+            return Err(QuotaError::ProviderNotConfigured(format!(
+                "gemini (no account matches {})",
+                self.account_patterns.join(", ")
+            )));
+        }
+
+        if account_data.iter().all(|a| a.error.is_some()) {
             return Err(QuotaError::ApiError(
                 "Failed to fetch quota for any Gemini account".to_string(),
             ));
@@ -434,6 +610,47 @@ impl Provider for GeminiProvider {
     }
 }
 
+/// Google access tokens cached in ~/.cache/ocu/gemini-tokens.json
+/// Keyed by a fingerprint of the refresh token they came from, so logging in again invalidates them
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CachedGoogleTokens {
+    tokens: HashMap<String, CachedAccessToken>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedAccessToken {
+    token: String,
+    /// Unix timestamp (seconds)
+    expires_at: i64,
+}
+
+impl CachedAccessToken {
+    /// Still usable for another minute
+    fn is_valid(&self) -> bool {
+        self.expires_at > Utc::now().timestamp() + 60
+    }
+}
+
+impl CachedGoogleTokens {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("gemini-tokens.json")
+    }
+
+    /// Cached tokens, or none when the cache is missing or unreadable
+    fn load(dir: Option<&Path>) -> Self {
+        dir.map(Self::path)
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&self, dir: Option<&Path>) -> Result<()> {
+        let dir = dir.ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))?;
+        // Access tokens grant access to the Google account, so keep them private to the user
+        write_private(&Self::path(dir), &serde_json::to_string(self)?)
+    }
+}
+
 impl Default for GeminiProvider {
     fn default() -> Self {
         Self::new()
@@ -481,3 +698,106 @@ struct CloudCodeQuotaInfo {
     #[serde(rename = "resetTime")]
     reset_time: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AntigravityAccounts, MemoryAuth};
+    use crate::providers::transport::FakeTransport;
+    use tempfile::TempDir;
+
+    const MODELS_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:fetchAvailableModels";
+
+    const MODELS: &str = r#"{
+        "models": {
+            "claude-sonnet": {"displayName": "Claude Sonnet 4.5", "quotaInfo": {"remainingFraction": 0.8, "resetTime": "2030-01-01T00:00:00Z"}},
+            "gemini-flash": {"displayName": "Gemini 3 Flash", "quotaInfo": {"remainingFraction": 0.25}},
+            "gemini-flash-2.5": {"displayName": "Gemini 2.5 Flash", "quotaInfo": {"remainingFraction": 1.0}}
+        }
+    }"#;
+
+    const TOKEN: &str = r#"{"access_token": "google-access", "expires_in": 3600}"#;
+
+    fn account(email: &str, refresh_token: &str) -> AntigravityAccount {
+        AntigravityAccount {
+            email: email.to_string(),
+            refresh_token: refresh_token.to_string(),
+            project_id: Some("project".to_string()),
+            managed_project_id: None,
+            rate_limit_reset_times: None,
+            added_at: None,
+            last_used: None,
+            fingerprint: None,
+        }
+    }
+
+    async fn fetch(transport: FakeTransport, accounts: Vec<AntigravityAccount>) -> (Result<ProviderData>, Vec<String>) {
+        let mut file = AntigravityAccounts::single(accounts[0].clone());
+        file.accounts = accounts;
+        let transport = Arc::new(transport.respond(GOOGLE_TOKEN_URL, 200, TOKEN));
+        let cache = TempDir::new().unwrap();
+        let result = GeminiProvider::new()
+            .with_auth(Arc::new(MemoryAuth::new(None, Some(file))))
+            .with_transport(transport.clone())
+            .with_cache_dir(cache.path())
+            .fetch(Duration::from_secs(5), false)
+            .await;
+        (result, transport.requests())
+    }
+
+    #[tokio::test]
+    async fn groups_models_into_buckets() {
+        let transport = FakeTransport::new().respond(MODELS_URL, 200, MODELS);
+        let (result, requests) = fetch(transport, vec![account("a@example.com", "gemini-buckets")]).await;
+        let ProviderData::Gemini(data) = result.unwrap() else {
+            panic!("expected Gemini data");
+        };
+        let account = &data.accounts[0];
+        assert!(account.is_active && account.error.is_none());
+        let buckets: Vec<(&str, f64)> = account.models.iter().map(|m| (m.model.as_str(), m.remaining_percent)).collect();
+        assert_eq!(buckets, [("Claude Models", 80.0), ("Gemini Flash", 25.0)]);
+        assert_eq!(account.all_models.len(), 3);
+        // The account names its project, so loadCodeAssist is skipped
+        assert_eq!(requests, [format!("POST {}", GOOGLE_TOKEN_URL), format!("POST {}", MODELS_URL)]);
+    }
+
+    #[tokio::test]
+    async fn fails_when_the_only_account_cannot_refresh() {
+        let transport = FakeTransport::new().respond(GOOGLE_TOKEN_URL, 401, r#"{"error": "invalid_grant"}"#);
+        let (result, requests) = fetch(transport, vec![account("a@example.com", "gemini-revoked")]).await;
+        assert!(matches!(result, Err(QuotaError::ApiError(_))));
+        assert_eq!(requests, [format!("POST {}", GOOGLE_TOKEN_URL)]);
+    }
+
+    #[tokio::test]
+    async fn keeps_a_rate_limited_account_as_degraded() {
+        let transport = FakeTransport::new()
+            .respond(MODELS_URL, 200, MODELS)
+            .respond_with_headers(MODELS_URL, 429, &[("retry-after", "3600")], "");
+        let accounts = vec![account("a@example.com", "gemini-ok"), account("b@example.com", "gemini-limited")];
+        let (result, requests) = fetch(transport, accounts).await;
+        let ProviderData::Gemini(data) = result.unwrap() else {
+            panic!("expected Gemini data");
+        };
+        assert!(data.accounts[0].error.is_none());
+        assert!(data.accounts[1].error.as_deref().is_some_and(|e| e.starts_with("rate limited")));
+        assert!(data.accounts[1].models.is_empty());
+        // Retry-After is past the deadline, so the 429 isn't retried
+        assert_eq!(requests.iter().filter(|r| r.ends_with("fetchAvailableModels")).count(), 2);
+    }
+
+    #[tokio::test]
+    async fn names_the_field_of_a_malformed_body() {
+        let body = r#"{"models": {"gemini-flash": {"quotaInfo": {"remainingFraction": "most"}}}}"#;
+        let transport = FakeTransport::new()
+            .respond(MODELS_URL, 200, MODELS)
+            .respond(MODELS_URL, 200, body);
+        let accounts = vec![account("a@example.com", "gemini-fine"), account("b@example.com", "gemini-malformed")];
+        let (result, _) = fetch(transport, accounts).await;
+        let ProviderData::Gemini(data) = result.unwrap() else {
+            panic!("expected Gemini data");
+        };
+        let error = data.accounts[1].error.as_deref().unwrap_or_default();
+        assert!(error.ends_with("at models.gemini-flash.quotaInfo.remainingFraction"), "{}", error);
+    }
+}
@@ -17,22 +17,46 @@
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use crate::error::Result;
+use crate::error::{QuotaError, Result};
+use crate::output::redact::scrub_secrets;
+use events::{emit, EventHook, FetchEvent};
+use timer::Instant;
+use transport::HttpTransport;
 
 pub mod claude;
 pub mod codex;
 pub mod copilot;
+pub mod cost;
+pub mod events;
 pub mod gemini;
+pub mod plans;
+pub mod thresholds;
+/// Sleeps and deadlines for providers; tokio's timer needs its runtime, which a browser
+/// or edge worker doesn't have, so the wasm32 build goes through `setTimeout` instead
+pub mod timer;
+pub mod transport;
+pub mod usage;
+
+pub use cost::{CostModel, TokenPrice};
+pub use thresholds::{ThresholdOverride, Thresholds};
+pub use usage::{UsageWindow, WindowKind};
 
 /// Trait that all providers must implement
-#[async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 pub trait Provider: Send + Sync {
     /// Provider name
     fn name(&self) -> &'static str;
 
+    /// Where users review usage, log in again and compare plans
+    fn links(&self) -> ProviderLinks;
+
     /// Check if this provider is configured (has auth tokens)
     fn is_configured(&self) -> bool;
 
@@ -40,8 +64,302 @@ pub trait Provider: Send + Sync {
     async fn fetch(&self, timeout: Duration, verbose: bool) -> Result<ProviderData>;
 }
 
+/// Fetch with `timeout` as a deadline for the whole provider
+/// Providers making several requests (token refresh, one round trip per Gemini account)
+/// would otherwise take a multiple of the timeout; each request keeps its own bound as well
+pub async fn fetch_with_deadline(provider: &dyn Provider, timeout: Duration, verbose: bool) -> Result<ProviderData> {
+    match timer::timeout(timeout, provider.fetch(timeout, verbose)).await {
+        Ok(result) => result,
+        Err(_) => Err(QuotaError::Timeout(timeout.as_secs())),
+    }
+}
+
+/// How `fetch_all_stream` queries providers
+#[derive(Clone)]
+pub struct FetchOptions {
+    /// Deadline for each provider, covering token refreshes and retries
+    pub timeout: Duration,
+    /// Query every provider at once instead of one after another
+    pub concurrent: bool,
+    /// Log requests and responses to stderr
+    pub verbose: bool,
+    /// Told when each provider starts and finishes; HTTP events come from each
+    /// provider's `with_event_hook`
+    pub on_event: Option<EventHook>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            concurrent: true,
+            verbose: false,
+            on_event: None,
+        }
+    }
+}
+
+/// One provider's outcome, as yielded by `fetch_all_stream`
+#[derive(Debug)]
+pub struct ProviderResult {
+    /// Position of the provider in the slice passed to `fetch_all_stream`
+    pub index: usize,
+    pub provider: &'static str,
+    pub result: Result<ProviderData>,
+}
+
+impl ProviderResult {
+    /// The provider's data, or a `Failed` entry describing the error
+    pub fn into_data(self) -> ProviderData {
+        match self.result {
+            Ok(data) => data,
+            Err(e) => ProviderData::Failed {
+                provider: self.provider.to_string(),
+                error: scrub_secrets(&e.to_string()),
+                rate_limited: e.is_rate_limited(),
+            },
+        }
+    }
+}
+
+/// `Send`, except in the wasm32 build, whose `fetch` futures stay on the page's one thread
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+/// `Send`, except in the wasm32 build, whose `fetch` futures stay on the page's one thread
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// Fetch every provider, yielding each result as soon as it is ready
+/// Concurrent fetches arrive in completion order (sort by `index` to restore provider order),
+/// sequential ones in provider order; dropping the stream cancels outstanding requests
+pub fn fetch_all_stream<'a>(
+    providers: &'a [&'a dyn Provider],
+    options: FetchOptions,
+) -> impl Stream<Item = ProviderResult> + MaybeSend + 'a {
+    if options.concurrent {
+        providers
+            .iter()
+            .enumerate()
+            .map(|(index, provider)| fetch_indexed(index, *provider, options.clone()))
+            .collect::<FuturesUnordered<_>>()
+            .left_stream()
+    } else {
+        futures::stream::iter(providers.iter().enumerate())
+            .then(move |(index, provider)| fetch_indexed(index, *provider, options.clone()))
+            .right_stream()
+    }
+}
+
+async fn fetch_indexed(index: usize, provider: &dyn Provider, options: FetchOptions) -> ProviderResult {
+    let name = provider.name();
+    let hook = options.on_event.as_ref();
+    emit(hook, || FetchEvent::Started { provider: name.to_string() });
+    let started = Instant::now();
+    let result = fetch_with_deadline(provider, options.timeout, options.verbose).await;
+    emit(hook, || FetchEvent::Finished {
+        provider: name.to_string(),
+        ok: result.is_ok(),
+        elapsed: started.elapsed(),
+    });
+    ProviderResult { index, provider: name, result }
+}
+
+/// Send a request through a provider's transport
+pub(crate) async fn send(
+    provider: &str,
+    transport: &dyn HttpTransport,
+    events: Option<&EventHook>,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    execute(provider, transport, events, request.build()?).await
+}
+
+/// Execute a built request, reporting it and its response to the event hook
+async fn execute(
+    provider: &str,
+    transport: &dyn HttpTransport,
+    events: Option<&EventHook>,
+    request: reqwest::Request,
+) -> Result<reqwest::Response> {
+    let url = request.url().to_string();
+    emit(events, || FetchEvent::Request {
+        provider: provider.to_string(),
+        method: request.method().to_string(),
+        url: url.clone(),
+    });
+    let started = Instant::now();
+    let response = transport.execute(request).await?;
+    emit(events, || FetchEvent::Response {
+        provider: provider.to_string(),
+        url,
+        status: response.status().as_u16(),
+        elapsed: started.elapsed(),
+        bytes: response.content_length(),
+    });
+    Ok(response)
+}
+
+/// Send a usage request, honouring rate limits
+/// A 429 whose Retry-After still fits within `timeout` is waited out and retried once;
+/// otherwise it becomes `QuotaError::RateLimited` with the advertised wait
+pub(crate) async fn send_with_retry(
+    provider: &str,
+    transport: &dyn HttpTransport,
+    events: Option<&EventHook>,
+    request: reqwest::RequestBuilder,
+    timeout: Duration,
+    verbose: bool,
+) -> Result<reqwest::Response> {
+    let started = Instant::now();
+    let request = request.build()?;
+    let retry = request.try_clone();
+    let response = execute(provider, transport, events, request).await?;
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Ok(response);
+    }
+
+    let wait = retry_after(response.headers());
+    if verbose {
+        match wait {
+            Some(wait) => eprintln!("[{}] 429 Too Many Requests (retry after {}s)", provider, wait.as_secs()),
+            None => eprintln!("[{}] 429 Too Many Requests", provider),
+        }
+    }
+
+    match (wait, retry) {
+        (Some(wait), Some(retry)) if started.elapsed() + wait < timeout => {
+            emit(events, || FetchEvent::Retry {
+                provider: provider.to_string(),
+                url: retry.url().to_string(),
+                wait,
+            });
+            timer::sleep(wait).await;
+            let response = execute(provider, transport, events, retry).await?;
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+            let wait = retry_after(response.headers());
+            if verbose {
+                eprintln!("[{}] 429 Too Many Requests", provider);
+            }
+            Err(QuotaError::rate_limited(wait))
+        }
+        _ => Err(QuotaError::rate_limited(wait)),
+    }
+}
+
+/// Decode a JSON response body, naming the field that failed to decode
+/// With `dump_body` the raw body is printed to stderr when decoding fails
+pub(crate) async fn decode_json<T: DeserializeOwned>(
+    provider: &str,
+    response: reqwest::Response,
+    dump_body: bool,
+) -> Result<T> {
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or(v).trim().to_ascii_lowercase());
+    let body = read_body(provider, response).await?;
+
+    // Captive portals and proxies answer with HTML pages; name that instead of a serde error
+    // A body that is plainly JSON is accepted whatever its declared type
+    let is_json_type = content_type
+        .as_deref()
+        .is_some_and(|t| t == "application/json" || t.ends_with("+json"));
+    if !is_json_type && !body.trim_start().starts_with(['{', '[']) {
+        if dump_body {
+            eprintln!("[{}] response body:\n{}", provider, scrub_secrets(&body));
+        }
+        return Err(QuotaError::UnexpectedContentType {
+            provider: provider.to_string(),
+            content_type: content_type.unwrap_or_else(|| "no content type".to_string()),
+        });
+    }
+
+    let deserializer = &mut serde_json::Deserializer::from_str(&body);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        if dump_body {
+            eprintln!("[{}] response body:\n{}", provider, scrub_secrets(&body));
+        }
+        QuotaError::decode(provider, e)
+    })
+}
+
+/// Largest response body accepted from a provider; quota responses are a few kilobytes
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Read a response body as text, giving up once it grows past `MAX_RESPONSE_BYTES`
+#[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+async fn read_body(provider: &str, mut response: reqwest::Response) -> Result<String> {
+    let too_large = || QuotaError::ResponseTooLarge {
+        provider: provider.to_string(),
+        limit: MAX_RESPONSE_BYTES,
+    };
+    if response.content_length().is_some_and(|len| len > MAX_RESPONSE_BYTES as u64) {
+        return Err(too_large());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let body = {
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > MAX_RESPONSE_BYTES {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        body
+    };
+    // fetch hands over the body whole, so it can only be checked once read
+    #[cfg(target_arch = "wasm32")]
+    let body = {
+        let body = response.bytes().await?;
+        if body.len() > MAX_RESPONSE_BYTES {
+            return Err(too_large());
+        }
+        body
+    };
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Body of an error response, capped and with credentials scrubbed, for use in error messages
+pub(crate) async fn error_body(provider: &str, response: reqwest::Response) -> String {
+    read_body(provider, response)
+        .await
+        .map(|body| scrub_secrets(&body))
+        .unwrap_or_default()
+}
+
+/// How long a 429 response asks us to wait
+/// Understands `Retry-After` (seconds or HTTP date), GitHub's `x-ratelimit-reset`
+/// (epoch seconds) and Anthropic's `anthropic-ratelimit-requests-reset` (RFC 3339)
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    let until = |at: DateTime<Utc>| Duration::from_secs((at - Utc::now()).num_seconds().max(0) as u64);
+
+    if let Some(value) = header("retry-after") {
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        if let Ok(at) = DateTime::parse_from_rfc2822(value) {
+            return Some(until(at.with_timezone(&Utc)));
+        }
+    }
+    if let Some(reset) = header("x-ratelimit-reset").and_then(|v| v.parse::<i64>().ok()) {
+        return DateTime::from_timestamp(reset, 0).map(until);
+    }
+    header("anthropic-ratelimit-requests-reset")
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|at| until(at.with_timezone(&Utc)))
+}
+
 /// Data returned by any provider
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ProviderData {
     Gemini(GeminiData),
@@ -52,70 +370,271 @@ pub enum ProviderData {
     Failed {
         provider: String,
         error: String,
+        /// The provider answered 429; `error` says when to retry
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        rate_limited: bool,
     },
 }
 
 /// Gemini/Antigravity provider data (supports multiple accounts)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct GeminiData {
     pub accounts: Vec<GeminiAccountData>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct GeminiAccountData {
     pub email: String,
     pub is_active: bool,
+    /// Quota buckets (models that share a quota are grouped)
     pub models: Vec<GeminiModelQuota>,
+    /// Every model reported by the API with its own quota, ungrouped
+    pub all_models: Vec<GeminiModelQuota>,
+    /// Why fetching this account failed; the quota lists are empty when set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl GeminiAccountData {
+    pub fn is_failed(&self) -> bool {
+        self.error.is_some()
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct GeminiModelQuota {
     pub model: String,
     pub remaining_percent: f64,
+    /// Inverse of `remaining_percent`, so JSON carries both views
+    pub used_percent: f64,
     pub reset_time: Option<DateTime<Utc>>,
 }
 
+impl GeminiModelQuota {
+    pub fn new(model: String, remaining_percent: f64, reset_time: Option<DateTime<Utc>>) -> Self {
+        Self {
+            model,
+            remaining_percent,
+            used_percent: 100.0 - remaining_percent,
+            reset_time,
+        }
+    }
+}
+
 /// Codex provider data
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct CodexData {
     pub plan: String,
-    pub primary_window: WindowQuota,
-    pub secondary_window: WindowQuota,
+    /// Missing for plans whose responses omit the window
+    pub primary_window: Option<WindowQuota>,
+    pub secondary_window: Option<WindowQuota>,
+}
+
+impl CodexData {
+    /// The windows present in the response, labelled "Primary" and "Secondary"
+    pub fn windows(&self) -> impl Iterator<Item = (&'static str, &WindowQuota)> {
+        [("Primary", self.primary_window.as_ref()), ("Secondary", self.secondary_window.as_ref())]
+            .into_iter()
+            .filter_map(|(label, window)| window.map(|w| (label, w)))
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct WindowQuota {
     pub used_percent: i32,
+    /// Inverse of `used_percent`, so JSON carries both views
+    pub remaining_percent: i32,
+    /// Seconds until the reset as of the fetch; stored data should go by `resets_at`
     pub resets_in_seconds: i64,
+    /// When the window resets, fixed when the response is parsed
+    pub resets_at: DateTime<Utc>,
+    /// Length of the rolling window, when reported by the API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_seconds: Option<i64>,
+}
+
+impl WindowQuota {
+    /// A window resetting `resets_in_seconds` from now
+    pub fn new(used_percent: i32, resets_in_seconds: i64) -> Self {
+        Self {
+            used_percent,
+            remaining_percent: 100 - used_percent,
+            resets_in_seconds,
+            resets_at: Utc::now() + chrono::Duration::seconds(resets_in_seconds),
+            window_seconds: None,
+        }
+    }
+
+    pub fn with_window_seconds(mut self, window_seconds: Option<i64>) -> Self {
+        self.window_seconds = window_seconds.filter(|&s| s > 0);
+        self
+    }
 }
 
 /// Copilot provider data
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct CopilotData {
     pub plan: String,
     pub premium_entitlement: i64,
     pub premium_remaining: i64,
+    /// Percentage of the premium entitlement consumed (0-100)
+    pub premium_used_percent: i32,
+    /// Premium requests aren't capped (Business/Enterprise plans without a per-user entitlement)
+    #[serde(default)]
+    pub premium_unlimited: bool,
     pub overage_permitted: bool,
     pub overage_count: i64,
+    pub chat: Option<CopilotQuota>,
+    pub completions: Option<CopilotQuota>,
     pub quota_reset_date: String,
 }
 
+impl CopilotData {
+    /// Premium requests beyond the entitlement this period, billed individually when overage
+    /// is permitted: the larger of the reported overage count and the negative remainder
+    pub fn overage_requests(&self) -> i64 {
+        if self.premium_unlimited {
+            0
+        } else {
+            self.overage_count.max(-self.premium_remaining).max(0)
+        }
+    }
+
+    /// Quota reset date as an instant (midnight UTC), if it can be parsed
+    pub fn quota_reset_at(&self) -> Option<DateTime<Utc>> {
+        chrono::NaiveDate::parse_from_str(&self.quota_reset_date, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| dt.and_utc())
+    }
+
+    /// Start of the current monthly quota period (one month before the reset)
+    pub fn quota_period_start(&self) -> Option<DateTime<Utc>> {
+        self.quota_reset_at()
+            .and_then(|t| t.checked_sub_months(chrono::Months::new(1)))
+    }
+}
+
+/// A secondary Copilot quota snapshot (chat, completions)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CopilotQuota {
+    pub entitlement: i64,
+    pub remaining: i64,
+    pub unlimited: bool,
+    /// Percentage of the entitlement consumed, or `None` for unlimited quotas
+    pub used_percent: Option<i32>,
+}
+
+/// Percentage of an entitlement consumed (0-100)
+/// Inverted from the remaining count to align with other providers
+pub fn used_percent_of(entitlement: i64, remaining: i64) -> i32 {
+    if entitlement > 0 {
+        let remaining_fraction = remaining as f64 / entitlement as f64;
+        ((1.0 - remaining_fraction) * 100.0).clamp(0.0, 100.0) as i32
+    } else {
+        0
+    }
+}
+
 /// Claude provider data
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ClaudeData {
     pub five_hour: WindowUsage,
     pub seven_day: WindowUsage,
     pub seven_day_sonnet: Option<WindowUsage>,
     pub seven_day_opus: Option<WindowUsage>,
     pub extra_usage_enabled: bool,
+    /// Extra usage billed this month (USD), when the API reports it
+    #[serde(default)]
+    pub extra_usage_spent_usd: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct WindowUsage {
     pub utilization: f64,
+    /// Inverse of `utilization`, so JSON carries both views
+    pub remaining_percent: f64,
     pub resets_at: Option<DateTime<Utc>>,
 }
 
+impl WindowUsage {
+    pub fn new(utilization: f64, resets_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            utilization,
+            remaining_percent: 100.0 - utilization,
+            resets_at,
+        }
+    }
+}
+
+/// Pages and commands for managing a provider account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderLinks {
+    /// Web page where the provider's usage can be reviewed
+    pub dashboard: &'static str,
+    /// Command that (re)authenticates the provider, e.g. "opencode auth login anthropic"
+    pub login: &'static str,
+    /// Plans and pricing page
+    pub pricing: &'static str,
+}
+
+impl ProviderLinks {
+    /// Links of a provider by name, for callers holding only data (e.g. a `Failed` entry)
+    pub fn for_provider(provider: &str) -> Option<Self> {
+        match provider {
+            "gemini" => Some(gemini::LINKS),
+            "codex" => Some(codex::LINKS),
+            "copilot" => Some(copilot::LINKS),
+            "claude" => Some(claude::LINKS),
+            _ => None,
+        }
+    }
+}
+
+/// Host serving a provider's usage API
+#[doc(hidden)]
+pub fn api_host(provider: &str) -> Option<&'static str> {
+    match provider {
+        "gemini" => Some("cloudcode-pa.googleapis.com"),
+        "codex" => Some("chatgpt.com"),
+        "copilot" => Some("api.github.com"),
+        "claude" => Some("api.anthropic.com"),
+        _ => None,
+    }
+}
+
+/// How far the local clock runs ahead of a host, from the `Date` header of a HEAD request sent over `transport`
+/// Negative when the local clock is behind; `Date` only has one-second resolution
+#[doc(hidden)]
+pub async fn clock_skew(transport: &dyn HttpTransport, host: &str, timeout: Duration) -> Result<chrono::Duration> {
+    let url = reqwest::Url::parse(&format!("https://{}/", host))
+        .map_err(|e| QuotaError::ApiError(format!("invalid host {}: {}", host, e)))?;
+    let mut request = reqwest::Request::new(reqwest::Method::HEAD, url);
+    *request.timeout_mut() = Some(timeout);
+    let response = transport.execute(request).await?;
+    let local = Utc::now();
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .ok_or_else(|| QuotaError::ApiError(format!("{} sent no Date header", host)))?;
+    Ok(local - date.with_timezone(&Utc))
+}
+
+/// Pay-per-use state beyond the plan's included quota
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overage {
+    /// Usage beyond the plan is not allowed
+    Disabled,
+    /// Usage beyond the plan is allowed but none has been billed yet
+    Enabled,
+    /// Requests beyond the plan are being billed; `CostModel::estimate` prices them
+    Billing { requests: i64 },
+    /// Requests went beyond the plan without overage permitted
+    Blocked { requests: i64 },
+}
+
 /// Provider status for display
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ProviderStatus {
@@ -125,6 +644,28 @@ pub enum ProviderStatus {
 }
 
 impl ProviderData {
+    /// Overage state, for providers that bill usage beyond the plan
+    pub fn overage(&self) -> Option<Overage> {
+        match self {
+            ProviderData::Copilot(data) if data.premium_unlimited => Some(Overage::Disabled),
+            ProviderData::Copilot(data) => {
+                let requests = data.overage_requests();
+                Some(match (data.overage_permitted, requests > 0) {
+                    (true, true) => Overage::Billing { requests },
+                    (true, false) => Overage::Enabled,
+                    (false, true) => Overage::Blocked { requests },
+                    (false, false) => Overage::Disabled,
+                })
+            }
+            ProviderData::Claude(data) => Some(if data.extra_usage_enabled {
+                Overage::Enabled
+            } else {
+                Overage::Disabled
+            }),
+            _ => None,
+        }
+    }
+
     /// Get the provider name
     pub fn provider_name(&self) -> &str {
         match self {
@@ -136,43 +677,27 @@ impl ProviderData {
         }
     }
 
-    /// Get display status based on quota levels
+    /// Get display status based on quota levels, under the default thresholds
+    /// A window 80% used is a warning and an exhausted one an error
     pub fn status(&self) -> ProviderStatus {
-        match self {
-            ProviderData::Gemini(data) => {
-                let min_remaining = data.accounts.iter()
-                    .flat_map(|a| a.models.iter())
-                    .map(|m| m.remaining_percent)
-                    .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-                match min_remaining {
-                    Some(remaining) if remaining < 20.0 => ProviderStatus::Warning,
-                    _ => ProviderStatus::Ok,
-                }
-            }
-            ProviderData::Codex(data) => {
-                if data.primary_window.used_percent > 80 || data.secondary_window.used_percent > 80 {
-                    ProviderStatus::Warning
-                } else {
-                    ProviderStatus::Ok
-                }
-            }
-            ProviderData::Copilot(data) => {
-                if data.premium_remaining < 0 {
-                    ProviderStatus::Warning
-                } else if (data.premium_remaining as f64) < (data.premium_entitlement as f64 * 0.2) {
-                    ProviderStatus::Warning
-                } else {
-                    ProviderStatus::Ok
-                }
-            }
-            ProviderData::Claude(data) => {
-                if data.five_hour.utilization > 80.0 || data.seven_day.utilization > 80.0 {
-                    ProviderStatus::Warning
-                } else {
-                    ProviderStatus::Ok
-                }
-            }
-            ProviderData::Failed { .. } => ProviderStatus::Error,
-        }
+        self.status_with(&Thresholds::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transport::FakeTransport;
+
+    #[tokio::test]
+    async fn measures_clock_skew_over_the_transport() {
+        let behind = (Utc::now() - chrono::Duration::hours(1)).to_rfc2822();
+        let transport = FakeTransport::new().respond_with_headers("https://api.example.com/", 200, &[("date", &behind)], "");
+        let skew = clock_skew(&transport, "api.example.com", Duration::from_secs(5)).await.unwrap();
+        assert!((3599..=3601).contains(&skew.num_seconds()), "{}", skew);
+        assert_eq!(transport.requests(), ["HEAD https://api.example.com/"]);
+
+        let transport = FakeTransport::new().respond_with_headers("https://api.example.com/", 200, &[], "");
+        assert!(clock_skew(&transport, "api.example.com", Duration::from_secs(5)).await.is_err());
     }
 }
@@ -0,0 +1,275 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Serialize;
+
+use crate::providers::ProviderData;
+
+/// How much of a quota a plan includes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Amount {
+    Exactly { count: u32 },
+    /// The provider quotes a range, depending on message size and model
+    Range { min: u32, max: u32 },
+    /// The provider quotes a floor, reached with short messages
+    AtLeast { count: u32 },
+    Unlimited,
+    /// The window exists but the provider doesn't say how much it holds
+    Unpublished,
+}
+
+impl Amount {
+    pub fn describe(&self, unit: &str) -> String {
+        match self {
+            Amount::Exactly { count } => format!("{} {}", count, unit),
+            Amount::Range { min, max } => format!("{}-{} {}", min, max, unit),
+            Amount::AtLeast { count } => format!("{}+ {}", count, unit),
+            Amount::Unlimited => "unlimited".to_string(),
+            Amount::Unpublished => "not published".to_string(),
+        }
+    }
+
+    /// The used percentage in the amount's own terms, e.g. "~135 of 300"
+    pub fn used(&self, used_percent: f64) -> Option<String> {
+        let share = |count: u32| (count as f64 * used_percent / 100.0).round() as u64;
+        match *self {
+            Amount::Exactly { count } => Some(format!("~{} of {}", share(count), count)),
+            Amount::Range { min, max } => Some(format!("~{}-{} of {}-{}", share(min), share(max), min, max)),
+            Amount::AtLeast { count } => Some(format!("~{}+ of {}+", share(count), count)),
+            Amount::Unlimited | Amount::Unpublished => None,
+        }
+    }
+}
+
+/// One quota of a plan
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Entitlement {
+    /// Usage window it fills, as labelled in the table ("Premium Requests", "5h Window")
+    pub window: &'static str,
+    pub amount: Amount,
+    /// What is counted, e.g. "premium requests"
+    pub unit: &'static str,
+    /// Period the amount covers, e.g. "month", "5 hours"
+    pub per: &'static str,
+}
+
+/// A plan and the quotas its provider publishes for it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PlanLimits {
+    pub provider: &'static str,
+    pub name: &'static str,
+    /// Plan identifiers the usage API reports for it; empty when the API doesn't report the plan
+    pub ids: &'static [&'static str],
+    /// Usage allowance relative to the provider's base plan, where the provider states one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<u32>,
+    pub entitlements: &'static [Entitlement],
+}
+
+const fn entitlement(window: &'static str, amount: Amount, unit: &'static str, per: &'static str) -> Entitlement {
+    Entitlement {
+        window,
+        amount,
+        unit,
+        per,
+    }
+}
+
+const CODEX_WEEKLY: Entitlement = entitlement("Secondary", Amount::Unpublished, "messages", "week");
+
+/// Published plan quotas; providers revise these without notice, so keep them in step with
+/// the pricing pages (`ProviderLinks::pricing`)
+pub const PLANS: &[PlanLimits] = &[
+    PlanLimits {
+        provider: "copilot",
+        name: "Free",
+        ids: &["free"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Premium Requests", Amount::Exactly { count: 50 }, "premium requests", "month"),
+            entitlement("Chat", Amount::Exactly { count: 50 }, "chat messages", "month"),
+            entitlement("Completions", Amount::Exactly { count: 2000 }, "completions", "month"),
+        ],
+    },
+    PlanLimits {
+        provider: "copilot",
+        name: "Pro",
+        ids: &["individual"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Premium Requests", Amount::Exactly { count: 300 }, "premium requests", "month"),
+            entitlement("Chat", Amount::Unlimited, "chat messages", "month"),
+            entitlement("Completions", Amount::Unlimited, "completions", "month"),
+        ],
+    },
+    PlanLimits {
+        provider: "copilot",
+        name: "Pro+",
+        ids: &["individual_pro"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Premium Requests", Amount::Exactly { count: 1500 }, "premium requests", "month"),
+            entitlement("Chat", Amount::Unlimited, "chat messages", "month"),
+            entitlement("Completions", Amount::Unlimited, "completions", "month"),
+        ],
+    },
+    PlanLimits {
+        provider: "copilot",
+        name: "Business",
+        ids: &["business"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Premium Requests", Amount::Exactly { count: 300 }, "premium requests", "month"),
+            entitlement("Chat", Amount::Unlimited, "chat messages", "month"),
+            entitlement("Completions", Amount::Unlimited, "completions", "month"),
+        ],
+    },
+    PlanLimits {
+        provider: "copilot",
+        name: "Enterprise",
+        ids: &["enterprise"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Premium Requests", Amount::Exactly { count: 1000 }, "premium requests", "month"),
+            entitlement("Chat", Amount::Unlimited, "chat messages", "month"),
+            entitlement("Completions", Amount::Unlimited, "completions", "month"),
+        ],
+    },
+    PlanLimits {
+        provider: "codex",
+        name: "Plus",
+        ids: &["plus"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Primary", Amount::Range { min: 45, max: 225 }, "local messages", "5 hours"),
+            CODEX_WEEKLY,
+        ],
+    },
+    PlanLimits {
+        provider: "codex",
+        name: "Business",
+        ids: &["team", "business"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Primary", Amount::Range { min: 45, max: 225 }, "local messages", "5 hours"),
+            CODEX_WEEKLY,
+        ],
+    },
+    PlanLimits {
+        provider: "codex",
+        name: "Pro",
+        ids: &["pro"],
+        multiplier: None,
+        entitlements: &[
+            entitlement("Primary", Amount::Range { min: 300, max: 1500 }, "local messages", "5 hours"),
+            CODEX_WEEKLY,
+        ],
+    },
+    PlanLimits {
+        provider: "claude",
+        name: "Pro",
+        ids: &[],
+        multiplier: Some(1),
+        entitlements: &[
+            entitlement("5h Window", Amount::AtLeast { count: 45 }, "messages", "5 hours"),
+            entitlement("7d Window", Amount::Range { min: 40, max: 80 }, "hours of Sonnet", "week"),
+        ],
+    },
+    PlanLimits {
+        provider: "claude",
+        name: "Max 5x",
+        ids: &[],
+        multiplier: Some(5),
+        entitlements: &[
+            entitlement("5h Window", Amount::AtLeast { count: 225 }, "messages", "5 hours"),
+            entitlement("7d Window", Amount::Range { min: 140, max: 280 }, "hours of Sonnet", "week"),
+            entitlement("7d Opus", Amount::Range { min: 15, max: 35 }, "hours of Opus", "week"),
+        ],
+    },
+    PlanLimits {
+        provider: "claude",
+        name: "Max 20x",
+        ids: &[],
+        multiplier: Some(20),
+        entitlements: &[
+            entitlement("5h Window", Amount::AtLeast { count: 900 }, "messages", "5 hours"),
+            entitlement("7d Window", Amount::Range { min: 240, max: 480 }, "hours of Sonnet", "week"),
+            entitlement("7d Opus", Amount::Range { min: 24, max: 40 }, "hours of Opus", "week"),
+        ],
+    },
+];
+
+impl ProviderData {
+    /// Plan identifier as the usage API reports it (Codex and Copilot)
+    pub fn reported_plan(&self) -> Option<&str> {
+        match self {
+            ProviderData::Codex(codex) => Some(&codex.plan),
+            ProviderData::Copilot(copilot) => Some(&copilot.plan),
+            _ => None,
+        }
+    }
+}
+
+/// Reference entries for the plan behind `data`: the reported plan's, or every plan
+/// of a provider whose API doesn't report one (Claude); empty when the crate has none
+pub fn plans_for(data: &ProviderData) -> Vec<&'static PlanLimits> {
+    if let ProviderData::Failed { .. } = data {
+        return Vec::new();
+    }
+    let provider = data.provider_name();
+    PLANS
+        .iter()
+        .filter(|plan| plan.provider == provider)
+        .filter(|plan| match data.reported_plan() {
+            Some(reported) => plan.ids.iter().any(|id| id.eq_ignore_ascii_case(reported)),
+            None => plan.ids.is_empty(),
+        })
+        .collect()
+}
+
+/// A plan quota next to the current usage of its window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlanUsage {
+    pub provider: String,
+    pub plan: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<u32>,
+    pub entitlement: Entitlement,
+    /// Used percentage of the matching window, when the provider reported it
+    pub used_percent: Option<f64>,
+}
+
+/// Every known quota of the plan behind `data`, with the current usage of its window
+pub fn plan_usage(data: &ProviderData) -> Vec<PlanUsage> {
+    let windows = data.windows();
+    plans_for(data)
+        .into_iter()
+        .flat_map(|plan| {
+            plan.entitlements.iter().map(|entitlement| PlanUsage {
+                provider: plan.provider.to_string(),
+                plan: plan.name,
+                multiplier: plan.multiplier,
+                entitlement: *entitlement,
+                used_percent: windows
+                    .iter()
+                    .find(|w| w.label == entitlement.window)
+                    .and_then(|w| w.used_percent()),
+            })
+        })
+        .collect()
+}
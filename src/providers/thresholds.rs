@@ -0,0 +1,197 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+
+use crate::error::{QuotaError, Result};
+use crate::glob::matches_filter;
+use crate::providers::{ProviderData, ProviderStatus};
+
+/// Usage levels at which a window turns into a warning or an error,
+/// configurable through the `[thresholds]` config section
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    /// Used percentage from which a window is a warning
+    pub warning: f64,
+    /// Used percentage from which a window is exhausted
+    pub critical: f64,
+    /// Per-window levels; the first entry matching a window wins
+    pub windows: Vec<ThresholdOverride>,
+}
+
+/// One `[[thresholds.windows]]` entry
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdOverride {
+    /// Window name, alone or prefixed by the provider ("7d Opus", "claude 5h*"), like `--model`
+    pub window: String,
+    /// Replaces the global warning level for matching windows
+    pub warning: Option<f64>,
+    /// Replaces the global critical level for matching windows
+    pub critical: Option<f64>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            warning: 80.0,
+            critical: 100.0,
+            windows: Vec::new(),
+        }
+    }
+}
+
+impl Thresholds {
+    /// Warning and critical levels for one window
+    pub fn levels(&self, provider: &str, window: &str) -> (f64, f64) {
+        let qualified = format!("{} {}", provider, window);
+        match self
+            .windows
+            .iter()
+            .find(|o| matches_filter(&o.window, window) || matches_filter(&o.window, &qualified))
+        {
+            Some(o) => (o.warning.unwrap_or(self.warning), o.critical.unwrap_or(self.critical)),
+            None => (self.warning, self.critical),
+        }
+    }
+
+    /// Every level must be a percentage from 0 to 100 and no window's warning level may exceed its
+    /// critical one; the error names the offending key
+    pub fn validate(&self) -> Result<()> {
+        check_level("thresholds.warning", self.warning)?;
+        check_level("thresholds.critical", self.critical)?;
+        if self.warning > self.critical {
+            return Err(QuotaError::ConfigError(format!(
+                "thresholds.warning ({}) must not exceed thresholds.critical ({})",
+                self.warning, self.critical
+            )));
+        }
+        for o in &self.windows {
+            if o.window.trim().is_empty() {
+                return Err(QuotaError::ConfigError("thresholds.windows entries need a window pattern".to_string()));
+            }
+            if let Some(warning) = o.warning {
+                check_level(&format!("thresholds.windows {:?} warning", o.window), warning)?;
+            }
+            if let Some(critical) = o.critical {
+                check_level(&format!("thresholds.windows {:?} critical", o.window), critical)?;
+            }
+            let warning = o.warning.unwrap_or(self.warning);
+            let critical = o.critical.unwrap_or(self.critical);
+            if warning > critical {
+                return Err(QuotaError::ConfigError(format!(
+                    "thresholds.windows {:?}: the warning level ({}) must not exceed the critical level ({})",
+                    o.window, warning, critical
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Status of a window at the given used percentage
+    pub fn status(&self, provider: &str, window: &str, used_percent: f64) -> ProviderStatus {
+        let (warning, critical) = self.levels(provider, window);
+        if used_percent >= critical {
+            ProviderStatus::Error
+        } else if used_percent >= warning {
+            ProviderStatus::Warning
+        } else {
+            ProviderStatus::Ok
+        }
+    }
+}
+
+impl ProviderData {
+    /// Worst status across the provider's windows under the given thresholds
+    /// Failed providers are always an error
+    pub fn status_with(&self, thresholds: &Thresholds) -> ProviderStatus {
+        if let ProviderData::Failed { .. } = self {
+            return ProviderStatus::Error;
+        }
+        let statuses: Vec<_> = self
+            .windows()
+            .iter()
+            .filter_map(|w| w.used_percent().map(|used| thresholds.status(&w.provider, &w.label, used)))
+            .collect();
+        if statuses.contains(&ProviderStatus::Error) {
+            ProviderStatus::Error
+        } else if statuses.contains(&ProviderStatus::Warning) {
+            ProviderStatus::Warning
+        } else {
+            ProviderStatus::Ok
+        }
+    }
+}
+
+/// Levels are used percentages; NaN would never trigger, so it is refused along with anything outside 0..=100
+fn check_level(key: &str, level: f64) -> Result<()> {
+    if (0.0..=100.0).contains(&level) {
+        Ok(())
+    } else {
+        Err(QuotaError::ConfigError(format!(
+            "{} must be a percentage from 0 to 100, got {}",
+            key, level
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(pattern: &str, warning: Option<f64>, critical: Option<f64>) -> ThresholdOverride {
+        ThresholdOverride {
+            window: pattern.to_string(),
+            warning,
+            critical,
+        }
+    }
+
+    #[test]
+    fn validate_rejects_levels_out_of_range_by_key() {
+        let levels = |warning, critical| Thresholds {
+            warning,
+            critical,
+            ..Thresholds::default()
+        };
+        assert!(levels(80.0, 100.0).validate().is_ok());
+        assert!(levels(0.0, 0.0).validate().is_ok());
+        let err = levels(120.0, 50.0).validate().unwrap_err().to_string();
+        assert!(err.contains("thresholds.warning"), "{}", err);
+        let err = levels(80.0, -5.0).validate().unwrap_err().to_string();
+        assert!(err.contains("thresholds.critical"), "{}", err);
+        assert!(levels(f64::NAN, 100.0).validate().is_err());
+        assert!(levels(80.0, f64::INFINITY).validate().is_err());
+        let err = levels(90.0, 85.0).validate().unwrap_err().to_string();
+        assert!(err.contains("must not exceed"), "{}", err);
+    }
+
+    #[test]
+    fn validate_checks_each_window_against_the_global_levels() {
+        let thresholds = |windows| Thresholds {
+            windows,
+            ..Thresholds::default()
+        };
+        assert!(thresholds(vec![window("7d*", Some(50.0), Some(90.0))]).validate().is_ok());
+        let err = thresholds(vec![window("7d*", Some(f64::NAN), None)]).validate().unwrap_err().to_string();
+        assert!(err.contains("\"7d*\" warning"), "{}", err);
+        // The global warning level of 80 applies, and is above this window's critical level
+        assert!(thresholds(vec![window("7d*", None, Some(70.0))]).validate().is_err());
+        assert!(thresholds(vec![window(" ", Some(50.0), None)]).validate().is_err());
+    }
+}
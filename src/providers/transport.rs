@@ -0,0 +1,142 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use async_trait::async_trait;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Mutex;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::QuotaError;
+use crate::error::Result;
+
+/// How providers put their requests on the wire
+/// The default goes over the network with reqwest; `FakeTransport` answers from canned
+/// responses so parsing and error paths can be exercised without live credentials
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait HttpTransport: Send + Sync {
+    async fn execute(&self, request: reqwest::Request) -> Result<reqwest::Response>;
+}
+
+/// Network transport backed by a reqwest client
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Send requests with this client, keeping its proxy, TLS and timeout settings
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl HttpTransport for ReqwestTransport {
+    async fn execute(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        Ok(self.client.execute(request).await?)
+    }
+}
+
+/// Transport that answers from canned responses, matched by URL prefix in the order added
+/// Each response answers one request, so a 401 followed by a 200 for the same URL plays out
+/// in turn; the last matching response keeps answering once the others are used up
+/// Requests without a matching response fail with an API error naming the URL
+/// Not in the wasm32 build, where reqwest only builds responses from `fetch`
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+pub struct FakeTransport {
+    responses: Vec<CannedResponse>,
+    /// Indexes of responses already served
+    served: Mutex<Vec<usize>>,
+    /// "METHOD url" of every request received, in order
+    requests: Mutex<Vec<String>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+struct CannedResponse {
+    url_prefix: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FakeTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests to URLs starting with `url_prefix` with a JSON body
+    pub fn respond(self, url_prefix: &str, status: u16, body: &str) -> Self {
+        self.respond_with_headers(url_prefix, status, &[("content-type", "application/json")], body)
+    }
+
+    /// Answer requests to URLs starting with `url_prefix` with the given headers and body
+    pub fn respond_with_headers(mut self, url_prefix: &str, status: u16, headers: &[(&str, &str)], body: &str) -> Self {
+        self.responses.push(CannedResponse {
+            url_prefix: url_prefix.to_string(),
+            status,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            body: body.to_string(),
+        });
+        self
+    }
+
+    /// Requests received so far, as "METHOD url"
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl HttpTransport for FakeTransport {
+    async fn execute(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        let url = request.url().to_string();
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.push(format!("{} {}", request.method(), url));
+        }
+
+        let matching: Vec<usize> = (0..self.responses.len())
+            .filter(|&i| url.starts_with(&self.responses[i].url_prefix))
+            .collect();
+        let index = {
+            let mut served = self.served.lock().map_err(|_| QuotaError::ApiError("fake transport poisoned".to_string()))?;
+            let index = matching
+                .iter()
+                .copied()
+                .find(|i| !served.contains(i))
+                .or_else(|| matching.last().copied())
+                .ok_or_else(|| QuotaError::ApiError(format!("no canned response for {}", url)))?;
+            served.push(index);
+            index
+        };
+        let canned = &self.responses[index];
+
+        let mut response = http::Response::builder().status(canned.status);
+        for (name, value) in &canned.headers {
+            response = response.header(name, value);
+        }
+        let response = response
+            .body(canned.body.clone())
+            .map_err(|e| QuotaError::ApiError(format!("invalid canned response for {}: {}", url, e)))?;
+        Ok(response.into())
+    }
+}
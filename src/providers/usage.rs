@@ -0,0 +1,152 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::providers::ProviderData;
+
+/// What a usage window measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    /// Short rolling window (Claude 5h, Codex primary)
+    Session,
+    /// Weekly rolling window (Claude 7d, Codex secondary)
+    Weekly,
+    /// Monthly quota period (Copilot)
+    Monthly,
+    /// Quota for one model or model family (Claude 7d Sonnet/Opus, Gemini buckets)
+    Model,
+}
+
+/// One quota window, the same shape for every provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageWindow {
+    pub provider: String,
+    /// Account the window belongs to, for providers with several (Gemini)
+    pub account: Option<String>,
+    /// Window name as shown in the table ("5h Window", "Primary", a Gemini bucket)
+    pub label: String,
+    /// Share of the quota consumed (0.0-1.0, above 1.0 when over the limit), or `None` when unlimited
+    pub used_fraction: Option<f64>,
+    pub resets_at: Option<DateTime<Utc>>,
+    pub kind: WindowKind,
+}
+
+impl UsageWindow {
+    fn new(provider: &str, label: &str, used_percent: f64, resets_at: Option<DateTime<Utc>>, kind: WindowKind) -> Self {
+        Self {
+            provider: provider.to_string(),
+            account: None,
+            label: label.to_string(),
+            used_fraction: Some(used_percent / 100.0),
+            resets_at,
+            kind,
+        }
+    }
+
+    /// Consumed share as a percentage (0-100)
+    pub fn used_percent(&self) -> Option<f64> {
+        self.used_fraction.map(|f| f * 100.0)
+    }
+}
+
+impl ProviderData {
+    /// Every quota window of the provider, in display order
+    /// Failed providers and failed Gemini accounts have none
+    pub fn windows(&self) -> Vec<UsageWindow> {
+        match self {
+            ProviderData::Gemini(gemini) => gemini
+                .accounts
+                .iter()
+                .flat_map(|account| {
+                    account.models.iter().map(|m| UsageWindow {
+                        account: Some(account.email.clone()),
+                        ..UsageWindow::new("gemini", &m.model, 100.0 - m.remaining_percent, m.reset_time, WindowKind::Model)
+                    })
+                })
+                .collect(),
+            ProviderData::Codex(codex) => codex
+                .windows()
+                .map(|(label, w)| {
+                    let kind = if label == "Primary" { WindowKind::Session } else { WindowKind::Weekly };
+                    UsageWindow::new("codex", label, w.used_percent as f64, Some(w.resets_at), kind)
+                })
+                .collect(),
+            ProviderData::Copilot(copilot) => {
+                let resets_at = copilot.quota_reset_at();
+                // Counted from the remaining requests so overage shows above 1.0
+                let premium = if copilot.premium_unlimited {
+                    None
+                } else if copilot.premium_entitlement > 0 {
+                    let used = copilot.premium_entitlement - copilot.premium_remaining;
+                    Some(used as f64 / copilot.premium_entitlement as f64)
+                } else {
+                    Some(copilot.premium_used_percent as f64 / 100.0)
+                };
+                let mut windows = vec![UsageWindow {
+                    used_fraction: premium,
+                    ..UsageWindow::new("copilot", "Premium Requests", 0.0, resets_at, WindowKind::Monthly)
+                }];
+                for (label, quota) in [("Chat", &copilot.chat), ("Completions", &copilot.completions)] {
+                    if let Some(quota) = quota {
+                        windows.push(UsageWindow {
+                            used_fraction: quota.used_percent.map(|p| p as f64 / 100.0),
+                            ..UsageWindow::new("copilot", label, 0.0, resets_at, WindowKind::Monthly)
+                        });
+                    }
+                }
+                windows
+            }
+            ProviderData::Claude(claude) => [
+                ("5h Window", Some(&claude.five_hour), WindowKind::Session),
+                ("7d Window", Some(&claude.seven_day), WindowKind::Weekly),
+                ("7d Sonnet", claude.seven_day_sonnet.as_ref(), WindowKind::Model),
+                ("7d Opus", claude.seven_day_opus.as_ref(), WindowKind::Model),
+            ]
+            .into_iter()
+            .filter_map(|(label, window, kind)| {
+                window.map(|w| UsageWindow::new("claude", label, w.utilization, w.resets_at, kind))
+            })
+            .collect(),
+            ProviderData::Failed { .. } => Vec::new(),
+        }
+    }
+
+    /// Highest used percentage across the provider's windows, ignoring unlimited ones
+    pub fn worst_used_percent(&self) -> Option<f64> {
+        self.windows()
+            .iter()
+            .filter_map(|w| w.used_percent())
+            .reduce(f64::max)
+    }
+
+    /// Earliest reset across the provider's windows
+    pub fn soonest_reset(&self) -> Option<DateTime<Utc>> {
+        self.windows().iter().filter_map(|w| w.resets_at).min()
+    }
+
+    /// The provider failed, or some of its Gemini accounts did
+    pub fn is_degraded(&self) -> bool {
+        match self {
+            ProviderData::Failed { .. } => true,
+            ProviderData::Gemini(gemini) => gemini.accounts.iter().any(|a| a.is_failed()),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,305 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::cache::cache_dir;
+use crate::cli::{ReportFormat, ReportPeriod};
+use crate::error::{QuotaError, Result};
+use crate::export::deliver;
+use crate::history::{UsageHistory, UsageSample};
+use crate::output::redact::{mask_email, redact_text};
+use crate::output::report::{format_report_html, format_report_markdown};
+use crate::providers::transport::ReqwestTransport;
+use crate::providers::Thresholds;
+
+/// `[report]` section: a report `ocu serve` posts every period
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReportConfig {
+    /// "week" or "month": post a report on that span each time it has passed; none when unset
+    pub period: Option<ReportPeriod>,
+    /// Document format, "markdown" (default) or "html"
+    pub format: ReportFormat,
+    /// URL the scheduled reports are POSTed to
+    pub webhook: Option<String>,
+}
+
+impl ReportConfig {
+    /// A schedule needs both a period and a webhook to post to
+    pub fn validate(&self) -> Result<()> {
+        match (&self.period, self.webhook.as_deref()) {
+            (Some(_), None | Some("")) => Err(QuotaError::ConfigError(
+                "report.period needs a report.webhook to post the reports to".to_string(),
+            )),
+            (None, Some(_)) => Err(QuotaError::ConfigError(
+                "report.webhook needs a report.period (\"week\" or \"month\")".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// When the next scheduled report is due, or None without a schedule
+    /// A period after the last one posted; the first call starts the schedule at `now`,
+    /// which is kept across restarts so a server restarted often still reports
+    pub fn next_due(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let period = self.period?;
+        self.webhook.as_ref()?;
+        let mut state = ReportState::load();
+        let last = match state.until {
+            Some(until) => until,
+            None => {
+                state.until = Some(now);
+                // Without a state file the schedule restarts with the server, which is the best left to do
+                let _ = state.store();
+                now
+            }
+        };
+        Some(last + period.duration())
+    }
+
+    /// Post the report on the period ending `until` to the webhook and record it as the last one
+    pub async fn post_scheduled(
+        &self,
+        until: DateTime<Utc>,
+        thresholds: &Thresholds,
+        redact: bool,
+        timeout: Duration,
+    ) -> Result<()> {
+        let (Some(period), Some(webhook)) = (self.period, self.webhook.as_deref()) else {
+            return Ok(());
+        };
+        let mut report = UsageReport::load(period, thresholds, until)?;
+        if redact {
+            report = report.redacted();
+        }
+        let (document, content_type) = report.render(self.format);
+        post_report(webhook, &document, content_type, timeout).await?;
+        ReportState { until: Some(until) }.store()
+    }
+}
+
+/// Where the `[report]` schedule stands, in `~/.cache/ocu/report.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReportState {
+    /// End of the last report posted, or when the schedule started
+    until: Option<DateTime<Utc>>,
+}
+
+impl ReportState {
+    fn default_path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("report.json"))
+    }
+
+    /// The stored state, or an empty one when missing or unreadable
+    fn load() -> Self {
+        Self::default_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&self) -> Result<()> {
+        let path = Self::default_path()
+            .ok_or_else(|| QuotaError::ConfigError("could not determine home directory".to_string()))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// How one window went over the report's period
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSummary {
+    pub provider: String,
+    pub account: Option<String>,
+    pub label: String,
+    /// Used percentage in the last sample
+    pub latest: f64,
+    pub peak: f64,
+    pub peak_at: DateTime<Utc>,
+    pub average: f64,
+    pub samples: usize,
+    /// Samples at or above the window's warning level
+    pub over_warning: usize,
+    /// Samples at or above the window's critical level
+    pub exhausted: usize,
+}
+
+/// Failed queries of one provider over the period
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailureSummary {
+    pub provider: String,
+    pub count: usize,
+    pub last_error: String,
+    pub last_at: DateTime<Utc>,
+}
+
+/// Quota consumption over a period, built from the usage history
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageReport {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Runs recorded in the period
+    pub samples: usize,
+    /// Ordered by provider, account and window
+    pub windows: Vec<WindowSummary>,
+    /// Highest estimated spend beyond the plan per provider
+    pub spend: BTreeMap<String, f64>,
+    pub failures: Vec<FailureSummary>,
+}
+
+impl UsageReport {
+    /// Summarize `samples` (oldest first) taken between `since` and `until`
+    pub fn from_samples(
+        samples: &[UsageSample],
+        thresholds: &Thresholds,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Self {
+        let samples: Vec<&UsageSample> = samples.iter().filter(|s| s.at >= since && s.at <= until).collect();
+
+        let mut windows: BTreeMap<(String, Option<String>, String), (WindowSummary, f64)> = BTreeMap::new();
+        let mut spend: BTreeMap<String, f64> = BTreeMap::new();
+        let mut failures: BTreeMap<String, FailureSummary> = BTreeMap::new();
+        for sample in &samples {
+            for w in &sample.windows {
+                let key = (w.provider.clone(), w.account.clone(), w.label.clone());
+                let (summary, sum) = windows.entry(key).or_insert_with(|| {
+                    (
+                        WindowSummary {
+                            provider: w.provider.clone(),
+                            account: w.account.clone(),
+                            label: w.label.clone(),
+                            latest: 0.0,
+                            peak: f64::MIN,
+                            peak_at: sample.at,
+                            average: 0.0,
+                            samples: 0,
+                            over_warning: 0,
+                            exhausted: 0,
+                        },
+                        0.0,
+                    )
+                });
+                let (warning, critical) = thresholds.levels(&w.provider, &w.label);
+                summary.latest = w.used_percent;
+                if w.used_percent > summary.peak {
+                    summary.peak = w.used_percent;
+                    summary.peak_at = sample.at;
+                }
+                summary.samples += 1;
+                summary.over_warning += usize::from(w.used_percent >= warning);
+                summary.exhausted += usize::from(w.used_percent >= critical);
+                *sum += w.used_percent;
+            }
+            for (provider, &usd) in &sample.cost_usd {
+                let entry = spend.entry(provider.clone()).or_insert(0.0);
+                *entry = entry.max(usd);
+            }
+            for (provider, error) in &sample.failed {
+                let failure = failures.entry(provider.clone()).or_insert_with(|| FailureSummary {
+                    provider: provider.clone(),
+                    count: 0,
+                    last_error: String::new(),
+                    last_at: sample.at,
+                });
+                failure.count += 1;
+                failure.last_error = error.clone();
+                failure.last_at = sample.at;
+            }
+        }
+
+        Self {
+            since,
+            until,
+            samples: samples.len(),
+            windows: windows
+                .into_values()
+                .map(|(mut summary, sum)| {
+                    summary.average = sum / summary.samples as f64;
+                    summary
+                })
+                .collect(),
+            spend,
+            failures: failures.into_values().collect(),
+        }
+    }
+
+    /// The report on the `period` ending at `until`, from the usage history
+    pub fn load(period: ReportPeriod, thresholds: &Thresholds, until: DateTime<Utc>) -> Result<Self> {
+        let since = until - period.duration();
+        let samples = UsageHistory::load_since(Some(since))?;
+        Ok(Self::from_samples(&samples, thresholds, since, until))
+    }
+
+    /// The document in `format`, with its content type
+    pub fn render(&self, format: ReportFormat) -> (String, &'static str) {
+        match format {
+            ReportFormat::Markdown => (format_report_markdown(self), "text/markdown; charset=utf-8"),
+            ReportFormat::Html => (format_report_html(self), "text/html; charset=utf-8"),
+        }
+    }
+
+    /// Mask account emails, in window names and error messages alike
+    pub fn redacted(mut self) -> Self {
+        for window in &mut self.windows {
+            window.account = window.account.as_deref().map(mask_email);
+        }
+        for failure in &mut self.failures {
+            failure.last_error = redact_text(&failure.last_error);
+        }
+        self
+    }
+}
+
+/// Post a rendered report to a webhook, with the format's content type
+pub async fn post_report(url: &str, report: &str, content_type: &str, timeout: Duration) -> Result<()> {
+    let request = reqwest::Client::new()
+        .post(url)
+        .header("content-type", content_type)
+        .body(report.to_string());
+    deliver("report", &ReqwestTransport::default(), request, timeout).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_needs_a_period_and_a_webhook_together() {
+        let config = |period, webhook: Option<&str>| ReportConfig {
+            period,
+            webhook: webhook.map(str::to_string),
+            ..ReportConfig::default()
+        };
+        assert!(config(None, None).validate().is_ok());
+        assert!(config(Some(ReportPeriod::Week), Some("https://example.com/hooks/quota")).validate().is_ok());
+        let err = config(Some(ReportPeriod::Month), None).validate().unwrap_err().to_string();
+        assert!(err.contains("report.webhook"), "{}", err);
+        assert!(config(Some(ReportPeriod::Month), Some("")).validate().is_err());
+        assert!(config(None, Some("https://example.com/hooks/quota")).validate().is_err());
+        let parsed: ReportConfig = toml::from_str("period = \"month\"\nformat = \"html\"\nwebhook = \"https://example.com\"").unwrap();
+        assert_eq!((parsed.period, parsed.format), (Some(ReportPeriod::Month), ReportFormat::Html));
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::error::Result;
+use crate::output::statusline::build_statusline;
+use crate::output::{prepare, FormatOptions};
+use crate::providers::ProviderData;
+use crate::run::{after_fetch, fetch_all, RunOptions};
+
+/// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+/// No data yet, e.g. no provider is configured
+const SERVER_ERROR: i64 = -32000;
+
+/// A request or notification from the editor
+#[derive(Deserialize)]
+struct Message {
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Serve JSON-RPC over stdin/stdout until the editor sends `exit` or closes stdin
+/// tokio keeps a blocking thread waiting on stdin, so exit the process once this returns
+/// rather than waiting for the runtime to shut down
+pub async fn serve(options: RunOptions, format: FormatOptions, interval: Duration) -> Result<()> {
+    serve_io(tokio::io::stdin(), tokio::io::stdout(), options, format, interval).await
+}
+
+/// JSON-RPC 2.0 with LSP-style `Content-Length` framing, as spoken by vscode-jsonrpc and `vim.lsp.rpc`
+/// - `usage/get` (request): the latest statusline document (`text`, `severity`, `details`);
+///   `{"refresh": true}` fetches first
+/// - `usage/changed` (notification to the editor): the same document, sent whenever usage changes
+/// - `initialize`, `shutdown` and `exit` behave as in LSP
+///
+/// Providers are fetched on start and every `interval`
+pub async fn serve_io<R, W>(reader: R, mut writer: W, options: RunOptions, format: FormatOptions, interval: Duration) -> Result<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    // Reading runs on its own task so a slow fetch never stalls the editor's pipe
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        while let Some(body) = read_frame(&mut reader).await {
+            if tx.send(body).is_err() {
+                break;
+            }
+        }
+    });
+
+    // The first fetch completes before any request is answered
+    let mut snapshot = Snapshot::default();
+    if snapshot.refresh(&options, &format).await {
+        write_frame(&mut writer, &json!({ "jsonrpc": "2.0", "method": "usage/changed", "params": snapshot.document })).await?;
+    }
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if snapshot.refresh(&options, &format).await {
+                    write_frame(&mut writer, &json!({ "jsonrpc": "2.0", "method": "usage/changed", "params": snapshot.document })).await?;
+                }
+            }
+            body = rx.recv() => {
+                let Some(body) = body else { return Ok(()) };
+                let message: Message = match serde_json::from_slice(&body) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        write_frame(&mut writer, &error(Value::Null, PARSE_ERROR, &e.to_string())).await?;
+                        continue;
+                    }
+                };
+
+                let response = match message.method.as_str() {
+                    "exit" => return Ok(()),
+                    "initialize" => Some(json!({
+                        "serverInfo": { "name": "ocu", "version": env!("CARGO_PKG_VERSION") },
+                    })),
+                    "shutdown" => Some(Value::Null),
+                    "usage/get" => {
+                        if message.params.get("refresh").and_then(Value::as_bool) == Some(true)
+                            && snapshot.refresh(&options, &format).await
+                        {
+                            write_frame(&mut writer, &json!({ "jsonrpc": "2.0", "method": "usage/changed", "params": snapshot.document })).await?;
+                        }
+                        match &snapshot.error {
+                            Some(e) if snapshot.document.is_null() => {
+                                if let Some(id) = message.id {
+                                    write_frame(&mut writer, &error(id, SERVER_ERROR, e)).await?;
+                                }
+                                continue;
+                            }
+                            _ => Some(snapshot.document.clone()),
+                        }
+                    }
+                    _ => {
+                        if let Some(id) = message.id {
+                            let text = format!("unknown method {}", message.method);
+                            write_frame(&mut writer, &error(id, METHOD_NOT_FOUND, &text)).await?;
+                        }
+                        continue;
+                    }
+                };
+                // Notifications get no response
+                if let (Some(id), Some(result)) = (message.id, response) {
+                    write_frame(&mut writer, &json!({ "jsonrpc": "2.0", "id": id, "result": result })).await?;
+                }
+            }
+        }
+    }
+}
+
+/// The latest fetch, as served to editors and desktop widgets
+#[derive(Default)]
+pub(crate) struct Snapshot {
+    /// Redacted and sorted results
+    pub(crate) data: Vec<ProviderData>,
+    /// Statusline document, or null before the first successful fetch
+    pub(crate) document: Value,
+    /// Why the last fetch produced nothing
+    pub(crate) error: Option<String>,
+    /// Used share of every window, to tell real changes from moving reset times
+    usage: Vec<(String, Option<String>, String, Option<f64>)>,
+}
+
+impl Snapshot {
+    /// Fetch again and pass the results through `after_fetch`; true when usage changed since the previous fetch
+    pub(crate) async fn refresh(&mut self, options: &RunOptions, format: &FormatOptions) -> bool {
+        let outcome = match fetch_all(options.clone()).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return false;
+            }
+        };
+        self.error = None;
+        after_fetch(&outcome, options).await;
+        let data = prepare(&outcome.results, format).into_owned();
+        let usage = usage_of(&data);
+        self.document = serde_json::to_value(build_statusline(&data, format)).unwrap_or_default();
+        self.data = data;
+        if usage == self.usage {
+            return false;
+        }
+        self.usage = usage;
+        true
+    }
+}
+
+fn usage_of(data: &[ProviderData]) -> Vec<(String, Option<String>, String, Option<f64>)> {
+    data.iter()
+        .flat_map(|d| match d {
+            // A failure is a change of its own
+            ProviderData::Failed { provider, .. } => vec![(provider.clone(), None, "failed".to_string(), None)],
+            _ => d
+                .windows()
+                .into_iter()
+                .map(|w| (w.provider, w.account, w.label, w.used_fraction))
+                .collect(),
+        })
+        .collect()
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Read one `Content-Length` framed message body, or None at end of input
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut BufReader<R>) -> Option<Vec<u8>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            // Headers without a length can't be skipped safely; wait for the next frame
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut body = vec![0; length?];
+    reader.read_exact(&mut body).await.ok()?;
+    Some(body)
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
@@ -0,0 +1,358 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use futures::stream::StreamExt;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::auth::run_blocking;
+use crate::budget::{BudgetUsage, SpendHistory};
+use crate::cache::CachedResults;
+use crate::cli::{Exit, ProviderArg};
+use crate::config::Config;
+use crate::error::{ErrorRecord, QuotaError, Result};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export::cloudwatch::CloudWatchExporter;
+use crate::export::{datadog::DatadogExporter, otlp::OtlpExporter, team::TeamPusher};
+use crate::history::{UsageHistory, UsageSample};
+use crate::notify::{notifiers, NotifyState};
+use crate::output::redact::{redact_providers, redact_text};
+use crate::output::FormatOptions;
+use crate::providers::claude::ClaudeProvider;
+use crate::providers::codex::CodexProvider;
+use crate::providers::copilot::CopilotProvider;
+use crate::providers::events::{EventHook, RequestTiming, TimingRecorder};
+use crate::providers::gemini::GeminiProvider;
+use crate::providers::{fetch_all_stream, FetchOptions, Provider, ProviderData, ProviderStatus, Thresholds};
+
+/// What `fetch_all` queries and how, mirroring the `ocu` command line
+#[derive(Clone, Default)]
+pub struct RunOptions {
+    /// Providers to query; empty (or `All`) queries every provider
+    pub providers: Vec<ProviderArg>,
+    /// Gemini account and identity settings come from here, and `after_fetch` takes its
+    /// thresholds, budgets, notification targets and exporters from it
+    pub config: Config,
+    /// Only query Gemini accounts matching these patterns
+    pub accounts: Vec<String>,
+    /// Only query the active Gemini account
+    pub gemini_active_only: bool,
+    /// Print raw response bodies that fail to decode
+    pub dump_body_on_error: bool,
+    /// Mask account emails in error messages
+    pub redact: bool,
+    /// Timeout, concurrency and verbosity; `on_event` also receives every provider's HTTP events
+    pub fetch: FetchOptions,
+}
+
+/// Everything a run produced, in provider order
+#[derive(Debug)]
+pub struct RunOutcome {
+    /// One entry per queried provider; failures are `ProviderData::Failed`
+    pub results: Vec<ProviderData>,
+    /// One record per failed provider, with scrubbed (and, under `redact`, masked) messages
+    pub errors: Vec<ErrorRecord>,
+    /// Latency, status and size of every HTTP request
+    pub timings: Vec<RequestTiming>,
+    /// Number of configured providers that were queried
+    pub queried: usize,
+    /// A failed provider needs a new `opencode auth login`
+    pub auth_expired: bool,
+    /// The run was stopped before every provider answered
+    pub interrupted: bool,
+}
+
+impl RunOutcome {
+    /// No provider returned data
+    pub fn all_failed(&self) -> bool {
+        self.results.iter().all(|r| matches!(r, ProviderData::Failed { .. }))
+    }
+
+    /// Exit code for the run; the most actionable cause wins:
+    /// an interrupt, then a login to redo, then failures, then usage past the thresholds
+    pub fn exit(&self, thresholds: &Thresholds) -> Exit {
+        if self.interrupted {
+            Exit::Interrupted
+        } else if self.auth_expired {
+            Exit::AuthExpired
+        } else if self.all_failed() {
+            Exit::AllFailed
+        } else if !self.errors.is_empty() {
+            Exit::Failure
+        } else if self.results.iter().any(|r| r.status_with(thresholds) != ProviderStatus::Ok) {
+            Exit::ThresholdExceeded
+        } else {
+            Exit::Success
+        }
+    }
+}
+
+/// Provider names selected by `--provider`, in display order
+pub fn provider_names(selected: &[ProviderArg]) -> Vec<&'static str> {
+    if selected.is_empty() || selected.contains(&ProviderArg::All) {
+        vec!["gemini", "codex", "copilot", "claude"]
+    } else {
+        selected
+            .iter()
+            .filter_map(|p| match p {
+                ProviderArg::Gemini => Some("gemini"),
+                ProviderArg::Codex => Some("codex"),
+                ProviderArg::Copilot => Some("copilot"),
+                ProviderArg::Claude => Some("claude"),
+                ProviderArg::All => None,
+            })
+            .collect()
+    }
+}
+
+/// Build the selected providers, configured or not, reporting HTTP events to `hook`
+fn build_providers(options: &RunOptions, hook: &EventHook) -> Vec<Box<dyn Provider>> {
+    let config = &options.config;
+    provider_names(&options.providers)
+        .into_iter()
+        .filter_map(|name| -> Option<Box<dyn Provider>> {
+            match name {
+                "gemini" => Some(Box::new(
+                    GeminiProvider::new()
+                        .with_accounts(options.accounts.clone())
+                        .with_active_only(options.gemini_active_only)
+                        .with_rules(config.gemini.clone())
+                        .with_identity(config.client.clone())
+                        .with_event_hook(hook.clone())
+                        .with_dump_body_on_error(options.dump_body_on_error),
+                )),
+                "codex" => Some(Box::new(
+                    CodexProvider::new()
+                        .with_event_hook(hook.clone())
+                        .with_dump_body_on_error(options.dump_body_on_error),
+                )),
+                "copilot" => Some(Box::new(
+                    CopilotProvider::new()
+                        .with_identity(config.client.clone())
+                        .with_event_hook(hook.clone())
+                        .with_dump_body_on_error(options.dump_body_on_error),
+                )),
+                "claude" => Some(Box::new(
+                    ClaudeProvider::new()
+                        .with_event_hook(hook.clone())
+                        .with_dump_body_on_error(options.dump_body_on_error),
+                )),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Query every selected, configured provider and collect the results, errors and timings
+/// Fails with `QuotaError::ProviderNotConfigured` when none of them is configured
+pub async fn fetch_all(options: RunOptions) -> Result<RunOutcome> {
+    fetch_all_until(options, std::future::pending::<()>()).await
+}
+
+/// Like `fetch_all`, but stops waiting once `stop` completes (e.g. on Ctrl-C),
+/// dropping outstanding requests and returning what already arrived as interrupted
+pub async fn fetch_all_until(options: RunOptions, stop: impl Future) -> Result<RunOutcome> {
+    // Timings are always recorded; the caller's hook still hears every event
+    let recorder = TimingRecorder::new();
+    let caller = options.fetch.on_event.clone();
+    let hook: EventHook = {
+        let recorder = recorder.clone();
+        Arc::new(move |event| {
+            recorder.record(event);
+            if let Some(caller) = &caller {
+                caller(event);
+            }
+        })
+    };
+
+    // Checking reads OpenCode's auth files, which may mean waiting out a concurrent write
+    let providers = build_providers(&options, &hook);
+    let (providers, is_configured) = run_blocking(move || {
+        let is_configured: Vec<bool> = providers.iter().map(|p| p.is_configured()).collect();
+        Ok((providers, is_configured))
+    })
+    .await?;
+    let configured: Vec<&dyn Provider> = providers
+        .iter()
+        .zip(is_configured)
+        .filter(|(_, configured)| *configured)
+        .map(|(p, _)| p.as_ref())
+        .collect();
+    if configured.is_empty() {
+        return Err(QuotaError::ProviderNotConfigured(
+            "no AI providers configured; authenticate with `opencode auth login`".to_string(),
+        ));
+    }
+
+    let mut outcomes = Vec::new();
+    let mut interrupted = false;
+    let stream = fetch_all_stream(&configured, options.fetch.clone());
+    tokio::pin!(stream);
+    tokio::pin!(stop);
+    loop {
+        tokio::select! {
+            outcome = stream.next() => match outcome {
+                Some(outcome) => outcomes.push(outcome),
+                None => break,
+            },
+            _ = &mut stop => {
+                interrupted = true;
+                break;
+            }
+        }
+    }
+    // Report in provider order, not completion order
+    outcomes.sort_by_key(|outcome| outcome.index);
+
+    let mut errors = Vec::new();
+    let mut auth_expired = false;
+    for outcome in &outcomes {
+        if let Err(e) = &outcome.result {
+            auth_expired |= e.needs_login();
+            let mut record = ErrorRecord::new(Some(outcome.provider), e);
+            // Error text may name accounts, so it is masked along with the output under `redact`
+            if options.redact {
+                record.message = redact_text(&record.message);
+            }
+            errors.push(record);
+        }
+    }
+
+    Ok(RunOutcome {
+        results: outcomes.into_iter().map(|o| o.into_data()).collect(),
+        errors,
+        timings: recorder.timings(),
+        queried: configured.len(),
+        auth_expired,
+        interrupted,
+    })
+}
+
+/// What every `ocu` run and refresh loop does with fresh results: record them in the usage history
+/// and the month's spend, notify status changes, keep them for `ocu prompt` and hand them to the
+/// exporters and team server configured in `options.config`
+/// Returns the month's spend against the `[budget]` limits; exporter failures are only printed
+pub async fn after_fetch(outcome: &RunOutcome, options: &RunOptions) -> Vec<BudgetUsage> {
+    let config = &options.config;
+    let (timeout, verbose) = (options.fetch.timeout, options.fetch.verbose);
+    let results = &outcome.results;
+    let shared = || if options.redact { redact_providers(results) } else { results.clone() };
+
+    // Record the run for `ocu report`; an interrupted one would look like missing providers
+    if !outcome.interrupted {
+        if let Err(e) = UsageHistory::append(&UsageSample::new(results, &config.cost, chrono::Utc::now())) {
+            if verbose {
+                eprintln!("[history] could not write usage history: {}", e);
+            }
+        }
+    }
+
+    // Track the month's estimated spend; an interrupted run may be missing providers that billed
+    let month = chrono::Local::now().format("%Y-%m").to_string();
+    let mut history = SpendHistory::load();
+    if !outcome.interrupted && results.iter().any(|d| config.cost.estimate(d).is_some()) {
+        history.record(results, &config.cost, &month);
+        if let Err(e) = history.store() {
+            if verbose {
+                eprintln!("[budget] could not write spend history: {}", e);
+            }
+        }
+    }
+    let budgets = config.budget.usage(&history.month(&month));
+
+    // Push status changes since the last run to the configured notification targets
+    let targets = notifiers(config);
+    if !targets.is_empty() && !outcome.interrupted {
+        let notified = shared();
+        let mut state = NotifyState::load();
+        let mut notifications = state.changes(&notified, &config.thresholds);
+        // summary_at was validated when the config was loaded
+        if let Ok(Some(at)) = config.notify.summary_time() {
+            notifications.extend(state.summary(&notified, &config.thresholds, at, chrono::Local::now()));
+        }
+        notifications.extend(state.budget_alerts(&budgets, &month));
+        for notification in notifications {
+            for target in &targets {
+                if let Err(e) = target.send(&notification, timeout).await {
+                    eprintln!("Warning: could not notify via {}: {}", target.name(), e);
+                }
+            }
+        }
+        if let Err(e) = state.store() {
+            if verbose {
+                eprintln!("[notify] could not write state: {}", e);
+            }
+        }
+    }
+
+    // Keep the latest results for `ocu prompt`; a run where everything failed keeps the previous cache,
+    // and so does an interrupted one, which would otherwise drop the providers it didn't reach
+    if !outcome.all_failed() && !outcome.interrupted {
+        if let Err(e) = CachedResults::new(results.clone()).store() {
+            if verbose {
+                eprintln!("[cache] could not write cache: {}", e);
+            }
+        }
+    }
+
+    if let Some(endpoint) = &config.otlp.endpoint {
+        let exporter = OtlpExporter::new(endpoint).with_headers(config.otlp.headers.clone());
+        if verbose {
+            eprintln!("[otlp] POST {}", exporter.url());
+        }
+        if let Err(e) = exporter.export(&shared(), &outcome.timings, timeout).await {
+            eprintln!("Warning: could not export metrics: {}", e);
+        }
+    }
+    if config.datadog.enabled || config.datadog.api_key.is_some() {
+        let submitted = match DatadogExporter::from_config(&config.datadog) {
+            Ok(exporter) => exporter.export(&shared(), &config.thresholds, timeout).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = submitted {
+            eprintln!("Warning: could not submit to Datadog: {}", e);
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(namespace) = &config.cloudwatch.namespace {
+        let exporter = CloudWatchExporter::new(namespace).with_config(&config.cloudwatch);
+        if let Err(e) = exporter.export(&shared(), timeout).await {
+            eprintln!("Warning: could not publish to CloudWatch: {}", e);
+        }
+    }
+    if let Some(url) = &config.team.url {
+        let pushed = match TeamPusher::new(url, &config.team) {
+            Ok(pusher) => {
+                if verbose {
+                    eprintln!("[team] POST {}", pusher.url());
+                }
+                let options = FormatOptions {
+                    redact: options.redact,
+                    cost: config.cost.clone(),
+                    ..Default::default()
+                };
+                pusher.push(results, &options, timeout).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = pushed {
+            eprintln!("Warning: could not push to the team server: {}", e);
+        }
+    }
+
+    budgets
+}
@@ -0,0 +1,159 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Requests with longer headers are cut off; local clients and webhooks send a few hundred bytes
+const MAX_HEAD: u64 = 16 * 1024;
+/// Larger bodies are refused
+const MAX_BODY: usize = 64 * 1024;
+
+/// An HTTP/1.1 request read by the local servers (`ocu serve`, `ocu serve --aggregate` and `ocu bot discord`)
+pub(crate) struct Request {
+    pub(crate) method: String,
+    /// Path without the query string
+    pub(crate) path: String,
+    /// Query string without the `?`, empty when there is none
+    pub(crate) query: String,
+    /// Header names are lowercased
+    headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Request {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the request carries `Authorization: Bearer <token>`; never for an empty token,
+    /// which a request without the header would otherwise match
+    pub(crate) fn has_bearer(&self, token: &str) -> bool {
+        if token.is_empty() {
+            return false;
+        }
+        let sent = self
+            .header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        same_secret(sent, token)
+    }
+
+    /// Whether the request carries `token` as a bearer token or, for browsers, as `?token=<token>`
+    pub(crate) fn has_token(&self, token: &str) -> bool {
+        let in_query = || {
+            form_urlencoded::parse(self.query.as_bytes())
+                .filter(|(key, _)| key == "token")
+                .fold(false, |found, (_, sent)| found | same_secret(&sent, token))
+        };
+        !token.is_empty() && (self.has_bearer(token) || in_query())
+    }
+
+    /// A request as `read_request` would parse it, for handler tests
+    #[cfg(test)]
+    pub(crate) fn new(method: &str, target: &str, headers: &[(&str, &str)]) -> Self {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.to_string(),
+            headers: headers.iter().map(|(k, v)| (k.to_lowercase(), v.to_string())).collect(),
+            body: Vec::new(),
+        }
+    }
+}
+
+/// Compared without returning early, so the response time doesn't reveal how much of a guess matched
+fn same_secret(sent: &str, token: &str) -> bool {
+    sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Read one request; None when the client hung up or sent something that isn't HTTP
+pub(crate) async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream).take(MAX_HEAD);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+    let method = method.to_string();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (path, query) = (path.to_string(), query.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        query,
+        headers,
+        body: Vec::new(),
+    };
+    let length = request
+        .header("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY {
+        return Ok(None);
+    }
+    let mut reader = reader.into_inner();
+    request.body.resize(length, 0);
+    reader.read_exact(&mut request.body).await?;
+    Ok(Some(request))
+}
+
+/// Write a complete response and close the exchange
+pub(crate) async fn respond<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(body).await?;
+    writer.flush().await
+}
+
+/// Bytes of a hex string, as request signatures are sent
+pub(crate) fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
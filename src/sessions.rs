@@ -0,0 +1,351 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::cli::SessionGroup;
+use crate::error::{QuotaError, Result};
+use crate::providers::CostModel;
+
+/// Share of the input price charged for cache reads, as Anthropic and OpenAI bill them
+const CACHE_READ_PRICE_FACTOR: f64 = 0.1;
+
+/// OpenCode's session and message storage (`storage/` under its data directory)
+pub fn storage_dir() -> Option<PathBuf> {
+    crate::paths::opencode_data_dir().map(|dir| dir.join("storage"))
+}
+
+/// OpenCode provider IDs behind an ocu provider name
+pub fn opencode_provider_ids(provider: &str) -> &'static [&'static str] {
+    match provider {
+        "claude" => &["anthropic"],
+        "codex" => &["openai"],
+        "copilot" => &["github-copilot", "github-copilot-enterprise"],
+        "gemini" => &["google"],
+        _ => &[],
+    }
+}
+
+/// Start of a time range: a duration back from now ("7d", "12h", "30m", "2w") or a local date ("2026-10-01")
+pub fn parse_since(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+            .map(|t| t.with_timezone(&Utc))
+            .ok_or_else(|| format!("{} has no local midnight", value));
+    }
+    let invalid = || format!("expected a duration like 7d, 12h or 30m, or a date like 2026-10-01, got {:?}", value);
+    let split = value.len().checked_sub(1).filter(|&i| value.is_char_boundary(i)).ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    // Unsigned, so "-5d" can't reach into the future
+    let amount: i64 = amount.parse::<u32>().map_err(|_| invalid())?.into();
+    let duration = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+    duration
+        .and_then(|duration| Utc::now().checked_sub_signed(duration))
+        .ok_or_else(invalid)
+}
+
+/// Token counts of one assistant message, with the session it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageUsage {
+    pub session_id: String,
+    pub session_title: String,
+    /// Directory the session ran in
+    pub project: String,
+    /// OpenCode provider ID, e.g. "anthropic"
+    pub provider_id: String,
+    pub model_id: String,
+    pub created: DateTime<Utc>,
+    pub tokens: TokenCounts,
+    /// Cost OpenCode recorded at API prices; zero for subscription providers
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TokenCounts {
+    pub input: u64,
+    pub output: u64,
+    pub reasoning: u64,
+    pub cache_read: u64,
+    pub cache_write: u64,
+}
+
+impl TokenCounts {
+    /// Tokens processed afresh; cache reads are left out, as they are a small fraction of the cost
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.reasoning + self.cache_write
+    }
+
+    fn add(&mut self, other: &TokenCounts) {
+        self.input += other.input;
+        self.output += other.output;
+        self.reasoning += other.reasoning;
+        self.cache_read += other.cache_read;
+        self.cache_write += other.cache_write;
+    }
+}
+
+/// Which messages `load_messages` keeps
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// OpenCode provider IDs; empty keeps every provider
+    pub providers: Vec<&'static str>,
+}
+
+impl SessionFilter {
+    fn keeps(&self, message: &MessageUsage) -> bool {
+        self.since.map_or(true, |since| message.created >= since)
+            && self.until.map_or(true, |until| message.created < until)
+            && (self.providers.is_empty() || self.providers.contains(&message.provider_id.as_str()))
+    }
+}
+
+#[derive(Deserialize)]
+struct StoredSession {
+    id: String,
+    #[serde(rename = "projectID", default)]
+    project_id: String,
+    #[serde(default)]
+    directory: Option<String>,
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct StoredProject {
+    worktree: String,
+}
+
+#[derive(Deserialize)]
+struct StoredMessage {
+    role: String,
+    time: StoredTime,
+    #[serde(rename = "providerID", default)]
+    provider_id: String,
+    #[serde(rename = "modelID", default)]
+    model_id: String,
+    #[serde(default)]
+    cost: f64,
+    tokens: Option<StoredTokens>,
+}
+
+#[derive(Deserialize)]
+struct StoredTime {
+    /// Milliseconds since the epoch
+    created: i64,
+}
+
+#[derive(Deserialize)]
+struct StoredTokens {
+    #[serde(default)]
+    input: u64,
+    #[serde(default)]
+    output: u64,
+    #[serde(default)]
+    reasoning: u64,
+    #[serde(default)]
+    cache: StoredCache,
+}
+
+#[derive(Deserialize, Default)]
+struct StoredCache {
+    #[serde(default)]
+    read: u64,
+    #[serde(default)]
+    write: u64,
+}
+
+/// JSON files directly under `dir`, or none when it doesn't exist
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Every assistant message with token counts under an OpenCode `storage` directory that passes `filter`
+/// Files OpenCode is writing or that come from another version are skipped rather than failing the run
+pub fn load_messages(storage: &Path, filter: &SessionFilter) -> Result<Vec<MessageUsage>> {
+    let session_root = storage.join("session");
+    let sessions_by_project = std::fs::read_dir(&session_root).map_err(|e| {
+        QuotaError::ConfigError(format!(
+            "no OpenCode sessions in {}: {}",
+            session_root.display(),
+            e
+        ))
+    })?;
+
+    let worktrees: HashMap<String, String> = json_files(&storage.join("project"))
+        .iter()
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_string_lossy().into_owned();
+            Some((id, read_json::<StoredProject>(path)?.worktree))
+        })
+        .collect();
+
+    let mut messages = Vec::new();
+    for project_dir in sessions_by_project.filter_map(|entry| entry.ok().map(|e| e.path())) {
+        for session in json_files(&project_dir).iter().filter_map(|path| read_json::<StoredSession>(path)) {
+            let project = session
+                .directory
+                .clone()
+                .or_else(|| worktrees.get(&session.project_id).cloned())
+                .unwrap_or_else(|| session.project_id.clone());
+            for path in json_files(&storage.join("message").join(&session.id)) {
+                let Some(message) = read_json::<StoredMessage>(&path) else {
+                    continue;
+                };
+                let (Some(tokens), "assistant") = (message.tokens, message.role.as_str()) else {
+                    continue;
+                };
+                let Some(created) = Utc.timestamp_millis_opt(message.time.created).single() else {
+                    continue;
+                };
+                let usage = MessageUsage {
+                    session_id: session.id.clone(),
+                    session_title: session.title.clone(),
+                    project: project.clone(),
+                    provider_id: message.provider_id,
+                    model_id: message.model_id,
+                    created,
+                    tokens: TokenCounts {
+                        input: tokens.input,
+                        output: tokens.output,
+                        reasoning: tokens.reasoning,
+                        cache_read: tokens.cache.read,
+                        cache_write: tokens.cache.write,
+                    },
+                    cost_usd: message.cost,
+                };
+                if filter.keeps(&usage) {
+                    messages.push(usage);
+                }
+            }
+        }
+    }
+    Ok(messages)
+}
+
+/// Usage attributed to one project, session or model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageTotal {
+    /// Project directory, session title or "provider/model"
+    pub name: String,
+    /// Session ID, when grouping by session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub messages: usize,
+    pub tokens: TokenCounts,
+    /// Percentage of every listed group's tokens (`TokenCounts::total`)
+    pub share_percent: f64,
+    /// Cost OpenCode recorded, or the tokens at the `[cost]` prices when it recorded none
+    /// (subscription usage); None when the model has no known price
+    pub cost_usd: Option<f64>,
+}
+
+/// What a message would cost: OpenCode's own figure, else its tokens at API prices
+fn message_cost(message: &MessageUsage, cost: &CostModel) -> Option<f64> {
+    if message.cost_usd > 0.0 {
+        return Some(message.cost_usd);
+    }
+    let (input, output) = cost.token_price(&message.model_id)?;
+    let t = &message.tokens;
+    let usd = (t.input + t.cache_write) as f64 * input
+        + t.cache_read as f64 * input * CACHE_READ_PRICE_FACTOR
+        + (t.output + t.reasoning) as f64 * output;
+    Some(usd / 1_000_000.0)
+}
+
+/// Sum messages per project, session or model, largest token count first
+pub fn attribute(messages: &[MessageUsage], group: SessionGroup, cost: &CostModel) -> Vec<UsageTotal> {
+    let mut totals: BTreeMap<String, UsageTotal> = BTreeMap::new();
+    for message in messages {
+        let (key, name, session_id) = match group {
+            SessionGroup::Project => (message.project.clone(), message.project.clone(), None),
+            SessionGroup::Session => (
+                message.session_id.clone(),
+                message.session_title.clone(),
+                Some(message.session_id.clone()),
+            ),
+            SessionGroup::Model => {
+                let name = format!("{}/{}", message.provider_id, message.model_id);
+                (name.clone(), name, None)
+            }
+        };
+        let total = totals.entry(key).or_insert_with(|| UsageTotal {
+            name,
+            session_id,
+            messages: 0,
+            tokens: TokenCounts::default(),
+            share_percent: 0.0,
+            cost_usd: Some(0.0),
+        });
+        total.messages += 1;
+        total.tokens.add(&message.tokens);
+        // One unpriced message makes the group's cost unknown rather than understated
+        total.cost_usd = total.cost_usd.zip(message_cost(message, cost)).map(|(a, b)| a + b);
+    }
+
+    let all: u64 = totals.values().map(|t| t.tokens.total()).sum();
+    let mut totals: Vec<UsageTotal> = totals.into_values().collect();
+    for total in &mut totals {
+        if all > 0 {
+            total.share_percent = total.tokens.total() as f64 / all as f64 * 100.0;
+        }
+    }
+    totals.sort_by(|a, b| b.tokens.total().cmp(&a.tokens.total()).then_with(|| a.name.cmp(&b.name)));
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_since_accepts_durations_and_dates() {
+        let week_ago = parse_since("7d").unwrap();
+        assert!((Utc::now() - week_ago - Duration::days(7)).num_seconds().abs() < 5);
+        assert!(parse_since("2026-10-01").is_ok());
+    }
+
+    #[test]
+    fn parse_since_rejects_out_of_range_and_negative_amounts() {
+        for value in ["99999999999999w", "999999999d", "4294967295w", "-5d", "5", "d", "5y", ""] {
+            assert!(parse_since(value).is_err(), "{} should be rejected", value);
+        }
+    }
+}
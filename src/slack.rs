@@ -0,0 +1,142 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use ring::hmac;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use tokio::net::TcpStream;
+
+use crate::cli::OutputFormat;
+use crate::error::{QuotaError, Result};
+use crate::output::{format_output, FormatOptions};
+use crate::providers::ProviderData;
+use crate::server::{decode_hex, respond, Request};
+
+/// Requests signed longer ago than this are refused, so a captured request can't be replayed
+const MAX_AGE_SECS: u64 = 300;
+
+/// `[slack]` section: answer `/ocu` slash commands on `ocu serve`'s `POST /slack`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlackConfig {
+    /// Signing secret of the Slack app; `SLACK_SIGNING_SECRET` is used when unset
+    pub signing_secret: Option<String>,
+}
+
+impl SlackConfig {
+    /// The configured signing secret, falling back to `SLACK_SIGNING_SECRET`
+    pub fn signing_secret(&self) -> Result<String> {
+        self.signing_secret
+            .clone()
+            .or_else(|| std::env::var("SLACK_SIGNING_SECRET").ok().filter(|v| !v.is_empty()))
+            .ok_or_else(|| {
+                QuotaError::ConfigError("no Slack signing secret: set SLACK_SIGNING_SECRET or [slack] signing_secret".to_string())
+            })
+    }
+}
+
+/// Whether a request carries a valid `X-Slack-Signature` for `secret`, signed at most five minutes from `now`
+/// (Unix seconds); see <https://api.slack.com/authentication/verifying-requests-from-slack>
+pub fn verify_signature(secret: &str, timestamp: &str, body: &[u8], signature: &str, now: i64) -> bool {
+    let Ok(signed_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if now.abs_diff(signed_at) > MAX_AGE_SECS {
+        return false;
+    }
+    let Some(tag) = signature.strip_prefix("v0=").and_then(decode_hex) else {
+        return false;
+    };
+    let mut base = format!("v0:{}:", timestamp).into_bytes();
+    base.extend_from_slice(body);
+    hmac::verify(&hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()), &base, &tag).is_ok()
+}
+
+/// The table posted to the channel, in a code block so Slack keeps its alignment
+pub(crate) fn message(data: &[ProviderData], format: &FormatOptions) -> String {
+    format!("```\n{}\n```", format_output(data, OutputFormat::Table, format))
+}
+
+/// Answer a slash command posted to `ocu serve`'s `POST /slack`: `/ocu` or `/ocu usage` posts `table` to the channel
+/// Requests must be signed with `secret`; Slack reaches the server through a reverse proxy terminating HTTPS.
+/// The table comes from the server's last fetch, so replies never wait on the network and stay within
+/// Slack's three-second limit
+pub(crate) async fn answer(stream: &mut TcpStream, request: &Request, secret: &str, table: &str) -> std::io::Result<()> {
+    let timestamp = request.header("x-slack-request-timestamp").unwrap_or_default();
+    let signature = request.header("x-slack-signature").unwrap_or_default();
+    if !verify_signature(secret, timestamp, &request.body, signature, chrono::Utc::now().timestamp()) {
+        return respond(stream, "401 Unauthorized", "text/plain", b"invalid signature").await;
+    }
+
+    let fields: HashMap<String, String> = form_urlencoded::parse(&request.body).into_owned().collect();
+    let command = fields.get("command").map_or("/ocu", String::as_str);
+    let reply = match fields.get("text").map_or("", |t| t.trim()) {
+        "" | "usage" => json!({ "response_type": "in_channel", "text": table }),
+        _ => json!({
+            "response_type": "ephemeral",
+            "text": format!("`{} usage` posts the current AI quota to the channel", command),
+        }),
+    };
+    respond(stream, "200 OK", "application/json", reply.to_string().as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+    const BODY: &[u8] = b"token=xyz&command=%2Focu&text=";
+    const SIGNED_AT: i64 = 1_531_420_618;
+
+    fn sign(body: &[u8]) -> String {
+        let mut base = format!("v0:{}:", SIGNED_AT).into_bytes();
+        base.extend_from_slice(body);
+        let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, SECRET.as_bytes()), &base);
+        let hex: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        format!("v0={}", hex)
+    }
+
+    fn verify(body: &[u8], signature: &str, timestamp: &str, now: i64) -> bool {
+        verify_signature(SECRET, timestamp, body, signature, now)
+    }
+
+    #[test]
+    fn accepts_a_valid_signature() {
+        assert!(verify(BODY, &sign(BODY), &SIGNED_AT.to_string(), SIGNED_AT + 60));
+    }
+
+    #[test]
+    fn rejects_a_tampered_body() {
+        assert!(!verify(b"token=xyz&command=%2Focu&text=all", &sign(BODY), &SIGNED_AT.to_string(), SIGNED_AT));
+    }
+
+    #[test]
+    fn rejects_a_stale_or_far_off_timestamp() {
+        let signature = sign(BODY);
+        assert!(!verify(BODY, &signature, &SIGNED_AT.to_string(), SIGNED_AT + 301));
+        assert!(!verify(BODY, &signature, &i64::MIN.to_string(), SIGNED_AT));
+        assert!(!verify(BODY, &signature, &i64::MAX.to_string(), SIGNED_AT));
+    }
+
+    #[test]
+    fn rejects_a_signature_without_its_version() {
+        let signature = sign(BODY);
+        let bare = signature.strip_prefix("v0=").unwrap();
+        assert!(!verify(BODY, bare, &SIGNED_AT.to_string(), SIGNED_AT));
+    }
+}
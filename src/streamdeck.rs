@@ -0,0 +1,100 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+use crate::output::statusline::build_statusline;
+use crate::output::{prepare, FormatOptions};
+use crate::providers::{ProviderData, ProviderStatus};
+
+/// Everything a Stream Deck plugin needs to draw its keys
+#[derive(Serialize)]
+struct Deck<'a> {
+    /// Summary line, as `--format summary`
+    text: &'a str,
+    /// "ok", "warning" or "error", as `--format statusline`
+    severity: &'a str,
+    buttons: Vec<Button>,
+}
+
+/// One key per provider
+#[derive(Serialize)]
+struct Button {
+    provider: String,
+    /// Key title, e.g. "claude\n85%"
+    title: String,
+    /// Highest used percentage across the provider's windows, null when unknown
+    percent: Option<f64>,
+    /// "ok", "warning", "error" or "failed"
+    status: &'static str,
+    /// Background color for the key
+    color: &'static str,
+}
+
+fn button(data: &ProviderData, format: &FormatOptions) -> Button {
+    let provider = data.provider_name();
+    let percent = data.worst_used_percent();
+    let (status, color) = match data {
+        ProviderData::Failed { .. } => ("failed", "#7f8c8d"),
+        _ => match data.status_with(&format.thresholds) {
+            ProviderStatus::Ok => ("ok", "#27ae60"),
+            ProviderStatus::Warning => ("warning", "#f39c12"),
+            ProviderStatus::Error => ("error", "#c0392b"),
+        },
+    };
+    let title = match percent {
+        Some(percent) => format!("{}\n{:.0}%", provider, percent),
+        None => format!("{}\n?", provider),
+    };
+    Button {
+        provider: provider.to_string(),
+        title,
+        percent,
+        status,
+        color,
+    }
+}
+
+/// The key states for `ocu serve`'s `GET /buttons` and `GET /events`
+pub(crate) fn deck(data: &[ProviderData], format: &FormatOptions) -> String {
+    let data = prepare(data, format);
+    let statusline = build_statusline(&data, format);
+    let deck = Deck {
+        text: &statusline.text,
+        severity: statusline.severity,
+        buttons: data.iter().map(|d| button(d, format)).collect(),
+    };
+    serde_json::to_string(&deck).unwrap_or_default()
+}
+
+/// Stream `deck` as Server-Sent Events: the current state on connect, then every change, until the client leaves
+pub(crate) async fn events(stream: &mut TcpStream, mut deck: watch::Receiver<String>) -> std::io::Result<()> {
+    stream
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n")
+        .await?;
+    loop {
+        let event = format!("data: {}\n\n", *deck.borrow_and_update());
+        stream.write_all(event.as_bytes()).await?;
+        stream.flush().await?;
+        if deck.changed().await.is_err() {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,309 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::error::{QuotaError, Result};
+use crate::output::json::parse_json;
+use crate::output::time::describe_reset;
+use crate::output::FormatOptions;
+use crate::providers::{ProviderData, ProviderStatus};
+use crate::server::{read_request, respond, Request};
+
+/// The latest report pushed by one member
+struct Snapshot {
+    pushed_at: DateTime<Utc>,
+    providers: Vec<ProviderData>,
+}
+
+/// Combined team view, as served by `GET /v1/team`
+#[derive(Serialize)]
+struct Team<'a> {
+    members: Vec<Member<'a>>,
+    /// Every member's windows, most used first within each provider and window
+    windows: Vec<TeamWindow>,
+}
+
+#[derive(Serialize)]
+struct Member<'a> {
+    name: &'a str,
+    pushed_at: DateTime<Utc>,
+    age_seconds: i64,
+    providers: &'a [ProviderData],
+}
+
+#[derive(Serialize)]
+struct TeamWindow {
+    member: String,
+    provider: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+    window: String,
+    /// Null when the window is unlimited
+    used_percent: Option<f64>,
+    resets_at: Option<DateTime<Utc>>,
+    /// "ok", "warning" or "error"
+    status: &'static str,
+}
+
+type Snapshots = Arc<RwLock<BTreeMap<String, Snapshot>>>;
+
+/// Collect reports pushed by team members and serve the combined view on `listener`:
+/// - `POST /v1/snapshots`: a member's `--format json` report, sent with `Authorization: Bearer <token>`
+/// - `GET /v1/team`: every member's latest report, plus all windows side by side
+/// - `GET /`: the same as an HTML page
+///
+/// `members` maps member names to their tokens; the token decides whose report a push is.
+/// The team view holds every member's accounts, so reading it takes a member token or the
+/// `viewer` token, as a bearer token or `?token=` for browsers.
+/// Reports are kept in memory, so a restarted server shows members again as they push.
+/// Runs until the listener fails or the task is dropped
+pub async fn serve_aggregate(
+    listener: TcpListener,
+    members: BTreeMap<String, String>,
+    viewer: Option<String>,
+    format: FormatOptions,
+) -> Result<()> {
+    let snapshots: Snapshots = Arc::default();
+    let members = Arc::new(members);
+    let format = Arc::new(format);
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| QuotaError::ApiError(format!("HTTP server: {}", e)))?;
+        let (snapshots, members, viewer, format) = (snapshots.clone(), members.clone(), viewer.clone(), format.clone());
+        tokio::spawn(async move {
+            // A client that hangs up mid-request needs no answer
+            let _ = handle(stream, &snapshots, &members, viewer.as_deref(), &format).await;
+        });
+    }
+}
+
+/// The member whose token the request carries
+fn member<'a>(request: &Request, members: &'a BTreeMap<String, String>) -> Option<&'a str> {
+    members
+        .iter()
+        .find(|(_, token)| request.has_bearer(token))
+        .map(|(name, _)| name.as_str())
+}
+
+/// Whether the request may read the team view: it carries a member token or the viewer token
+fn can_view(request: &Request, members: &BTreeMap<String, String>, viewer: Option<&str>) -> bool {
+    members.values().map(String::as_str).chain(viewer).any(|token| request.has_token(token))
+}
+
+async fn handle(
+    mut stream: TcpStream,
+    snapshots: &Snapshots,
+    members: &BTreeMap<String, String>,
+    viewer: Option<&str>,
+    format: &FormatOptions,
+) -> std::io::Result<()> {
+    let Some(request) = read_request(&mut stream).await? else {
+        return Ok(());
+    };
+    let error = |message: &str| json!({ "error": message }).to_string();
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/v1/snapshots") => {
+            let Some(name) = member(&request, members) else {
+                let body = error("missing or unknown member token");
+                return respond(&mut stream, "401 Unauthorized", "application/json", body.as_bytes()).await;
+            };
+            let report = match std::str::from_utf8(&request.body).map_err(|e| e.to_string()).and_then(|body| {
+                parse_json(body).map_err(|e| e.to_string())
+            }) {
+                Ok(report) => report,
+                Err(e) => {
+                    let body = error(&format!("not an ocu --format json report: {}", e));
+                    return respond(&mut stream, "400 Bad Request", "application/json", body.as_bytes()).await;
+                }
+            };
+            let snapshot = Snapshot {
+                pushed_at: Utc::now(),
+                providers: report.providers,
+            };
+            if let Ok(mut snapshots) = snapshots.write() {
+                snapshots.insert(name.to_string(), snapshot);
+            }
+            respond(&mut stream, "204 No Content", "text/plain", b"").await
+        }
+        ("GET", "/v1/team" | "/") if !can_view(&request, members, viewer) => {
+            let body = error("missing or unknown token; send a member or viewer token");
+            respond(&mut stream, "401 Unauthorized", "application/json", body.as_bytes()).await
+        }
+        ("GET", "/v1/team") => {
+            let body = match snapshots.read() {
+                Ok(snapshots) => serde_json::to_string(&team(&snapshots, format)).unwrap_or_default(),
+                Err(_) => error("team state unavailable"),
+            };
+            respond(&mut stream, "200 OK", "application/json", body.as_bytes()).await
+        }
+        ("GET", "/") => {
+            let body = match snapshots.read() {
+                Ok(snapshots) => html(&team(&snapshots, format), format),
+                Err(_) => String::new(),
+            };
+            respond(&mut stream, "200 OK", "text/html; charset=utf-8", body.as_bytes()).await
+        }
+        (_, "/v1/snapshots" | "/v1/team" | "/") => respond(&mut stream, "405 Method Not Allowed", "text/plain", b"").await,
+        _ => respond(&mut stream, "404 Not Found", "text/plain", b"").await,
+    }
+}
+
+fn team<'a>(snapshots: &'a BTreeMap<String, Snapshot>, format: &FormatOptions) -> Team<'a> {
+    let now = Utc::now();
+    let members = snapshots
+        .iter()
+        .map(|(name, snapshot)| Member {
+            name,
+            pushed_at: snapshot.pushed_at,
+            age_seconds: (now - snapshot.pushed_at).num_seconds(),
+            providers: &snapshot.providers,
+        })
+        .collect();
+
+    let mut windows: Vec<TeamWindow> = snapshots
+        .iter()
+        .flat_map(|(name, snapshot)| {
+            snapshot.providers.iter().flat_map(move |d| {
+                d.windows().into_iter().map(move |w| {
+                    let used_percent = w.used_percent();
+                    let status = match used_percent.map(|used| format.thresholds.status(&w.provider, &w.label, used)) {
+                        Some(ProviderStatus::Error) => "error",
+                        Some(ProviderStatus::Warning) => "warning",
+                        _ => "ok",
+                    };
+                    TeamWindow {
+                        member: name.clone(),
+                        provider: w.provider,
+                        account: w.account,
+                        window: w.label,
+                        used_percent,
+                        resets_at: w.resets_at,
+                        status,
+                    }
+                })
+            })
+        })
+        .collect();
+    windows.sort_by(|a, b| {
+        (&a.provider, &a.window)
+            .cmp(&(&b.provider, &b.window))
+            .then(b.used_percent.unwrap_or(0.0).total_cmp(&a.used_percent.unwrap_or(0.0)))
+    });
+    Team { members, windows }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A self-contained page: one table row per member window, grouped by provider and window
+fn html(team: &Team, format: &FormatOptions) -> String {
+    let mut rows = String::new();
+    for w in &team.windows {
+        let window = match &w.account {
+            Some(account) => format!("{} ({})", w.window, account),
+            None => w.window.clone(),
+        };
+        let used = w.used_percent.map_or("unlimited".to_string(), |used| format!("{:.0}%", used));
+        let bar = w.used_percent.unwrap_or(0.0).clamp(0.0, 100.0);
+        let resets = w.resets_at.map(|t| describe_reset(t, format)).unwrap_or_default();
+        rows.push_str(&format!(
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td><div class=\"bar\"><span style=\"width:{:.0}%\"></span></div>{}</td><td>{}</td></tr>\n",
+            w.status,
+            escape_html(&w.provider),
+            escape_html(&window),
+            escape_html(&w.member),
+            bar,
+            used,
+            escape_html(&resets)
+        ));
+    }
+    let mut pushed = String::new();
+    for m in &team.members {
+        pushed.push_str(&format!("<li>{}: {}s ago</li>", escape_html(m.name), m.age_seconds));
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>Team AI quota</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+td, th {{ padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: left; }}
+.bar {{ display: inline-block; width: 8em; height: 0.7em; background: #eee; margin-right: 0.5em; }}
+.bar span {{ display: block; height: 100%; background: #27ae60; }}
+.warning .bar span {{ background: #f39c12; }}
+.error .bar span {{ background: #c0392b; }}
+</style></head>
+<body><h1>Team AI quota</h1>
+<table><tr><th>Provider</th><th>Window</th><th>Member</th><th>Used</th><th>Resets</th></tr>
+{}</table>
+<h2>Last reports</h2><ul>{}</ul>
+</body></html>
+"#,
+        rows, pushed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members() -> BTreeMap<String, String> {
+        BTreeMap::from([("alice".to_string(), "alice-token".to_string())])
+    }
+
+    #[test]
+    fn refuses_requests_without_a_known_token() {
+        let anonymous = Request::new("GET", "/v1/team", &[]);
+        assert!(!can_view(&anonymous, &members(), Some("viewer-token")));
+        assert_eq!(member(&anonymous, &members()), None);
+
+        let guessed = Request::new("POST", "/v1/snapshots", &[("Authorization", "Bearer guess")]);
+        assert_eq!(member(&guessed, &members()), None);
+
+        // Empty tokens never match, even if they get past `ServeConfig::validate`
+        let empty = BTreeMap::from([("bob".to_string(), String::new())]);
+        assert_eq!(member(&anonymous, &empty), None);
+        assert!(!can_view(&anonymous, &empty, Some("")));
+    }
+
+    #[test]
+    fn lets_members_push_and_view() {
+        let push = Request::new("POST", "/v1/snapshots", &[("Authorization", "Bearer alice-token")]);
+        assert_eq!(member(&push, &members()), Some("alice"));
+        assert!(can_view(&push, &members(), None));
+    }
+
+    #[test]
+    fn lets_the_viewer_read_but_not_push() {
+        let viewer = Request::new("GET", "/v1/team?token=viewer-token", &[]);
+        assert!(can_view(&viewer, &members(), Some("viewer-token")));
+        let push = Request::new("POST", "/v1/snapshots", &[("Authorization", "Bearer viewer-token")]);
+        assert_eq!(member(&push, &members()), None);
+    }
+}
@@ -0,0 +1,55 @@
+/*
+ * Copyright (C) 2026 João Sena Ribeiro <sena@smux.net>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, CStr};
+
+// Linked by symbol, as a C program would, rather than through the crate's Rust paths
+use opencode_usage_companion as _;
+
+extern "C" {
+    fn ocu_fetch_json(timeout_secs: u32) -> *mut c_char;
+    fn ocu_fetch_summary(timeout_secs: u32) -> *mut c_char;
+    fn ocu_string_free(s: *mut c_char);
+}
+
+/// Take a string returned by the library, freeing it
+fn take(s: *mut c_char) -> String {
+    assert!(!s.is_null());
+    let text = unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned();
+    unsafe { ocu_string_free(s) };
+    text
+}
+
+#[test]
+fn fetches_through_the_c_interface() {
+    // No providers are configured in an empty home, so nothing goes over the network
+    let home = std::env::temp_dir().join(format!("ocu-ffi-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+    for var in ["HOME", "XDG_DATA_HOME", "XDG_CONFIG_HOME", "XDG_CACHE_HOME"] {
+        std::env::set_var(var, &home);
+    }
+    std::env::remove_var("OPENCODE_DATA_DIR");
+
+    let json: serde_json::Value = serde_json::from_str(&take(unsafe { ocu_fetch_json(1) })).unwrap();
+    assert_eq!(json["providers"], serde_json::json!([]));
+    take(unsafe { ocu_fetch_summary(1) });
+    unsafe { ocu_string_free(std::ptr::null_mut()) };
+
+    let _ = std::fs::remove_dir_all(home);
+}